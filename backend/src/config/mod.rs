@@ -2,4 +2,7 @@
 
 pub mod settings;
 
-pub use settings::{OAuthConfig, ConfigError};
+pub use settings::{
+    Settings, OAuthConfig, CacheConfig, CacheBackendKind, LogFormat, ConfigError, RateLimitConfig,
+    RateLimitKey, RateLimitRule, MailConfig, SmtpTls, ProxyConfig, MetricsConfig,
+};
@@ -1,9 +1,220 @@
 //! Application settings and configuration
 
-use std::env;
+use rocket::figment::value::{Dict, Map, Tag, Value};
+use rocket::figment::{Error as FigmentError, Figment, Metadata, Profile, Provider};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::fairings::cors::OriginPattern;
+use crate::services::db_service::redact_uri;
+use crate::utils::ip_network::IpNetwork;
+
+/// Typed application settings
+///
+/// Loaded once at startup by [`Settings::load`]; each layer overrides the previous one:
+/// built-in defaults → `Rocket.toml` (and `ROCKET_*` env) → environment variables → CLI flags.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub database: DatabaseConfig,
+    pub oauth: OAuthConfig,
+    pub cache: CacheConfig,
+    pub revalidation: RevalidationConfig,
+    pub ip2region: Ip2RegionConfig,
+    pub cors: CorsConfig,
+    pub proxy: ProxyConfig,
+    pub rate_limit: RateLimitConfig,
+    pub metrics: MetricsConfig,
+    pub log: LogConfig,
+    pub mail: MailConfig,
+}
+
+/// Environment variables and the settings key each one sets
+const ENV_KEYS: &[(&str, &str)] = &[
+    ("MONGODB_URI", "database.uri"),
+    ("JWT_SECRET", "oauth.jwt_secret"),
+    ("GITHUB_CLIENT_ID", "oauth.github_client_id"),
+    ("GITHUB_CLIENT_SECRET", "oauth.github_client_secret"),
+    ("FRONTEND_URL", "oauth.frontend_url"),
+    ("BACKEND_URL", "oauth.backend_url"),
+    ("TURNSTILE_SECRET", "oauth.turnstile_secret"),
+    ("CACHE_BACKEND", "cache.backend"),
+    ("CACHE_MAX_CAPACITY", "cache.max_capacity"),
+    ("CACHE_TTL_SECONDS", "cache.ttl_seconds"),
+    ("REDIS_URL", "cache.redis_url"),
+    ("CACHE_REDIS_PREFIX", "cache.redis_key_prefix"),
+    ("CACHE_REDIS_CHANNEL", "cache.redis_channel"),
+    ("NEXTJS_URL", "revalidation.nextjs_url"),
+    ("REVALIDATION_SECRET", "revalidation.secret"),
+    ("REVALIDATION_SALT", "revalidation.salt"),
+    ("IP2REGION_V4_DB", "ip2region.v4_db"),
+    ("IP2REGION_V6_DB", "ip2region.v6_db"),
+    ("CORS_ALLOWED_ORIGINS", "cors.allowed_origins"),
+    ("TRUSTED_PROXIES", "proxy.trusted_proxies"),
+    ("METRICS_ENABLED", "metrics.enabled"),
+    ("METRICS_TOKEN", "metrics.token"),
+    ("LOG_FORMAT", "log.format"),
+    ("SMTP_HOST", "mail.smtp_host"),
+    ("SMTP_PORT", "mail.smtp_port"),
+    ("SMTP_USERNAME", "mail.smtp_username"),
+    ("SMTP_PASSWORD", "mail.smtp_password"),
+    ("SMTP_TLS", "mail.smtp_tls"),
+    ("MAIL_FROM", "mail.from"),
+    ("OWNER_EMAIL", "mail.owner_email"),
+    ("MAIL_UNSUBSCRIBE_SECRET", "mail.unsubscribe_secret"),
+];
+
+/// Keys whose environment variable holds a comma separated list
+const LIST_KEYS: &[&str] = &["cors.allowed_origins", "proxy.trusted_proxies"];
+
+/// Environment variable that sets `key`, if any
+fn env_name(key: &str) -> Option<&'static str> {
+    ENV_KEYS.iter().find(|(_, k)| *k == key).map(|(var, _)| *var)
+}
+
+impl Settings {
+    /// Load settings from `Rocket.toml`, the environment and the command line
+    ///
+    /// The merged figment is returned as well so that Rocket is configured from the same layers.
+    pub fn load() -> Result<(Self, Figment), ConfigError> {
+        let figment = rocket::Config::figment()
+            .merge(EnvVars::new(std::env::vars()))
+            .merge(CliArgs::parse(std::env::args().skip(1))?);
+        let settings = Self::from_figment(&figment)?;
+        Ok((settings, figment))
+    }
+
+    /// Extract and validate settings from a figment
+    pub fn from_figment(figment: &Figment) -> Result<Self, ConfigError> {
+        let settings: Self = figment
+            .extract_lossy()
+            .map_err(|e| ConfigError::InvalidConfig(e.to_string()))?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.database.validate()?;
+        self.oauth.validate()?;
+        self.cache.validate()?;
+        self.revalidation.validate()?;
+        self.cors.validate()?;
+        self.proxy.validate()?;
+        self.mail.validate()?;
+        self.metrics.validate()?;
+        self.rate_limit.validate()
+    }
+
+    /// Effective settings with secrets redacted, for the startup log
+    pub fn summary(&self) -> Vec<(String, String)> {
+        let mut lines: Vec<(String, String)> = [
+            ("database.uri", redact_uri(&self.database.uri)),
+            ("oauth.jwt_secret", mask(&self.oauth.jwt_secret)),
+            ("oauth.github_client_id", or_unset(&self.oauth.github_client_id)),
+            ("oauth.github_client_secret", mask(&self.oauth.github_client_secret)),
+            ("oauth.frontend_url", self.oauth.frontend_url.clone()),
+            ("oauth.backend_url", self.oauth.backend_url.clone()),
+            ("oauth.turnstile_secret", mask(&self.oauth.turnstile_secret)),
+            ("cache.backend", format!("{:?}", self.cache.backend).to_lowercase()),
+            ("cache.max_capacity", self.cache.max_capacity.to_string()),
+            ("cache.ttl_seconds", self.cache.ttl_seconds.to_string()),
+            ("cache.redis_url", self.cache.redis_url.as_deref().map_or_else(|| UNSET.to_string(), redact_uri)),
+            ("cache.redis_key_prefix", self.cache.redis_key_prefix.clone()),
+            ("cache.redis_channel", self.cache.redis_channel.clone()),
+            ("revalidation.nextjs_url", self.revalidation.nextjs_url.clone()),
+            ("revalidation.secret", mask(self.revalidation.secret.as_deref().unwrap_or_default())),
+            ("revalidation.salt", mask(&self.revalidation.salt)),
+            ("ip2region.v4_db", self.ip2region.v4_db.clone()),
+            ("ip2region.v6_db", self.ip2region.v6_db.clone()),
+            ("cors.allowed_origins", match self.cors.allowed_origins.is_empty() {
+                true => "<site webUrl/adminUrl only>".to_string(),
+                false => self.cors.allowed_origins.join(", "),
+            }),
+            ("proxy.trusted_proxies", match self.proxy.trusted_proxies.is_empty() {
+                true => "<none, client IP is the socket address>".to_string(),
+                false => self.proxy.trusted_proxies.join(", "),
+            }),
+            ("metrics.enabled", self.metrics.enabled.to_string()),
+            ("metrics.token", mask(self.metrics.token.as_deref().unwrap_or_default())),
+            ("log.format", format!("{:?}", self.log.format).to_lowercase()),
+            ("mail.smtp_host", self.mail.smtp_host.clone().unwrap_or_else(|| "<unset, mail disabled>".to_string())),
+            ("mail.smtp_port", self.mail.port().to_string()),
+            ("mail.smtp_username", self.mail.smtp_username.clone().unwrap_or_else(|| UNSET.to_string())),
+            ("mail.smtp_password", mask(self.mail.smtp_password.as_deref().unwrap_or_default())),
+            ("mail.smtp_tls", format!("{:?}", self.mail.smtp_tls).to_lowercase()),
+            ("mail.from", self.mail.from.clone().unwrap_or_else(|| UNSET.to_string())),
+            ("mail.owner_email", self.mail.owner_email.clone().unwrap_or_else(|| "<site owner reader>".to_string())),
+            ("mail.unsubscribe_secret", mask(self.mail.unsubscribe_secret.as_deref().unwrap_or_default())),
+            ("rate_limit.enabled", self.rate_limit.enabled.to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+
+        for (name, rule) in self.rate_limit.rules() {
+            lines.push((
+                format!("rate_limit.{}", name),
+                format!(
+                    "{} per {}s by {}",
+                    rule.capacity,
+                    rule.period_seconds,
+                    format!("{:?}", rule.key).to_lowercase()
+                ),
+            ));
+        }
+
+        lines
+    }
+}
+
+const UNSET: &str = "<unset>";
+
+/// Hide a secret value, only telling whether it is set
+fn mask(secret: &str) -> String {
+    if secret.is_empty() { UNSET } else { "<redacted>" }.to_string()
+}
+
+fn or_unset(value: &str) -> String {
+    if value.is_empty() { UNSET.to_string() } else { value.to_string() }
+}
+
+fn require_http_url(key: &'static str, value: &str) -> Result<(), ConfigError> {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(key, format!("must be an http(s) URL, got \"{}\"", value)))
+    }
+}
+
+/// MongoDB configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Connection string; the database name is taken from its path
+    pub uri: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            uri: "mongodb://localhost:27017/mx-space".to_string(),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.uri.starts_with("mongodb://") || self.uri.starts_with("mongodb+srv://") {
+            Ok(())
+        } else {
+            Err(ConfigError::invalid("database.uri", "must start with mongodb:// or mongodb+srv://"))
+        }
+    }
+}
 
 /// OAuth configuration structure
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct OAuthConfig {
     pub jwt_secret: String,
     pub github_client_id: String,
@@ -13,39 +224,44 @@ pub struct OAuthConfig {
     pub turnstile_secret: String,
 }
 
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: String::new(),
+            github_client_id: String::new(),
+            github_client_secret: String::new(),
+            frontend_url: "http://localhost:3000".to_string(),
+            backend_url: "http://localhost:8000".to_string(),
+            turnstile_secret: String::new(),
+        }
+    }
+}
+
 impl OAuthConfig {
-    /// Load OAuth configuration from environment variables (fallback)
-    pub fn from_env() -> Result<Self, ConfigError> {
-        let jwt_secret = env::var("JWT_SECRET")
-            .map_err(|_| ConfigError::MissingEnvVar("JWT_SECRET".to_string()))?;
-        
-        if jwt_secret.is_empty() {
-            return Err(ConfigError::InvalidConfig("JWT_SECRET cannot be empty".to_string()));
-        }
-
-        let github_client_id = env::var("GITHUB_CLIENT_ID")
-            .unwrap_or_default();
-        
-        let github_client_secret = env::var("GITHUB_CLIENT_SECRET")
-            .unwrap_or_default();
-        
-        let frontend_url = env::var("FRONTEND_URL")
-            .unwrap_or_else(|_| "http://localhost:3000".to_string());
-
-        let backend_url = env::var("BACKEND_URL")
-            .unwrap_or_else(|_| "http://localhost:8000".to_string());
-
-        let turnstile_secret = env::var("TURNSTILE_SECRET")
-            .unwrap_or_else(|_| "THISISTURNSTILEKEY".to_string());
-
-        Ok(Self {
-            jwt_secret,
-            github_client_id,
-            github_client_secret,
-            frontend_url,
-            backend_url,
-            turnstile_secret,
-        })
+    /// HS256 keys shorter than 256 bits are rejected
+    const MIN_JWT_SECRET_LEN: usize = 32;
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.jwt_secret.is_empty() {
+            return Err(ConfigError::MissingSetting("oauth.jwt_secret"));
+        }
+        if self.jwt_secret.len() < Self::MIN_JWT_SECRET_LEN {
+            return Err(ConfigError::invalid(
+                "oauth.jwt_secret",
+                format!("must be at least {} characters long", Self::MIN_JWT_SECRET_LEN),
+            ));
+        }
+        if self.turnstile_secret.is_empty() {
+            return Err(ConfigError::MissingSetting("oauth.turnstile_secret"));
+        }
+        if self.github_client_id.is_empty() != self.github_client_secret.is_empty() {
+            return Err(ConfigError::invalid(
+                "oauth.github_client_secret",
+                "GitHub client ID and secret must be set together",
+            ));
+        }
+        require_http_url("oauth.frontend_url", &self.frontend_url)?;
+        require_http_url("oauth.backend_url", &self.backend_url)
     }
 
     /// Get GitHub OAuth redirect URI (points to backend)
@@ -59,20 +275,589 @@ impl OAuthConfig {
     }
 }
 
+/// Cache backend kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackendKind {
+    /// Process-local Moka cache (single instance)
+    #[default]
+    Memory,
+    /// Shared Redis cache with pub/sub invalidation (multiple instances)
+    Redis,
+}
+
+/// Cache configuration structure
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub backend: CacheBackendKind,
+    /// Max entries of the in-process cache (the local tier when using Redis)
+    pub max_capacity: u64,
+    pub ttl_seconds: u64,
+    pub redis_url: Option<String>,
+    /// Prefix for every key written to Redis
+    pub redis_key_prefix: String,
+    /// Pub/sub channel used to broadcast invalidations between instances
+    pub redis_channel: String,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            backend: CacheBackendKind::Memory,
+            max_capacity: 10000,
+            ttl_seconds: 3600,
+            redis_url: None,
+            redis_key_prefix: "neo-space:cache:".to_string(),
+            redis_channel: "neo-space:cache:invalidate".to_string(),
+        }
+    }
+}
+
+impl CacheConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_capacity == 0 || self.ttl_seconds == 0 {
+            return Err(ConfigError::InvalidConfig(
+                "cache: max_capacity and ttl_seconds must be greater than 0".to_string(),
+            ));
+        }
+        let has_redis_url = self.redis_url.as_deref().is_some_and(|url| !url.is_empty());
+        if self.backend == CacheBackendKind::Redis && !has_redis_url {
+            return Err(ConfigError::MissingSetting("cache.redis_url"));
+        }
+        Ok(())
+    }
+}
+
+/// Next.js on-demand revalidation (disabled unless `secret` is set)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RevalidationConfig {
+    pub nextjs_url: String,
+    pub secret: Option<String>,
+    /// Signature salt, must match the frontend's `REVALIDATION_SALT`; required with `secret`
+    pub salt: String,
+}
+
+impl Default for RevalidationConfig {
+    fn default() -> Self {
+        Self {
+            nextjs_url: "http://localhost:3000".to_string(),
+            secret: None,
+            salt: String::new(),
+        }
+    }
+}
+
+impl RevalidationConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.secret.as_deref() == Some("") {
+            return Err(ConfigError::invalid("revalidation.secret", "must not be empty; leave it unset to disable revalidation"));
+        }
+        if self.secret.is_some() && self.salt.is_empty() {
+            return Err(ConfigError::MissingSetting("revalidation.salt"));
+        }
+        require_http_url("revalidation.nextjs_url", &self.nextjs_url)
+    }
+}
+
+/// ip2region database paths (relative paths resolve against the working directory)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Ip2RegionConfig {
+    pub v4_db: String,
+    pub v6_db: String,
+}
+
+impl Default for Ip2RegionConfig {
+    fn default() -> Self {
+        Self {
+            v4_db: "data/ip2region_v4.xdb".to_string(),
+            v6_db: "data/ip2region_v6.xdb".to_string(),
+        }
+    }
+}
+
+/// CORS configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to make credentialed requests, in addition to the site options'
+    /// `webUrl` / `adminUrl`: exact origins, `https://*.example.com` or anchored `^...$` regexes
+    pub allowed_origins: Vec<String>,
+}
+
+impl CorsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        for origin in &self.allowed_origins {
+            OriginPattern::parse(origin).map_err(|e| ConfigError::invalid("cors.allowed_origins", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reverse proxies whose `X-Forwarded-For` / `X-Real-IP` headers are trusted
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Proxy addresses or CIDR ranges, e.g. `127.0.0.1`, `10.0.0.0/8`; when empty the
+    /// client IP is always the socket address
+    pub trusted_proxies: Vec<String>,
+}
+
+impl ProxyConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        for proxy in &self.trusted_proxies {
+            IpNetwork::parse(proxy).map_err(|e| ConfigError::invalid("proxy.trusted_proxies", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Prometheus `/metrics` endpoint; not mounted unless `enabled`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    /// Bearer token scrapers must send; without it the endpoint is open to anyone who can reach it
+    pub token: Option<String>,
+}
+
+impl MetricsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.token.as_deref() == Some("") {
+            return Err(ConfigError::invalid("metrics.token", "must not be empty; leave it unset to disable the check"));
+        }
+        Ok(())
+    }
+}
+
+/// Log output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines (local development)
+    #[default]
+    Text,
+    /// One JSON object per line (log collectors)
+    Json,
+}
+
+/// Logging configuration (the filter itself comes from `RUST_LOG`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    pub format: LogFormat,
+}
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (port 587)
+    #[default]
+    Starttls,
+    /// Implicit TLS (port 465)
+    Tls,
+    /// No encryption, for local SMTP catchers such as Mailpit (port 1025)
+    None,
+}
+
+/// Outgoing mail (comment notifications); disabled unless `smtp_host` is set
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MailConfig {
+    pub smtp_host: Option<String>,
+    /// Defaults to the usual port for `smtp_tls`
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_tls: SmtpTls,
+    /// Sender, e.g. `Blog <noreply@example.com>`
+    pub from: Option<String>,
+    /// Recipient of new comment notifications; defaults to the site owner reader's email
+    pub owner_email: Option<String>,
+    /// HMAC key for unsubscribe links, kept apart from the JWT secret so rotating one
+    /// does not affect the other; required with `smtp_host`
+    pub unsubscribe_secret: Option<String>,
+}
+
+impl MailConfig {
+    /// Whether outgoing mail is configured
+    pub fn enabled(&self) -> bool {
+        self.smtp_host.as_deref().is_some_and(|host| !host.is_empty())
+    }
+
+    /// Effective SMTP port
+    pub fn port(&self) -> u16 {
+        self.smtp_port.unwrap_or(match self.smtp_tls {
+            SmtpTls::Starttls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        })
+    }
+
+    /// HMAC-SHA256 keys shorter than 256 bits are rejected
+    const MIN_UNSUBSCRIBE_SECRET_LEN: usize = 32;
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(secret) = &self.unsubscribe_secret {
+            if secret.len() < Self::MIN_UNSUBSCRIBE_SECRET_LEN {
+                return Err(ConfigError::invalid(
+                    "mail.unsubscribe_secret",
+                    format!("must be at least {} characters", Self::MIN_UNSUBSCRIBE_SECRET_LEN),
+                ));
+            }
+        }
+        if !self.enabled() {
+            return Ok(());
+        }
+        let from = self.from.as_deref().ok_or(ConfigError::MissingSetting("mail.from"))?;
+        from.parse::<lettre::message::Mailbox>()
+            .map_err(|e| ConfigError::invalid("mail.from", format!("invalid mailbox \"{}\": {}", from, e)))?;
+        if self.unsubscribe_secret.is_none() {
+            return Err(ConfigError::MissingSetting("mail.unsubscribe_secret"));
+        }
+        if self.smtp_username.is_some() != self.smtp_password.is_some() {
+            return Err(ConfigError::invalid(
+                "mail.smtp_password",
+                "SMTP username and password must be set together",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Which identity a rate limit bucket is keyed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitKey {
+    /// Client IP address
+    Ip,
+    /// Logged-in user (anonymous requests fall back to the client IP)
+    User,
+    /// Both the client IP and the user must have tokens left
+    Both,
+}
+
+/// Token bucket rule for one route group
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitRule {
+    /// Bucket size (max burst)
+    pub capacity: u32,
+    /// Seconds for an empty bucket to refill completely
+    pub period_seconds: u64,
+    pub key: RateLimitKey,
+}
+
+impl RateLimitRule {
+    fn new(capacity: u32, period_seconds: u64, key: RateLimitKey) -> Self {
+        Self { capacity, period_seconds, key }
+    }
+
+    /// Tokens added back per second
+    pub fn refill_per_second(&self) -> f64 {
+        self.capacity as f64 / self.period_seconds.max(1) as f64
+    }
+}
+
+/// Rate limit configuration (`[default.rate_limit]` in Rocket.toml)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// POST /api/comments
+    pub comment_create: RateLimitRule,
+    /// POST/DELETE /api/comments/<id>/reactions/<emoji>
+    pub comment_reaction: RateLimitRule,
+    /// POST /api/ai/time-capsule (may trigger paid LLM calls)
+    pub ai_time_capsule: RateLimitRule,
+    /// POST /api/nbnhhsh/guess
+    pub nbnhhsh: RateLimitRule,
+    /// GET /api/auth/oauth/<provider>/callback
+    pub oauth_callback: RateLimitRule,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            comment_create: RateLimitRule::new(5, 60, RateLimitKey::Both),
+            comment_reaction: RateLimitRule::new(30, 60, RateLimitKey::Both),
+            ai_time_capsule: RateLimitRule::new(10, 3600, RateLimitKey::Ip),
+            nbnhhsh: RateLimitRule::new(30, 60, RateLimitKey::Ip),
+            oauth_callback: RateLimitRule::new(10, 60, RateLimitKey::Ip),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        for (name, rule) in self.rules() {
+            if rule.capacity == 0 || rule.period_seconds == 0 {
+                return Err(ConfigError::InvalidConfig(format!(
+                    "rate_limit.{}: capacity and period_seconds must be greater than 0",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn rules(&self) -> [(&'static str, &RateLimitRule); 5] {
+        [
+            ("comment_create", &self.comment_create),
+            ("comment_reaction", &self.comment_reaction),
+            ("ai_time_capsule", &self.ai_time_capsule),
+            ("nbnhhsh", &self.nbnhhsh),
+            ("oauth_callback", &self.oauth_callback),
+        ]
+    }
+}
+
+/// Provider for the environment variables listed in [`ENV_KEYS`]
+///
+/// Values stay raw strings (numbers and booleans are converted by the lossy extraction),
+/// so a numeric-looking secret is still read as a string. Empty values count as unset.
+struct EnvVars {
+    values: Vec<(&'static str, String)>,
+}
+
+impl EnvVars {
+    fn new(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let vars: HashMap<String, String> = vars.into_iter().collect();
+        let values = ENV_KEYS
+            .iter()
+            .filter_map(|(var, key)| {
+                let value = vars.get(*var)?.trim();
+                (!value.is_empty()).then(|| (*key, value.to_string()))
+            })
+            .collect();
+        Self { values }
+    }
+}
+
+impl Provider for EnvVars {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("environment variable").interpolater(|_, keys| {
+            let key = keys.join(".");
+            env_name(&key).map(str::to_string).unwrap_or(key)
+        })
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, FigmentError> {
+        let mut dict = Dict::new();
+        for (key, value) in &self.values {
+            let value = if LIST_KEYS.contains(key) {
+                Value::from(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>(),
+                )
+            } else {
+                Value::from(value.clone())
+            };
+            insert_nested(&mut dict, key, value);
+        }
+        Ok(Profile::Global.collect(dict))
+    }
+}
+
+/// Provider for `--key=value` / `--key value` command line flags, e.g. `--cache.backend=redis`
+///
+/// Values are parsed like `ROCKET_*` env values, so Rocket's own keys (`--port 9000`) work too.
+struct CliArgs {
+    values: Vec<(String, Value)>,
+}
+
+impl CliArgs {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ConfigError> {
+        let mut args = args.into_iter();
+        let mut values = Vec::new();
+
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                return Err(ConfigError::InvalidConfig(format!(
+                    "unexpected argument \"{}\", expected --key=value",
+                    arg
+                )));
+            };
+            let (key, raw) = match flag.split_once('=') {
+                Some((key, raw)) => (key.to_string(), raw.to_string()),
+                None => {
+                    let raw = args.next().ok_or_else(|| {
+                        ConfigError::InvalidConfig(format!("missing value for --{}", flag))
+                    })?;
+                    (flag.to_string(), raw)
+                }
+            };
+            if key.is_empty() {
+                return Err(ConfigError::InvalidConfig(format!("invalid argument \"{}\"", arg)));
+            }
+
+            let Ok(value) = raw.parse::<Value>();
+            values.push((key, value));
+        }
+
+        Ok(Self { values })
+    }
+}
+
+impl Provider for CliArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("command line").interpolater(|_, keys| format!("--{}", keys.join(".")))
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, FigmentError> {
+        let mut dict = Dict::new();
+        for (key, value) in &self.values {
+            insert_nested(&mut dict, key, value.clone());
+        }
+        Ok(Profile::Global.collect(dict))
+    }
+}
+
+/// Insert `value` at a dotted `key` path, creating intermediate dictionaries
+fn insert_nested(dict: &mut Dict, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            let entry = dict
+                .entry(head.to_string())
+                .or_insert_with(|| Value::Dict(Tag::Default, Dict::new()));
+            if !matches!(entry, Value::Dict(..)) {
+                *entry = Value::Dict(Tag::Default, Dict::new());
+            }
+            if let Value::Dict(_, inner) = entry {
+                insert_nested(inner, rest, value);
+            }
+        }
+        None => {
+            dict.insert(key.to_string(), value);
+        }
+    }
+}
+
 /// Configuration error types
 #[derive(Debug)]
 pub enum ConfigError {
-    MissingEnvVar(String),
+    /// A required setting (usually a secret) is not set
+    MissingSetting(&'static str),
     InvalidConfig(String),
 }
 
+impl ConfigError {
+    pub(crate) fn invalid(key: &'static str, msg: impl std::fmt::Display) -> Self {
+        ConfigError::InvalidConfig(format!("{}{}: {}", key, env_hint(key), msg))
+    }
+}
+
+/// ` (env FOO)` suffix for keys that can be set from an environment variable
+fn env_hint(key: &str) -> String {
+    env_name(key).map(|var| format!(" (env {})", var)).unwrap_or_default()
+}
+
 impl std::fmt::Display for ConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ConfigError::MissingEnvVar(var) => write!(f, "Missing environment variable: {}", var),
+            ConfigError::MissingSetting(key) => {
+                write!(f, "Missing required setting: {}{}", key, env_hint(key))
+            }
             ConfigError::InvalidConfig(msg) => write!(f, "Invalid configuration: {}", msg),
         }
     }
 }
 
 impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JWT_SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn env(vars: &[(&str, &str)]) -> EnvVars {
+        EnvVars::new(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+    }
+
+    fn cli(args: &[&str]) -> CliArgs {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_layers_env_then_cli() {
+        let figment = Figment::from(env(&[
+            ("JWT_SECRET", JWT_SECRET),
+            ("TURNSTILE_SECRET", "12345"),
+            ("CACHE_MAX_CAPACITY", "500"),
+            ("CACHE_TTL_SECONDS", "60"),
+            ("CORS_ALLOWED_ORIGINS", "https://a.example, https://b.example"),
+            ("TRUSTED_PROXIES", "127.0.0.1, 10.0.0.0/8"),
+            ("REVALIDATION_SECRET", ""),
+        ]))
+        .merge(cli(&["--cache.max_capacity=700", "--log.format", "json"]));
+
+        let settings = Settings::from_figment(&figment).unwrap();
+        assert_eq!(settings.oauth.turnstile_secret, "12345");
+        assert_eq!(settings.cache.max_capacity, 700);
+        assert_eq!(settings.cache.ttl_seconds, 60);
+        assert_eq!(settings.cors.allowed_origins, ["https://a.example", "https://b.example"]);
+        assert_eq!(settings.proxy.trusted_proxies, ["127.0.0.1", "10.0.0.0/8"]);
+        assert_eq!(settings.log.format, LogFormat::Json);
+        assert!(settings.revalidation.secret.is_none());
+        assert_eq!(settings.database.uri, "mongodb://localhost:27017/mx-space");
+
+        let summary = format!("{:?}", settings.summary());
+        assert!(!summary.contains(JWT_SECRET));
+        assert!(!summary.contains("12345"));
+    }
+
+    #[test]
+    fn test_missing_or_invalid_settings_fail_fast() {
+        let err = Settings::from_figment(&Figment::from(env(&[("JWT_SECRET", JWT_SECRET)]))).unwrap_err();
+        assert!(err.to_string().contains("TURNSTILE_SECRET"), "{}", err);
+
+        let err = Settings::from_figment(&Figment::from(env(&[
+            ("JWT_SECRET", JWT_SECRET),
+            ("TURNSTILE_SECRET", "secret"),
+            ("CACHE_BACKEND", "redis"),
+        ])))
+        .unwrap_err();
+        assert!(err.to_string().contains("REDIS_URL"), "{}", err);
+
+        let err = Settings::from_figment(&Figment::from(env(&[
+            ("JWT_SECRET", JWT_SECRET),
+            ("TURNSTILE_SECRET", "secret"),
+            ("CACHE_TTL_SECONDS", "soon"),
+        ])))
+        .unwrap_err();
+        assert!(err.to_string().contains("CACHE_TTL_SECONDS"), "{}", err);
+
+        let err = Settings::from_figment(&Figment::from(env(&[
+            ("JWT_SECRET", JWT_SECRET),
+            ("TURNSTILE_SECRET", "secret"),
+            ("SMTP_HOST", "localhost"),
+        ])))
+        .unwrap_err();
+        assert!(err.to_string().contains("MAIL_FROM"), "{}", err);
+
+        let err = Settings::from_figment(&Figment::from(env(&[
+            ("JWT_SECRET", JWT_SECRET),
+            ("TURNSTILE_SECRET", "secret"),
+            ("SMTP_HOST", "localhost"),
+            ("MAIL_FROM", "Blog <noreply@example.com>"),
+        ])))
+        .unwrap_err();
+        assert!(err.to_string().contains("MAIL_UNSUBSCRIBE_SECRET"), "{}", err);
+
+        let err = Settings::from_figment(&Figment::from(env(&[
+            ("JWT_SECRET", JWT_SECRET),
+            ("TURNSTILE_SECRET", "secret"),
+            ("REVALIDATION_SECRET", "revalidate"),
+        ])))
+        .unwrap_err();
+        assert!(err.to_string().contains("REVALIDATION_SALT"), "{}", err);
+    }
+}
@@ -7,6 +7,209 @@ use rocket::Request;
 
 use crate::models::{ApiResponse};
 
+/// 应用错误类型
+///
+/// 路由统一返回 `Result<_, AppError>`，错误响应的 HTTP 状态码与 `ApiResponse.code` 一致，
+/// 并在 `errorCode` 中携带稳定的机器可读错误码。
+#[derive(Debug)]
+pub enum AppError {
+    /// 请求参数校验失败
+    Validation(String),
+    /// 资源不存在
+    NotFound(String),
+    /// 已认证但不允许执行该操作
+    Forbidden(String),
+    /// 与现有数据冲突
+    Conflict(String),
+    /// 上游服务（AI、OAuth、第三方 API 等）调用失败
+    Upstream(String),
+    /// 依赖的服务未启用或暂不可用
+    Unavailable(String),
+    /// 请求过于频繁
+    RateLimited { retry_after_secs: u64 },
+    /// 数据库操作失败
+    Database(String),
+    /// 认证 / 授权失败
+    Auth(AuthError),
+    /// 其他内部错误
+    Internal(String),
+}
+
+impl AppError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation(message.into())
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        AppError::NotFound(message.into())
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        AppError::Forbidden(message.into())
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict(message.into())
+    }
+
+    pub fn upstream(message: impl Into<String>) -> Self {
+        AppError::Upstream(message.into())
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        AppError::Unavailable(message.into())
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal(message.into())
+    }
+
+    /// 对应的 HTTP 状态码
+    pub fn status(&self) -> Status {
+        match self {
+            AppError::Validation(_) => Status::BadRequest,
+            AppError::NotFound(_) => Status::NotFound,
+            AppError::Forbidden(_) => Status::Forbidden,
+            AppError::Conflict(_) => Status::Conflict,
+            AppError::Upstream(_) => Status::BadGateway,
+            AppError::Unavailable(_) => Status::ServiceUnavailable,
+            AppError::RateLimited { .. } => Status::TooManyRequests,
+            AppError::Database(_) | AppError::Internal(_) => Status::InternalServerError,
+            AppError::Auth(err) => err.status(),
+        }
+    }
+
+    /// 稳定的机器可读错误码
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            AppError::Validation(_) => "VALIDATION_FAILED",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Upstream(_) => "UPSTREAM_FAILED",
+            AppError::Unavailable(_) => "SERVICE_UNAVAILABLE",
+            AppError::RateLimited { .. } => "RATE_LIMITED",
+            AppError::Database(_) => "DATABASE_ERROR",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+            AppError::Auth(err) => err.error_code(),
+        }
+    }
+
+    /// 返回给客户端的错误信息（数据库与内部错误不暴露细节）
+    pub fn public_message(&self) -> String {
+        match self {
+            AppError::Validation(msg)
+            | AppError::NotFound(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Conflict(msg)
+            | AppError::Upstream(msg)
+            | AppError::Unavailable(msg) => msg.clone(),
+            AppError::RateLimited { retry_after_secs } => {
+                format!("请求过于频繁，请 {} 秒后重试", retry_after_secs)
+            }
+            AppError::Database(_) => "数据库错误".to_string(),
+            AppError::Internal(_) => "服务器内部错误".to_string(),
+            AppError::Auth(err) => err.public_message(),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Validation(msg) => write!(f, "参数错误: {}", msg),
+            AppError::NotFound(msg) => write!(f, "资源不存在: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "禁止访问: {}", msg),
+            AppError::Conflict(msg) => write!(f, "数据冲突: {}", msg),
+            AppError::Upstream(msg) => write!(f, "上游服务错误: {}", msg),
+            AppError::Unavailable(msg) => write!(f, "服务不可用: {}", msg),
+            AppError::RateLimited { retry_after_secs } => {
+                write!(f, "请求过于频繁 (retry after {}s)", retry_after_secs)
+            }
+            AppError::Database(msg) => write!(f, "数据库错误: {}", msg),
+            AppError::Internal(msg) => write!(f, "内部错误: {}", msg),
+            AppError::Auth(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// 将 AppError 转换为 HTTP 响应
+impl<'r> Responder<'r, 'static> for AppError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = self.status();
+        if status.code >= 500 {
+            tracing::error!(error_code = self.error_code(), "请求失败: {} (HTTP {})", self, status.code);
+        } else {
+            tracing::warn!(error_code = self.error_code(), "请求失败: {} (HTTP {})", self, status.code);
+        }
+
+        let response = ApiResponse::<()>::error(status.code, self.public_message())
+            .with_error_code(self.error_code());
+
+        let mut res = response::status::Custom(status, Json(response)).respond_to(req)?;
+        if let AppError::RateLimited { retry_after_secs } = self {
+            res.set_raw_header("Retry-After", retry_after_secs.to_string());
+        }
+        Ok(res)
+    }
+}
+
+impl From<AuthError> for AppError {
+    fn from(err: AuthError) -> Self {
+        AppError::Auth(err)
+    }
+}
+
+impl From<mongodb::error::Error> for AppError {
+    fn from(err: mongodb::error::Error) -> Self {
+        AppError::Database(err.to_string())
+    }
+}
+
+impl From<bson::oid::Error> for AppError {
+    fn from(_: bson::oid::Error) -> Self {
+        AppError::Validation("无效的 ID 格式".to_string())
+    }
+}
+
+impl From<crate::services::comment::service::CommentTargetError> for AppError {
+    fn from(err: crate::services::comment::service::CommentTargetError) -> Self {
+        use crate::services::comment::service::CommentTargetError;
+        match err {
+            CommentTargetError::CommentsDisabled => AppError::Forbidden("站点已关闭评论".to_string()),
+            CommentTargetError::RefNotFound => AppError::NotFound("评论目标不存在".to_string()),
+            CommentTargetError::CommentsClosed => AppError::Forbidden("该内容不允许评论".to_string()),
+            CommentTargetError::ParentNotFound => AppError::NotFound("回复的评论不存在".to_string()),
+            CommentTargetError::ParentMismatch => {
+                AppError::Validation("回复的评论不属于该内容".to_string())
+            }
+            CommentTargetError::Database(message) => AppError::Database(message),
+        }
+    }
+}
+
+/// 按 HTTP 状态码推断错误码（用于 catcher 等没有具体错误的场景）
+pub fn error_code_for_status(status: Status) -> &'static str {
+    match status.code {
+        400 => "VALIDATION_FAILED",
+        401 => "UNAUTHORIZED",
+        403 => "FORBIDDEN",
+        404 => "NOT_FOUND",
+        409 => "CONFLICT",
+        429 => "RATE_LIMITED",
+        422 => "UNPROCESSABLE_ENTITY",
+        502 => "UPSTREAM_FAILED",
+        503 => "SERVICE_UNAVAILABLE",
+        code if code >= 500 => "INTERNAL_ERROR",
+        _ => "REQUEST_FAILED",
+    }
+}
+
+/// 返回 `ApiResponse` 的路由结果
+pub type ApiResult<T> = Result<Json<ApiResponse<T>>, AppError>;
+
 /// 认证错误类型
 #[derive(Debug)]
 #[allow(unused)]
@@ -54,28 +257,51 @@ impl std::fmt::Display for AuthError {
 
 impl std::error::Error for AuthError {}
 
-/// 将 AuthError 转换为 HTTP 响应
-impl<'r> Responder<'r, 'static> for AuthError {
-    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
-        let (status, code, message) = match &self {
+impl AuthError {
+    fn status(&self) -> Status {
+        match self {
             AuthError::InvalidToken | AuthError::ExpiredToken | AuthError::MissingAuthHeader => {
-                (Status::Unauthorized, 401, self.to_string())
+                Status::Unauthorized
             }
-            AuthError::InsufficientPermissions => (Status::Forbidden, 403, self.to_string()),
-            AuthError::UserNotFound => (Status::NotFound, 404, self.to_string()),
-            AuthError::UnsupportedProvider(_)
-            | AuthError::AccountAlreadyLinked
-            | AuthError::ConfigError(_) => (Status::BadRequest, 400, self.to_string()),
-            AuthError::OAuthFlowFailed(_) | AuthError::DatabaseError(_) => {
-                (Status::InternalServerError, 500, self.to_string())
+            AuthError::InsufficientPermissions => Status::Forbidden,
+            AuthError::UserNotFound => Status::NotFound,
+            AuthError::AccountAlreadyLinked => Status::Conflict,
+            AuthError::UnsupportedProvider(_) => Status::BadRequest,
+            AuthError::OAuthFlowFailed(_) => Status::BadGateway,
+            AuthError::ConfigError(_) | AuthError::DatabaseError(_) => {
+                Status::InternalServerError
             }
-        };
+        }
+    }
 
-        log::error!("认证错误: {} (HTTP {})", message, code);
+    fn error_code(&self) -> &'static str {
+        match self {
+            AuthError::InvalidToken => "AUTH_INVALID_TOKEN",
+            AuthError::ExpiredToken => "AUTH_TOKEN_EXPIRED",
+            AuthError::MissingAuthHeader => "UNAUTHORIZED",
+            AuthError::InsufficientPermissions => "FORBIDDEN",
+            AuthError::UserNotFound => "USER_NOT_FOUND",
+            AuthError::AccountAlreadyLinked => "ACCOUNT_ALREADY_LINKED",
+            AuthError::UnsupportedProvider(_) => "UNSUPPORTED_PROVIDER",
+            AuthError::OAuthFlowFailed(_) => "OAUTH_FAILED",
+            AuthError::ConfigError(_) => "CONFIG_ERROR",
+            AuthError::DatabaseError(_) => "DATABASE_ERROR",
+        }
+    }
 
-        let response = ApiResponse::<()>::error(code, message);
+    fn public_message(&self) -> String {
+        match self {
+            AuthError::ConfigError(_) => "服务配置错误".to_string(),
+            AuthError::DatabaseError(_) => "数据库错误".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
 
-        response::status::Custom(status, Json(response)).respond_to(req)
+/// 将 AuthError 转换为 HTTP 响应
+impl<'r> Responder<'r, 'static> for AuthError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        AppError::Auth(self).respond_to(req)
     }
 }
 
@@ -0,0 +1,276 @@
+//! CORS fairing - credentialed cross-origin access for configured origins only
+
+use regex::Regex;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Method, Status};
+use rocket::{Request, Response};
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+use crate::models::SiteConfig;
+use crate::services::{OptionsService, OptionsSnapshot};
+
+const ALLOWED_METHODS: &str = "GET, POST, PUT, DELETE, PATCH";
+const ALLOWED_HEADERS: &str = "Authorization, Content-Type, Accept";
+/// 预检结果缓存时间（秒）
+const PREFLIGHT_MAX_AGE: &str = "86400";
+
+/// 一条允许的来源规则
+#[derive(Debug, Clone)]
+pub enum OriginPattern {
+    /// 完全匹配（`scheme://host[:port]`，小写）
+    Exact(String),
+    /// 通配子域名（`https://*.example.com`）或正则（`^...$`）
+    Regex(Regex),
+}
+
+impl OriginPattern {
+    /// 解析配置中的来源规则
+    ///
+    /// - 以 `^` 开头的视为正则，必须以 `$` 结尾
+    /// - `https://*.example.com` 匹配任意层级的子域名（不含 `example.com` 本身）
+    /// - 其余按 URL 取 `scheme://host[:port]` 完全匹配
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let entry = entry.trim();
+
+        if entry.starts_with('^') {
+            if !entry.ends_with('$') {
+                return Err(format!("origin regex \"{}\" must end with $", entry));
+            }
+            return Regex::new(entry)
+                .map(Self::Regex)
+                .map_err(|e| format!("invalid origin regex \"{}\": {}", entry, e));
+        }
+
+        let origin = normalize_origin(entry)
+            .ok_or_else(|| format!("invalid origin \"{}\", expected http(s)://host[:port]", entry))?;
+        if !origin.contains('*') {
+            return Ok(Self::Exact(origin));
+        }
+
+        let (scheme, host) = origin.split_once("://").unwrap_or_default();
+        match host.strip_prefix("*.") {
+            Some(domain) if !domain.is_empty() && !domain.contains('*') => {
+                let pattern = format!(
+                    r"^{}://[a-z0-9-]+(?:\.[a-z0-9-]+)*\.{}$",
+                    regex::escape(scheme),
+                    regex::escape(domain)
+                );
+                Regex::new(&pattern)
+                    .map(Self::Regex)
+                    .map_err(|e| format!("invalid origin \"{}\": {}", entry, e))
+            }
+            _ => Err(format!(
+                "invalid origin \"{}\", wildcards are only allowed as the first label (https://*.example.com)",
+                entry
+            )),
+        }
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Exact(allowed) => allowed == origin,
+            Self::Regex(regex) => regex.is_match(origin),
+        }
+    }
+}
+
+/// 把 URL 规范化为来源：`scheme://host[:port]`，小写，去掉路径
+fn normalize_origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.trim().split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    let host = rest.split(['/', '?', '#']).next().filter(|host| !host.is_empty())?;
+    Some(format!("{}://{}", scheme, host.to_ascii_lowercase()))
+}
+
+/// 站点配置中的前台和后台地址
+fn site_origins(site: &SiteConfig) -> Vec<String> {
+    [&site.url.web_url, &site.url.admin_url]
+        .into_iter()
+        .flatten()
+        .filter_map(|url| normalize_origin(url))
+        .collect()
+}
+
+/// 站点来源缓存：对应的配置快照和从中解析出的来源
+type SiteOriginsCache = Option<(Arc<OptionsSnapshot>, Arc<Vec<String>>)>;
+
+/// CORS Fairing
+///
+/// 允许的来源 = `Settings.cors.allowed_origins` + 站点配置的 `webUrl` / `adminUrl`。
+/// 站点配置部分按快照缓存，配置刷新后下一个请求自动重建。
+/// 只有允许的来源会收到 `Access-Control-Allow-Origin` 和 `Access-Control-Allow-Credentials`；
+/// 预检请求（OPTIONS）对允许的来源返回 204，否则返回 403。
+pub struct CorsFairing {
+    configured: Vec<OriginPattern>,
+    site_origins: RwLock<SiteOriginsCache>,
+}
+
+impl CorsFairing {
+    /// 创建 Fairing，`allowed_origins` 中的规则见 [`OriginPattern::parse`]
+    pub fn new(allowed_origins: &[String]) -> Result<Self, String> {
+        let configured = allowed_origins
+            .iter()
+            .map(|entry| OriginPattern::parse(entry))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            configured,
+            site_origins: RwLock::new(None),
+        })
+    }
+
+    fn is_allowed(&self, req: &Request<'_>, origin: &str) -> bool {
+        if self.configured.iter().any(|pattern| pattern.matches(origin)) {
+            return true;
+        }
+
+        match req.rocket().state::<OptionsService>() {
+            Some(options) => self.site_origins(options).iter().any(|allowed| allowed == origin),
+            None => false,
+        }
+    }
+
+    /// 当前快照对应的站点来源（快照未变化时复用上次的结果）
+    fn site_origins(&self, options: &OptionsService) -> Arc<Vec<String>> {
+        let snapshot = options.current();
+
+        if let Some((cached, origins)) = &*self.site_origins.read().unwrap_or_else(|e| e.into_inner()) {
+            if Arc::ptr_eq(cached, &snapshot) {
+                return origins.clone();
+            }
+        }
+
+        let origins = Arc::new(site_origins(snapshot.site()));
+        tracing::info!("CORS 站点来源已更新: {:?}", origins);
+        *self.site_origins.write().unwrap_or_else(|e| e.into_inner()) = Some((snapshot, origins.clone()));
+        origins
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for CorsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let Some(origin) = req.headers().get_one("Origin") else {
+            return;
+        };
+        res.adjoin_raw_header("Vary", "Origin");
+
+        let preflight = req.method() == Method::Options
+            && req.headers().contains("Access-Control-Request-Method");
+        let allowed = normalize_origin(origin).is_some_and(|origin| self.is_allowed(req, &origin));
+
+        if !allowed {
+            if preflight {
+                tracing::warn!("拒绝来自 {} 的跨域预检请求", origin);
+                res.set_status(Status::Forbidden);
+                res.remove_header("Content-Type");
+                res.set_sized_body(0, Cursor::new(""));
+            }
+            return;
+        }
+
+        res.set_raw_header("Access-Control-Allow-Origin", origin.to_string());
+        res.set_raw_header("Access-Control-Allow-Credentials", "true");
+
+        if preflight {
+            res.set_status(Status::NoContent);
+            res.set_raw_header("Access-Control-Allow-Methods", ALLOWED_METHODS);
+            res.set_raw_header("Access-Control-Allow-Headers", ALLOWED_HEADERS);
+            res.set_raw_header("Access-Control-Max-Age", PREFLIGHT_MAX_AGE);
+            res.remove_header("Content-Type");
+            res.set_sized_body(0, Cursor::new(""));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UrlOptions;
+    use rocket::http::Header;
+    use rocket::local::asynchronous::Client;
+
+    #[get("/ping")]
+    fn ping() -> &'static str {
+        "pong"
+    }
+
+    #[test]
+    fn test_origin_patterns() {
+        let wildcard = OriginPattern::parse("https://*.example.com").unwrap();
+        assert!(wildcard.matches("https://blog.example.com"));
+        assert!(wildcard.matches("https://a.b.example.com"));
+        assert!(!wildcard.matches("https://example.com"));
+        assert!(!wildcard.matches("https://evil-example.com"));
+        assert!(!wildcard.matches("https://example.com.evil.net"));
+        assert!(!wildcard.matches("http://blog.example.com"));
+
+        let exact = OriginPattern::parse("https://Example.com/admin/").unwrap();
+        assert!(exact.matches("https://example.com"));
+
+        let regex = OriginPattern::parse(r"^https://preview-\d+\.example\.dev$").unwrap();
+        assert!(regex.matches("https://preview-42.example.dev"));
+
+        assert!(OriginPattern::parse("https://*").is_err());
+        assert!(OriginPattern::parse("https://a.*.example.com").is_err());
+        assert!(OriginPattern::parse("^https://.*").is_err());
+        assert!(OriginPattern::parse("example.com").is_err());
+
+        let site = SiteConfig {
+            url: UrlOptions {
+                web_url: Some("https://tnxg.top/".to_string()),
+                admin_url: Some("https://admin.tnxg.top/proxy/qaqdmin".to_string()),
+                ..UrlOptions::default()
+            },
+            ..SiteConfig::default()
+        };
+        assert_eq!(site_origins(&site), ["https://tnxg.top", "https://admin.tnxg.top"]);
+    }
+
+    #[rocket::async_test]
+    async fn test_only_allowed_origins_get_credentials() {
+        let fairing = CorsFairing::new(&["https://*.example.com".to_string()]).unwrap();
+        let rocket = rocket::build().attach(fairing).mount("/", routes![ping]);
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let response = client
+            .get("/ping")
+            .header(Header::new("Origin", "https://blog.example.com"))
+            .dispatch()
+            .await;
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), Some("https://blog.example.com"));
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Credentials"), Some("true"));
+
+        let response = client
+            .get("/ping")
+            .header(Header::new("Origin", "https://evil.test"))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.headers().get_one("Access-Control-Allow-Origin").is_none());
+        assert!(response.headers().get_one("Access-Control-Allow-Credentials").is_none());
+
+        let preflight = |origin: &'static str| {
+            client
+                .req(Method::Options, "/ping")
+                .header(Header::new("Origin", origin))
+                .header(Header::new("Access-Control-Request-Method", "POST"))
+                .dispatch()
+        };
+        let response = preflight("https://blog.example.com").await;
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Methods"), Some(ALLOWED_METHODS));
+        assert_eq!(preflight("https://evil.test").await.status(), Status::Forbidden);
+    }
+}
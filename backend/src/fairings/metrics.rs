@@ -0,0 +1,137 @@
+//! Metrics fairing - per-route request metrics and the `/metrics` endpoint
+
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Build, Data, Request, Response, Rocket};
+use sha2::{Digest, Sha256};
+use std::time::Instant;
+
+use crate::config::MetricsConfig;
+use crate::services::metrics::metrics;
+
+/// 请求开始时间（存放在请求本地缓存中）
+struct RequestStart(Option<Instant>);
+
+/// Prometheus 指标 Fairing
+///
+/// - `metrics.enabled` 时挂载 `GET /metrics`（Prometheus 文本格式），配置了 `metrics.token` 时需携带 Bearer 令牌
+/// - 按路由记录请求数和耗时；未匹配任何路由的请求统一记为 `unmatched`，避免标签基数膨胀
+pub struct MetricsFairing {
+    config: MetricsConfig,
+}
+
+impl MetricsFairing {
+    pub fn new(config: MetricsConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for MetricsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Prometheus Metrics",
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        if !self.config.enabled {
+            return Ok(rocket);
+        }
+        Ok(rocket
+            .manage(MetricsToken(self.config.token.clone()))
+            .mount("/", super::traced(routes![prometheus_metrics])))
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        req.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let Some(start) = req.local_cache(|| RequestStart(None)).0 else {
+            return;
+        };
+
+        let route = req
+            .route()
+            .map(|route| route.uri.as_str())
+            .unwrap_or("unmatched");
+
+        metrics().observe_http(
+            req.method().as_str(),
+            route,
+            res.status().code,
+            start.elapsed(),
+        );
+    }
+}
+
+/// 抓取 `/metrics` 需要的 Bearer 令牌（`Settings.metrics.token`）
+struct MetricsToken(Option<String>);
+
+/// 校验抓取请求的 Bearer 令牌（未配置令牌时放行）
+struct MetricsAuth;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for MetricsAuth {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(expected) = req.rocket().state::<MetricsToken>().and_then(|token| token.0.as_deref()) else {
+            return Outcome::Success(MetricsAuth);
+        };
+        let provided = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+
+        // 比较摘要，避免逐字节比较泄露令牌前缀
+        if Sha256::digest(provided.trim()) == Sha256::digest(expected) {
+            Outcome::Success(MetricsAuth)
+        } else {
+            Outcome::Error((Status::Unauthorized, ()))
+        }
+    }
+}
+
+/// 导出 Prometheus 指标
+#[get("/metrics")]
+fn prometheus_metrics(_auth: MetricsAuth) -> (ContentType, String) {
+    let content_type = ContentType::new("text", "plain")
+        .with_params([("version", "0.0.4"), ("charset", "utf-8")]);
+    (content_type, metrics().render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Header;
+    use rocket::local::asynchronous::Client;
+
+    async fn metrics_client(enabled: bool, token: Option<&str>) -> Client {
+        let config = MetricsConfig {
+            enabled,
+            token: token.map(str::to_string),
+        };
+        Client::tracked(rocket::build().attach(MetricsFairing::new(config))).await.unwrap()
+    }
+
+    #[rocket::async_test]
+    async fn test_metrics_endpoint_is_opt_in_and_token_protected() {
+        let client = metrics_client(false, None).await;
+        assert_eq!(client.get("/metrics").dispatch().await.status(), Status::NotFound);
+
+        let client = metrics_client(true, None).await;
+        assert_eq!(client.get("/metrics").dispatch().await.status(), Status::Ok);
+
+        let client = metrics_client(true, Some("scrape")).await;
+        assert_eq!(client.get("/metrics").dispatch().await.status(), Status::Unauthorized);
+        let wrong = client.get("/metrics").header(Header::new("Authorization", "Bearer nope"));
+        assert_eq!(wrong.dispatch().await.status(), Status::Unauthorized);
+        let right = client.get("/metrics").header(Header::new("Authorization", "Bearer scrape"));
+        assert_eq!(right.dispatch().await.status(), Status::Ok);
+    }
+}
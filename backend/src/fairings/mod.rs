@@ -0,0 +1,11 @@
+//! Fairing modules
+
+pub mod cors;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
+
+pub use cors::CorsFairing;
+pub use metrics::MetricsFairing;
+pub use rate_limit::{rate_limited, RateLimitFairing};
+pub use request_id::{traced, RequestIdFairing};
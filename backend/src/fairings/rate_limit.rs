@@ -0,0 +1,164 @@
+//! Rate limit fairing - token bucket limits for selected route groups
+
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::route::{Handler, Outcome};
+use rocket::{Build, Data, Request, Rocket, Route};
+
+use crate::config::RateLimitConfig;
+use crate::error::AppError;
+use crate::guards::{ClientIp, OptionalAuthGuard};
+use crate::services::{CacheService, RateLimitGroup, RateLimiter};
+use crate::services::rate_limiter::RateLimitDecision;
+
+/// 限流 Fairing
+///
+/// 启动时基于 `Settings.rate_limit` 的限额和已注册的 `CacheService` 创建 `RateLimiter`。
+/// 具体哪些路由受限由 [`rate_limited`] 在挂载时指定。
+pub struct RateLimitFairing {
+    config: RateLimitConfig,
+}
+
+impl RateLimitFairing {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RateLimitFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate Limit",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let Some(cache) = rocket.state::<CacheService>().cloned() else {
+            tracing::error!("限流依赖 CacheService，请先 manage 缓存服务");
+            return Err(rocket);
+        };
+
+        if self.config.enabled {
+            tracing::info!("限流已启用 (后端: {})", cache.backend_name());
+        } else {
+            tracing::warn!("限流已禁用");
+        }
+
+        Ok(rocket.manage(RateLimiter::new(self.config.clone(), cache)))
+    }
+}
+
+/// 在路由处理函数前检查令牌桶
+#[derive(Clone)]
+struct RateLimitedHandler {
+    group: RateLimitGroup,
+    inner: Box<dyn Handler>,
+}
+
+#[rocket::async_trait]
+impl Handler for RateLimitedHandler {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        if let Some(limiter) = req.rocket().state::<RateLimiter>() {
+            let ip = req
+                .guard::<ClientIp>()
+                .await
+                .succeeded()
+                .map(|ip| ip.0)
+                .unwrap_or_else(|| "unknown".to_string());
+            let user_id = req
+                .guard::<OptionalAuthGuard>()
+                .await
+                .succeeded()
+                .and_then(|auth| auth.user_id)
+                .map(|id| id.to_hex());
+
+            if let RateLimitDecision::Limited { retry_after } =
+                limiter.check(self.group, &ip, user_id.as_deref()).await
+            {
+                let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+                return Outcome::from(req, AppError::RateLimited { retry_after_secs });
+            }
+        }
+
+        self.inner.handle(req, data).await
+    }
+}
+
+/// 让一组路由受指定分组的限额约束，超限时返回 429 并带上 `Retry-After`
+pub fn rate_limited(group: RateLimitGroup, routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(RateLimitedHandler {
+                group,
+                inner: route.handler.clone(),
+            });
+            route
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProxyConfig, RateLimitKey, RateLimitRule};
+    use crate::guards::TrustedProxies;
+    use rocket::http::{Header, Status};
+    use rocket::local::asynchronous::Client;
+    use std::net::SocketAddr;
+
+    #[get("/limited")]
+    fn limited() -> &'static str {
+        "ok"
+    }
+
+    #[rocket::async_test]
+    async fn test_over_limit_returns_429_with_retry_after() {
+        let config = RateLimitConfig {
+            nbnhhsh: RateLimitRule {
+                capacity: 2,
+                period_seconds: 60,
+                key: RateLimitKey::Ip,
+            },
+            ..RateLimitConfig::default()
+        };
+        let rocket = rocket::build()
+            .manage(CacheService::new(100, 60))
+            .manage(TrustedProxies::new(&ProxyConfig {
+                trusted_proxies: vec!["127.0.0.1".to_string()],
+            }))
+            .attach(RateLimitFairing::new(config))
+            .mount("/", rate_limited(RateLimitGroup::Nbnhhsh, routes![limited]));
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let get = |peer: &'static str, forwarded_for: &'static str| {
+            client
+                .get("/limited")
+                .remote(SocketAddr::new(peer.parse().unwrap(), 40000))
+                .header(Header::new("X-Forwarded-For", forwarded_for))
+                .dispatch()
+        };
+
+        // 直连客户端伪造的 X-Forwarded-For 不影响分桶
+        assert_eq!(get("203.0.113.9", "10.0.0.1").await.status(), Status::Ok);
+        assert_eq!(get("203.0.113.9", "10.0.0.2").await.status(), Status::Ok);
+
+        let response = get("203.0.113.9", "10.0.0.3").await;
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("30"));
+        let body: serde_json::Value = response.into_json().await.unwrap();
+        assert_eq!(body["errorCode"], "RATE_LIMITED");
+
+        // 经受信任代理转发时按代理记录的客户端地址分桶，最左侧的伪造地址被忽略
+        assert_eq!(get("127.0.0.1", "203.0.113.9, 198.51.100.7").await.status(), Status::Ok);
+        assert_eq!(get("127.0.0.1", "203.0.113.10, 198.51.100.7").await.status(), Status::Ok);
+        assert_eq!(
+            get("127.0.0.1", "203.0.113.11, 198.51.100.7").await.status(),
+            Status::TooManyRequests
+        );
+
+        // 其他 IP 使用独立的桶
+        assert_eq!(get("127.0.0.1", "198.51.100.8").await.status(), Status::Ok);
+    }
+}
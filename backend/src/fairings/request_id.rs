@@ -0,0 +1,148 @@
+//! Request ID fairing - assigns each request an ID and traces handlers with it
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::route::{Handler, Outcome};
+use rocket::{Data, Request, Response, Route};
+use std::time::Instant;
+use tracing::Instrument;
+
+use crate::utils::request_id::{self, REQUEST_ID_HEADER};
+
+/// 当前请求的 ID 和开始时间（存放在请求本地缓存中）
+struct RequestContext {
+    id: String,
+    start: Instant,
+}
+
+/// 获取请求 ID（Fairing 未运行时生成一个新的）
+pub fn request_id(req: &Request<'_>) -> String {
+    context(req).id.clone()
+}
+
+fn context<'r>(req: &'r Request<'_>) -> &'r RequestContext {
+    req.local_cache(|| RequestContext {
+        id: request_id::generate(),
+        start: Instant::now(),
+    })
+}
+
+/// 请求 ID Fairing
+///
+/// - 沿用网关传入的 `X-Request-Id`（校验通过时），否则生成新的 UUID
+/// - 在响应头中返回 `X-Request-Id`
+/// - 请求结束时输出一条带状态码和耗时的访问日志
+pub struct RequestIdFairing;
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ID",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let id = req
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .and_then(request_id::sanitize)
+            .map(str::to_string)
+            .unwrap_or_else(request_id::generate);
+
+        req.local_cache(|| RequestContext {
+            id,
+            start: Instant::now(),
+        });
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let context = context(req);
+        res.set_raw_header(REQUEST_ID_HEADER, context.id.clone());
+
+        tracing::info!(
+            request_id = %context.id,
+            method = %req.method(),
+            uri = %req.uri(),
+            status = res.status().code,
+            elapsed_ms = context.start.elapsed().as_millis() as u64,
+            "请求完成"
+        );
+    }
+}
+
+/// 为路由处理函数包裹 tracing span 和请求 ID 作用域
+#[derive(Clone)]
+struct TracedHandler(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for TracedHandler {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        let id = request_id(req);
+        let span = tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %req.method(),
+            path = %req.uri().path(),
+        );
+
+        request_id::scope(id, self.0.handle(req, data))
+            .instrument(span)
+            .await
+    }
+}
+
+/// 让一组路由在请求 span 内执行，处理函数中的日志（包括 `in_current_span` 派生的任务）都会带上 request_id
+pub fn traced(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(TracedHandler(route.handler.clone()));
+            route
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use rocket::http::{Header, Status};
+    use rocket::local::asynchronous::Client;
+
+    #[get("/fail")]
+    fn fail() -> Result<(), AppError> {
+        Err(AppError::validation("bad"))
+    }
+
+    async fn client() -> Client {
+        let rocket = rocket::build()
+            .attach(RequestIdFairing)
+            .mount("/", traced(routes![fail]));
+        Client::tracked(rocket).await.unwrap()
+    }
+
+    #[rocket::async_test]
+    async fn test_error_response_carries_request_id() {
+        let client = client().await;
+        let response = client.get("/fail").dispatch().await;
+
+        let header = response.headers().get_one(REQUEST_ID_HEADER).unwrap().to_string();
+        let body: serde_json::Value = response.into_json().await.unwrap();
+        assert_eq!(body["requestId"], header);
+        assert_eq!(body["errorCode"], "VALIDATION_FAILED");
+    }
+
+    #[rocket::async_test]
+    async fn test_incoming_request_id_is_reused() {
+        let client = client().await;
+        let response = client
+            .get("/fail")
+            .header(Header::new(REQUEST_ID_HEADER, "gateway-42"))
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(response.headers().get_one(REQUEST_ID_HEADER), Some("gateway-42"));
+    }
+}
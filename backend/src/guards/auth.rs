@@ -11,7 +11,6 @@ use crate::utils::jwt::verify_jwt;
 #[derive(Debug, Clone)]
 pub struct AuthGuard {
     pub user_id: ObjectId,
-    #[allow(unused)]
     pub is_owner: bool,
 }
 
@@ -26,12 +25,12 @@ impl<'r> FromRequest<'r> for AuthGuard {
                 if auth.starts_with("Bearer ") {
                     &auth[7..]
                 } else {
-                    log::warn!("Authorization header 格式错误，缺少 Bearer 前缀");
+                    tracing::warn!("Authorization header 格式错误，缺少 Bearer 前缀");
                     return Outcome::Error((Status::Unauthorized, ()));
                 }
             }
             None => {
-                log::warn!("缺少 Authorization header");
+                tracing::warn!("缺少 Authorization header");
                 return Outcome::Error((Status::Unauthorized, ()));
             }
         };
@@ -40,7 +39,7 @@ impl<'r> FromRequest<'r> for AuthGuard {
         let config = match req.guard::<&State<OAuthConfig>>().await {
             Outcome::Success(config) => config,
             _ => {
-                log::error!("无法获取 OAuthConfig");
+                tracing::error!("无法获取 OAuthConfig");
                 return Outcome::Error((Status::InternalServerError, ()));
             }
         };
@@ -49,14 +48,14 @@ impl<'r> FromRequest<'r> for AuthGuard {
         let claims = match verify_jwt(token, &config.jwt_secret) {
             Ok(claims) => claims,
             Err(e) => {
-                log::warn!("JWT 验证失败: {:?}", e);
+                tracing::warn!("JWT 验证失败: {:?}", e);
                 return Outcome::Error((Status::Unauthorized, ()));
             }
         };
 
         // 4. 检查 token 是否过期
         if claims.is_expired() {
-            log::warn!("JWT token 已过期");
+            tracing::warn!("JWT token 已过期");
             return Outcome::Error((Status::Unauthorized, ()));
         }
 
@@ -64,12 +63,12 @@ impl<'r> FromRequest<'r> for AuthGuard {
         let user_id = match claims.user_id() {
             Ok(id) => id,
             Err(e) => {
-                log::error!("解析 user_id 失败: {:?}", e);
+                tracing::error!("解析 user_id 失败: {:?}", e);
                 return Outcome::Error((Status::Unauthorized, ()));
             }
         };
 
-        log::debug!("认证成功: user_id={}, is_owner={}", user_id, claims.is_owner);
+        tracing::debug!("认证成功: user_id={}, is_owner={}", user_id, claims.is_owner);
 
         Outcome::Success(AuthGuard {
             user_id,
@@ -2,52 +2,104 @@
 
 use rocket::request::{FromRequest, Outcome, Request};
 use rocket::http::Status;
+use std::net::IpAddr;
+
+use crate::config::ProxyConfig;
+use crate::utils::ip_network::IpNetwork;
 
 /// 客户端 IP 地址
 pub struct ClientIp(pub String);
 
+/// 受信任的反向代理（`Settings.proxy.trusted_proxies`）
+///
+/// 只有直连地址属于受信任代理时才读取 `X-Forwarded-For` / `X-Real-IP`，
+/// 否则这些请求头可由客户端任意伪造。
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Vec<IpNetwork>);
+
+impl TrustedProxies {
+    /// 配置在加载时已校验，无法解析的条目直接忽略
+    pub fn new(config: &ProxyConfig) -> Self {
+        Self(
+            config
+                .trusted_proxies
+                .iter()
+                .filter_map(|proxy| IpNetwork::parse(proxy).ok())
+                .collect(),
+        )
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|network| network.contains(ip))
+    }
+
+    /// 解析客户端 IP
+    ///
+    /// 从直连地址开始，沿 `X-Forwarded-For` 从右往左跳过受信任代理，第一个不受信任的地址即客户端；
+    /// 全部受信任时取最左侧的地址。无法解析的条目不再继续向左查找。
+    pub fn resolve(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let mut client = peer;
+        let hops = forwarded_for.into_iter().flat_map(|header| header.rsplit(','));
+        for hop in hops {
+            if !self.is_trusted(client) {
+                break;
+            }
+            let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+        }
+        client
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for ClientIp {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // 1. 尝试从 X-Forwarded-For 头获取（代理/负载均衡器）
-        if let Some(forwarded_for) = request.headers().get_one("X-Forwarded-For") {
-            // X-Forwarded-For 可能包含多个 IP，取第一个（真实客户端 IP）
-            let ip = forwarded_for
-                .split(',')
-                .next()
-                .unwrap_or(forwarded_for)
-                .trim()
-                .to_string();
-            
-            if !ip.is_empty() {
-                return Outcome::Success(ClientIp(ip));
-            }
-        }
+        let Some(peer) = request.remote().map(|addr| addr.ip()) else {
+            return Outcome::Error((Status::BadRequest, ()));
+        };
 
-        // 2. 尝试从 X-Real-IP 头获取（Nginx 代理）
-        if let Some(real_ip) = request.headers().get_one("X-Real-IP") {
-            let ip = real_ip.trim().to_string();
-            if !ip.is_empty() {
-                return Outcome::Success(ClientIp(ip));
-            }
-        }
+        let proxies = request.rocket().state::<TrustedProxies>();
+        let Some(proxies) = proxies.filter(|proxies| proxies.is_trusted(peer)) else {
+            return Outcome::Success(ClientIp(peer.to_string()));
+        };
 
-        // 3. 尝试从 CF-Connecting-IP 头获取（Cloudflare）
-        if let Some(cf_ip) = request.headers().get_one("CF-Connecting-IP") {
-            let ip = cf_ip.trim().to_string();
-            if !ip.is_empty() {
-                return Outcome::Success(ClientIp(ip));
-            }
-        }
+        // 多个 X-Forwarded-For 头按顺序拼接；没有时使用代理设置的 X-Real-IP
+        let forwarded_for: Vec<&str> = request.headers().get("X-Forwarded-For").collect();
+        let forwarded_for = if forwarded_for.is_empty() {
+            request.headers().get_one("X-Real-IP").map(str::to_string)
+        } else {
+            Some(forwarded_for.join(","))
+        };
 
-        // 4. 从远程地址获取
-        if let Some(remote_addr) = request.remote() {
-            return Outcome::Success(ClientIp(remote_addr.ip().to_string()));
-        }
+        Outcome::Success(ClientIp(proxies.resolve(peer, forwarded_for.as_deref()).to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_rightmost_untrusted_hop() {
+        let proxies = TrustedProxies::new(&ProxyConfig {
+            trusted_proxies: vec!["127.0.0.1".to_string(), "10.0.0.0/8".to_string()],
+        });
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
 
-        // 5. 无法获取 IP
-        Outcome::Error((Status::BadRequest, ()))
+        // 直连地址不受信任时忽略请求头
+        assert_eq!(proxies.resolve(ip("203.0.113.9"), Some("198.51.100.1")), ip("203.0.113.9"));
+        // 客户端伪造的最左侧地址被跳过
+        assert_eq!(
+            proxies.resolve(ip("127.0.0.1"), Some("198.51.100.1, 203.0.113.9, 10.0.0.2")),
+            ip("203.0.113.9")
+        );
+        // 全部受信任时取最左侧
+        assert_eq!(proxies.resolve(ip("127.0.0.1"), Some("10.0.0.3")), ip("10.0.0.3"));
+        assert_eq!(proxies.resolve(ip("127.0.0.1"), Some("garbage, 10.0.0.3")), ip("10.0.0.3"));
+        assert_eq!(proxies.resolve(ip("127.0.0.1"), None), ip("127.0.0.1"));
     }
 }
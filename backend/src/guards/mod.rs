@@ -8,4 +8,4 @@ pub use auth::AuthGuard;
 pub use auth::OptionalAuthGuard;
 #[allow(unused_imports)]
 pub use owner::OwnerGuard;
-pub use client_ip::ClientIp;
+pub use client_ip::{ClientIp, TrustedProxies};
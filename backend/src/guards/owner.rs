@@ -26,11 +26,11 @@ impl<'r> FromRequest<'r> for OwnerGuard {
 
         // 2. 检查是否为 Owner
         if !auth.is_owner {
-            log::warn!("用户 {} 尝试访问 Owner 专属 API，但不是 Owner", auth.user_id);
+            tracing::warn!("用户 {} 尝试访问 Owner 专属 API，但不是 Owner", auth.user_id);
             return Outcome::Error((Status::Forbidden, ()));
         }
 
-        log::debug!("Owner 验证成功: user_id={}", auth.user_id);
+        tracing::debug!("Owner 验证成功: user_id={}", auth.user_id);
 
         Outcome::Success(OwnerGuard {
             user_id: auth.user_id,
@@ -8,160 +8,247 @@ mod services;
 mod utils;
 mod guards;
 mod error;
+mod fairings;
 
-use rocket::http::Method;
+use rocket::http::Status;
+use rocket::response::status::Custom;
 use rocket::serde::json::Json;
 use rocket::Request;
-use rocket_cors::{AllowedOrigins, CorsOptions};
 use models::ApiResponse;
+use tracing::Instrument;
 
 /// 404 Not Found error catcher - returns JSON
 #[catch(404)]
-fn not_found(_req: &Request) -> Json<ApiResponse<()>> {
-    ApiResponse::not_found("Resource not found".to_string())
+fn not_found(req: &Request) -> Json<ApiResponse<()>> {
+    let response = ApiResponse::<()>::error(404, "Resource not found".to_string())
+        .with_error_code(error::error_code_for_status(Status::NotFound));
+    Json(response.with_request_id(fairings::request_id::request_id(req)))
 }
 
 /// 500 Internal Server Error catcher - returns JSON
 #[catch(500)]
-fn internal_error(_req: &Request) -> Json<ApiResponse<()>> {
-    ApiResponse::internal_error("Internal server error".to_string())
+fn internal_error(req: &Request) -> Json<ApiResponse<()>> {
+    let response = ApiResponse::<()>::error(500, "Internal server error".to_string())
+        .with_error_code(error::error_code_for_status(Status::InternalServerError));
+    Json(response.with_request_id(fairings::request_id::request_id(req)))
+}
+
+/// Fallback catcher (guard failures, malformed bodies, ...) - returns JSON
+#[catch(default)]
+fn default_catcher(status: Status, req: &Request) -> Custom<Json<ApiResponse<()>>> {
+    let message = status.reason().unwrap_or("Request failed").to_string();
+    let response = ApiResponse::<()>::error(status.code, message)
+        .with_error_code(error::error_code_for_status(status));
+    Custom(status, Json(response.with_request_id(fairings::request_id::request_id(req))))
 }
 
 #[launch]
 async fn rocket() -> _ {
-    // 加载 .env 文件（必须在所有环境变量读取之前）
+    // 加载 .env 文件（必须在读取配置之前）
     dotenv::dotenv().ok();
-    
-    // 初始化日志系统（设置默认日志级别为 info）
-    if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
+
+    // 加载配置：默认值 → Rocket.toml → 环境变量 → 命令行参数
+    let (settings, figment) = match config::Settings::load() {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("配置加载失败: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // 初始化日志系统（默认日志级别为 info，log.format = json 时输出 JSON）
+    utils::logging::init(settings.log.format);
+    tracing::info!("启动 Rocket 服务器...");
+    tracing::info!("生效配置:");
+    for (key, value) in settings.summary() {
+        tracing::info!("  {} = {}", key, value);
     }
-    env_logger::init();
-    log::info!("启动 Rocket 服务器...");
 
-    // 加载 OAuth 配置（仅作为后备配置，实际使用时从数据库读取）
-    let oauth_config = config::OAuthConfig::from_env()
-        .expect("Failed to load OAuth configuration from environment variables");
-    log::info!("OAuth 后备配置从环境变量加载成功");
+    let config::Settings {
+        database: database_config,
+        oauth: oauth_config,
+        cache: cache_config,
+        revalidation: revalidation_config,
+        ip2region: ip2region_config,
+        cors: cors_config,
+        proxy: proxy_config,
+        rate_limit: rate_limit_config,
+        metrics: metrics_config,
+        mail: mail_config,
+        log: _,
+    } = settings;
 
-    // Initialize database connection
-    let database = services::init_db().await.expect("Failed to connect to MongoDB");
-    log::info!("MongoDB 连接成功");
-
-    // Initialize cache service
-    let cache_max_capacity = std::env::var("CACHE_MAX_CAPACITY")
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(10000);
-    let cache_ttl_seconds = std::env::var("CACHE_TTL_SECONDS")
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(3600);
-    
-    let cache_service = services::CacheService::new(cache_max_capacity, cache_ttl_seconds);
-    log::info!("缓存服务初始化成功");
-
-    // Initialize revalidation service (optional - only if configured)
-    let nextjs_url = std::env::var("NEXTJS_URL")
-        .unwrap_or_else(|_| "http://localhost:3000".to_string());
-    let revalidation_secret = std::env::var("REVALIDATION_SECRET").ok();
-    let revalidation_salt = std::env::var("REVALIDATION_SALT")
-        .unwrap_or_else(|_| "default-salt".to_string());
-    
-    // Only start Change Stream if revalidation is configured
-    if let Some(secret) = revalidation_secret {
-        let revalidation_service = services::RevalidationService::new(
-            nextjs_url,
-            secret,
-            revalidation_salt,
-        );
-        log::info!("Revalidation 服务初始化成功");
-
-        // Initialize and start Change Stream service in background
-        let change_stream_service = services::ChangeStreamService::new(
-            database.clone(),
-            cache_service.clone(),
-            revalidation_service.clone(),
-        );
-        
-        // Spawn Change Stream listener in background task
-        tokio::spawn(async move {
-            change_stream_service.start_watching().await;
-        });
-        log::info!("Change Stream 监听服务已启动（后台任务）");
+    // Comment notification mail (disabled unless SMTP_HOST is set)
+    let mailer = match services::Mailer::from_config(
+        &mail_config,
+        &oauth_config.backend_url,
+        &oauth_config.frontend_url,
+    ) {
+        Ok(mailer) => mailer,
+        Err(e) => {
+            eprintln!("配置加载失败: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if mailer.is_enabled() {
+        tracing::info!("邮件通知已启用 (SMTP: {})", mail_config.smtp_host.as_deref().unwrap_or_default());
     } else {
-        log::warn!("REVALIDATION_SECRET 未配置，Change Stream 监听服务已禁用");
-        log::warn!("如需启用 ISR 缓存自动刷新，请在 .env 中配置 REVALIDATION_SECRET");
+        tracing::info!("SMTP_HOST 未配置，邮件通知已禁用");
+    }
+
+    // Initialize database connection
+    let database = services::init_db(&database_config.uri)
+        .await
+        .expect("Failed to connect to MongoDB");
+    tracing::info!("MongoDB 连接成功");
+
+    // Initialize cache service (memory or redis, selected by cache.backend)
+    let cache_service = services::CacheService::from_config(&cache_config)
+        .await
+        .expect("Failed to initialize cache service");
+    tracing::info!("缓存服务初始化成功 (后端: {})", cache_service.backend_name());
+
+    // One reaction per visitor, comment and emoji
+    if let Err(e) = services::ReactionService::new(&database).ensure_indexes().await {
+        tracing::warn!("创建 comment_reactions 索引失败: {}", e);
+    }
+
+    // Webhook delivery queue and log
+    if let Err(e) = services::WebhookService::new(&database).ensure_indexes().await {
+        tracing::warn!("创建 webhook_deliveries 索引失败: {}", e);
+    }
+
+    // Enforce unique comment keys / commentsIndex (duplicates are repaired first if index creation fails)
+    match services::comment::counters::CommentCounters::new(&database).ensure_indexes().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("已修复 {} 条评论的重复序号", count),
+        Err(e) => tracing::warn!("创建 comments 唯一索引失败: {}", e),
     }
 
+    // Duplicate comment detection (same IP and text within a window)
+    if let Err(e) = services::comment::rules::CommentRules::ensure_indexes(&database).await {
+        tracing::warn!("创建 comments 重复检测索引失败: {}", e);
+    }
+
+    // Comment review queue
+    if let Err(e) = services::ReviewQueue::new(&database).ensure_indexes().await {
+        tracing::warn!("创建 comment_review_jobs 索引失败: {}", e);
+    }
+
+    // Load site options (served from memory, refreshed by the Change Stream)
+    let options_service = services::OptionsService::init(&database)
+        .await
+        .expect("Failed to load site options");
+
+    // Change Stream 状态（供 /ready 报告）
+    let change_stream_health = services::ChangeStreamHealth::default();
+
+    // Revalidation is optional - without it the Change Stream only refreshes local state
+    let revalidation_service = match revalidation_config.secret {
+        Some(secret) => {
+            tracing::info!("Revalidation 服务初始化成功");
+            Some(services::RevalidationService::new(
+                revalidation_config.nextjs_url,
+                secret,
+                revalidation_config.salt,
+            ))
+        }
+        None => {
+            tracing::warn!("REVALIDATION_SECRET 未配置，不会通知 Next.js 刷新 ISR 缓存");
+            tracing::warn!("如需启用 ISR 缓存自动刷新，请在 .env 中配置 REVALIDATION_SECRET");
+            None
+        }
+    };
+
+    // Initialize and start Change Stream service in background
+    let change_stream_service = services::ChangeStreamService::new(
+        database.clone(),
+        cache_service.clone(),
+        options_service.clone(),
+        revalidation_service,
+        change_stream_health.clone(),
+    );
+
+    // Spawn Change Stream listener in background task
+    tokio::spawn(
+        async move {
+            change_stream_service.start_watching().await;
+        }
+        .instrument(tracing::info_span!("change_stream")),
+    );
+    tracing::info!("Change Stream 监听服务已启动（后台任务）");
+
+    // Periodically remove deleted comments (tombstones) that no longer have replies
+    tokio::spawn(
+        services::comment::purge::run_tombstone_purge(database.clone())
+            .instrument(tracing::info_span!("comment_purge")),
+    );
+
+    // Deliver queued webhooks, retrying failures with backoff
+    tokio::spawn(
+        services::webhook::worker::run_delivery_worker(database.clone())
+            .instrument(tracing::info_span!("webhook_delivery")),
+    );
+
     // Initialize IP service
-    // 获取当前工作目录的绝对路径
-    let current_dir = std::env::current_dir()
-        .unwrap_or_else(|_| std::path::PathBuf::from("."));
-    
-    let ipv4_db_path = std::env::var("IP2REGION_V4_DB")
-        .unwrap_or_else(|_| {
-            current_dir.join("data/ip2region_v4.xdb")
-                .to_string_lossy()
-                .to_string()
-        });
-    let ipv6_db_path = std::env::var("IP2REGION_V6_DB")
-        .unwrap_or_else(|_| {
-            current_dir.join("data/ip2region_v6.xdb")
-                .to_string_lossy()
-                .to_string()
-        });
-    
+    let ipv4_db_path = ip2region_config.v4_db;
+    let ipv6_db_path = ip2region_config.v6_db;
+
     let ip_service = match services::IpService::new(ipv4_db_path.clone(), ipv6_db_path.clone()) {
         Ok(service) => {
-            log::info!("IP2Region 服务初始化成功");
-            log::info!("  - IPv4 数据库: {}", ipv4_db_path);
-            log::info!("  - IPv6 数据库: {}", ipv6_db_path);
+            tracing::info!("IP2Region 服务初始化成功");
+            tracing::info!("  - IPv4 数据库: {}", ipv4_db_path);
+            tracing::info!("  - IPv6 数据库: {}", ipv6_db_path);
             Some(service)
         }
         Err(e) => {
-            log::warn!("IP2Region 服务初始化失败: {}", e);
-            log::warn!("IP 地理位置查询功能将不可用");
-            log::warn!("当前工作目录: {}", current_dir.display());
-            log::warn!("请下载数据库文件到以下位置:");
-            log::warn!("  - {}", current_dir.join("data/ip2region_v4.xdb").display());
-            log::warn!("  - {}", current_dir.join("data/ip2region_v6.xdb").display());
-            log::warn!("下载地址: https://github.com/lionsoul2014/ip2region/tree/master/data");
-            log::warn!("或运行脚本: bash scripts/download-ip2region.sh");
+            let current_dir = std::env::current_dir()
+                .unwrap_or_else(|_| std::path::PathBuf::from("."));
+            tracing::warn!("IP2Region 服务初始化失败: {}", e);
+            tracing::warn!("IP 地理位置查询功能将不可用");
+            tracing::warn!("当前工作目录: {}", current_dir.display());
+            tracing::warn!("请下载数据库文件到以下位置:");
+            tracing::warn!("  - {}", current_dir.join(&ipv4_db_path).display());
+            tracing::warn!("  - {}", current_dir.join(&ipv6_db_path).display());
+            tracing::warn!("下载地址: https://github.com/lionsoul2014/ip2region/tree/master/data");
+            tracing::warn!("或运行脚本: bash scripts/download-ip2region.sh");
             None
         }
     };
 
-    // Configure CORS for frontend communication
-    let cors = CorsOptions::default()
-        .allowed_origins(AllowedOrigins::all())
-        .allowed_methods(
-            vec![Method::Get, Method::Post, Method::Put, Method::Delete, Method::Patch]
-                .into_iter()
-                .map(From::from)
-                .collect(),
-        )
-        .allowed_headers(rocket_cors::AllowedHeaders::some(&[
-            "Authorization",
-            "Content-Type",
-            "Accept",
-        ]))
-        .allow_credentials(true)
-        .to_cors()
+    // Review queued comments (and comments left pending by a restart), retrying AI failures with backoff
+    tokio::spawn(
+        services::comment::review::run_review_worker(database.clone(), options_service.clone(), mailer.clone())
+            .instrument(tracing::info_span!("comment_review")),
+    );
+
+    // Configure CORS (configured origins + site webUrl/adminUrl, reloaded with the options)
+    let cors = fairings::CorsFairing::new(&cors_config.allowed_origins)
         .expect("Failed to create CORS");
-    log::info!("CORS 配置完成");
+    tracing::info!("CORS 配置完成");
 
     // Build and launch Rocket with modular route registration
-    rocket::build()
+    rocket::custom(figment)
         .manage(database)
         .manage(oauth_config)
         .manage(ip_service)
         .manage(cache_service)
+        .manage(options_service)
+        .manage(change_stream_health)
+        .manage(mailer)
+        .manage(guards::TrustedProxies::new(&proxy_config))
         .attach(cors)
-        .register("/", catchers![not_found, internal_error])
-        .mount("/api/auth", routes::auth::routes())
-        .mount("/api/comments", routes::comments::routes())
-        .mount("/api", routes![
+        .attach(fairings::RequestIdFairing)
+        .attach(fairings::MetricsFairing::new(metrics_config))
+        .attach(fairings::RateLimitFairing::new(rate_limit_config))
+        .register("/", catchers![not_found, internal_error, default_catcher])
+        .mount("/api", fairings::traced(routes::openapi::routes()))
+        .mount("/api/auth", fairings::traced(routes::auth::routes()))
+        .mount("/api/comments", fairings::traced(routes::comments::routes()))
+        // Health probes（/api 下保留别名）
+        .mount("/", fairings::traced(routes::health::routes()))
+        .mount("/api", fairings::traced(routes::health::routes()))
+        .mount("/api", fairings::traced(routes![
             // Posts routes
             routes::posts::list_posts,
             routes::posts::get_post_by_id,
@@ -182,14 +269,31 @@ async fn rocket() -> _ {
             routes::users::get_user_profile,
             routes::users::list_readers,
             routes::users::get_reader_by_id,
-            // Nbnhhsh routes
-            routes::nbnhhsh::guess,
             // Pages routes
             routes::pages::get_page_by_slug,
             // Config routes
             routes::config::get_site_config,
             // AI routes
-            routes::ai::analyze_time_capsule,
             routes::ai::get_time_capsule,
-        ])
+            // Mail routes
+            routes::mail::unsubscribe_link,
+            routes::mail::unsubscribe_one_click,
+            routes::mail::get_preferences,
+            routes::mail::update_preferences,
+            // Webhook routes
+            routes::webhooks::list_webhooks,
+            routes::webhooks::create_webhook,
+            routes::webhooks::update_webhook,
+            routes::webhooks::delete_webhook,
+            routes::webhooks::list_deliveries,
+        ]))
+        // Rate limited routes (limits from `Settings.rate_limit`)
+        .mount("/api", fairings::traced(fairings::rate_limited(
+            services::RateLimitGroup::Nbnhhsh,
+            routes![routes::nbnhhsh::guess],
+        )))
+        .mount("/api", fairings::traced(fairings::rate_limited(
+            services::RateLimitGroup::AiTimeCapsule,
+            routes![routes::ai::analyze_time_capsule],
+        )))
 }
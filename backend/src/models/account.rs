@@ -3,6 +3,7 @@
 use crate::utils::serializers::*;
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Account model - links OAuth providers to Reader
 /// This is used for MongoDB storage - keeps native BSON types
@@ -52,11 +53,13 @@ fn default_account_datetime() -> bson::DateTime {
 
 /// Account response model for API responses
 /// This converts BSON types to JSON-friendly strings
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct AccountResponse {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     #[serde(rename = "userId", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub user_id: ObjectId,
     pub provider: String,
     #[serde(rename = "accountId")]
@@ -65,8 +68,10 @@ pub struct AccountResponse {
     pub access_token: String,
     pub scope: Option<String>,
     #[serde(rename = "createdAt", serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created_at: bson::DateTime,
     #[serde(rename = "updatedAt", serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub updated_at: bson::DateTime,
 }
 
@@ -2,17 +2,20 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// Category model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Category {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub name: String,
     pub slug: String,
     #[serde(rename = "type")]
     pub category_type: i32,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
 }
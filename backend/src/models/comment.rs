@@ -1,5 +1,8 @@
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::response::Pagination;
 
 /// 评论状态常量
 /// - 0: 未读 + 正常
@@ -9,14 +12,179 @@ use serde::{Deserialize, Serialize};
 #[allow(non_snake_case)]
 pub mod CommentState {
     pub const UNREAD: i32 = 0;
-    #[allow(dead_code)]
     pub const READ: i32 = 1;
     pub const SPAM: i32 = 2;
     pub const PENDING: i32 = 3;
+
+    /// 解析状态名（unread / read / spam / pending，不区分大小写）或数字
+    pub fn parse(value: &str) -> Option<i32> {
+        match value.to_ascii_lowercase().as_str() {
+            "unread" | "0" => Some(UNREAD),
+            "read" | "1" => Some(READ),
+            "spam" | "2" => Some(SPAM),
+            "pending" | "3" => Some(PENDING),
+            _ => None,
+        }
+    }
+}
+
+/// 评论目标类型（与目标所在的集合同名）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RefType {
+    /// 博文
+    Posts,
+    /// 手记
+    Notes,
+    /// 页面
+    Pages,
+}
+
+impl RefType {
+    pub const ALL: [RefType; 3] = [RefType::Posts, RefType::Notes, RefType::Pages];
+
+    /// 对应的集合名，同时也是评论上保存的 `refType`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefType::Posts => "posts",
+            RefType::Notes => "notes",
+            RefType::Pages => "pages",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ref_type| ref_type.as_str() == value)
+    }
+}
+
+/// 未配置 `commentOptions.reactions` 时允许的表情回应
+const DEFAULT_REACTIONS: &[&str] = &["👍", "❤️", "😄", "🎉", "😕", "👀"];
+
+pub fn default_reactions() -> Vec<String> {
+    DEFAULT_REACTIONS.iter().map(|r| r.to_string()).collect()
+}
+
+/// 某个表情回应的聚合结果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ReactionCount {
+    pub emoji: String,
+    pub count: i64,
+    /// 当前访客是否回应过
+    pub reacted: bool,
+}
+
+/// 评论过滤规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// 内容不含中文（`disableNoChinese`）
+    NoChinese,
+    /// 命中关键词黑名单
+    Keyword,
+    /// 命中正则黑名单
+    Regex,
+    /// 链接数量超过上限
+    Links,
+    /// 同一 IP 短时间内重复发表相同内容
+    Duplicate,
+    /// 邮箱被屏蔽
+    BlockedEmail,
+    /// IP 或 IP 段被屏蔽
+    BlockedIp,
+}
+
+/// 规则命中后的处理方式（按严重程度递增排列）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// 进入待审核，等待博主处理（不再交给 AI 审核）
+    Pending,
+    /// 直接标记为垃圾评论
+    Spam,
+    /// 拒绝发表
+    Reject,
+}
+
+/// 规则命中记录（保存在评论上）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RuleHit {
+    pub rule: RuleKind,
+    pub action: RuleAction,
+    /// 命中的关键词、正则、邮箱、IP 段等
+    pub detail: String,
+}
+
+/// 审核引擎
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationEngine {
+    /// 过滤规则
+    Rule,
+    /// 本地贝叶斯分类器
+    Bayes,
+    /// AI（大语言模型）
+    Llm,
+}
+
+/// 审核记录（保存在评论上，每次审核覆盖上一次的记录）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Moderation {
+    pub engine: ModerationEngine,
+    /// AI 模型名（仅 llm）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 评分：评分法为 0-10 分，贝叶斯分类器为垃圾概率（0-1），二分法和规则为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    pub is_spam: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[schema(value_type = Object)]
+    pub reviewed_at: mongodb::bson::DateTime,
+    /// 博主改判后的状态（通过或标记垃圾）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_state: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub overridden_at: Option<mongodb::bson::DateTime>,
+}
+
+impl Moderation {
+    /// 过滤规则的审核记录
+    pub fn rule(hit: &RuleHit) -> Self {
+        Self {
+            engine: ModerationEngine::Rule,
+            model: None,
+            score: None,
+            is_spam: hit.action == RuleAction::Spam,
+            reason: Some(hit.detail.clone()),
+            reviewed_at: mongodb::bson::DateTime::now(),
+            override_state: None,
+            overridden_at: None,
+        }
+    }
+}
+
+/// 贝叶斯分类器的训练标签（来自博主的审核操作）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SpamLabel {
+    Spam,
+    Ham,
+}
+
+impl SpamLabel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpamLabel::Spam => "spam",
+            SpamLabel::Ham => "ham",
+        }
+    }
 }
 
 /// 用户代理信息（浏览器/系统）
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UAInfo {
     pub browser: String,
@@ -26,11 +194,13 @@ pub struct UAInfo {
     pub device: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Comment {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub id: Option<ObjectId>,
+    #[schema(value_type = Object)]
     pub r#ref: ObjectId,
     #[serde(rename = "refType")]
     pub ref_type: String,
@@ -40,6 +210,7 @@ pub struct Comment {
     /// 评论状态: 0=未读+正常, 1=已读+正常, 2=垃圾, 3=待审核
     pub state: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Object>>)]
     pub children: Option<Vec<ObjectId>>,
     #[serde(rename = "commentsIndex")]
     pub comments_index: i32,
@@ -56,16 +227,43 @@ pub struct Comment {
     pub source: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub avatar: Option<String>,
+    #[schema(value_type = Object)]
     pub created: mongodb::bson::DateTime,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
     pub parent: Option<ObjectId>,
     /// 用户代理信息（浏览器/系统）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ua: Option<UAInfo>,
+    /// 发表评论的 Reader（旧评论没有该字段，按邮箱匹配作者）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub reader_id: Option<ObjectId>,
+    /// 最后一次编辑时间，未编辑过时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub edited_at: Option<mongodb::bson::DateTime>,
+    /// 删除时间：已删除的评论保留为墓碑（清空作者与内容），以便其回复仍能挂载
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub deleted_at: Option<mongodb::bson::DateTime>,
+    /// 博主在审核队列中删除的评论保留原内容，在此时间之前可以恢复，之后才会被清理
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub purge_after: Option<mongodb::bson::DateTime>,
+    /// 发表或编辑时命中的过滤规则
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_hit: Option<RuleHit>,
+    /// 已作为哪类样本训练过贝叶斯分类器（改判时先撤销旧样本）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trained_as: Option<SpamLabel>,
+    /// 最近一次审核的记录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moderation: Option<Moderation>,
 }
 
 impl Default for Comment {
@@ -92,11 +290,18 @@ impl Default for Comment {
             url: None,
             parent: None,
             ua: None,
+            reader_id: None,
+            edited_at: None,
+            deleted_at: None,
+            purge_after: None,
+            rule_hit: None,
+            trained_as: None,
+            moderation: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CommentTree {
     #[serde(rename = "_id")]
@@ -107,6 +312,7 @@ pub struct CommentTree {
     pub author: String,
     pub text: String,
     pub state: i32,
+    #[schema(no_recursion)]
     pub children: Vec<CommentTree>,
     #[serde(rename = "commentsIndex")]
     pub comments_index: i32,
@@ -130,13 +336,28 @@ pub struct CommentTree {
     /// 用户代理信息（浏览器/系统）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ua: Option<UAInfo>,
+    /// 最后一次编辑时间，未编辑过时不返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<String>,
+    /// 评论已被删除（墓碑节点，作者与内容为空）
+    #[serde(rename = "isDeleted", skip_serializing_if = "Option::is_none")]
+    pub is_deleted: Option<bool>,
+    /// 表情回应（按数量降序）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reactions: Vec<ReactionCount>,
+    /// 可见回复总数（仅列表中的根评论）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_count: Option<usize>,
+    /// 加载剩余回复的游标（仅在还有未内联的回复时出现）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies_cursor: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateCommentRequest {
     pub r#ref: String,
-    pub ref_type: String,
+    pub ref_type: RefType,
     #[serde(default)]
     pub author: Option<String>,
     #[serde(default)]
@@ -154,22 +375,122 @@ pub struct CreateCommentRequest {
     pub ua: Option<UAInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCommentRequest {
     pub text: String,
 }
 
-#[derive(Debug, Serialize)]
+/// 评论列表（分页的根评论，每个根评论内联少量回复）
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CommentListResponse {
     pub comments: Vec<CommentTree>,
+    /// 可见评论总数（含回复，不含已删除的墓碑）
     pub count: i64,
+    /// 根评论分页信息
+    pub pagination: Pagination,
 }
 
-impl Default for CommentListResponse {
-    fn default() -> Self {
+/// 展开的回复（按时间升序；父评论不在本批中的回复位于顶层，通过 `parent` 挂载）
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentRepliesResponse {
+    pub comments: Vec<CommentTree>,
+    /// 该评论下的可见回复总数
+    pub count: usize,
+    /// 下一批的游标，没有更多时为空
+    pub next_cursor: Option<String>,
+}
+
+/// 审核队列中的评论（博主视图，包含邮箱、IP 和所属文章标题）
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminComment {
+    pub id: String,
+    pub r#ref: String,
+    pub ref_type: String,
+    /// 所属文章 / 页面 / 手记的标题，找不到时为空
+    pub ref_title: Option<String>,
+    pub author: String,
+    pub mail: String,
+    pub text: String,
+    pub state: i32,
+    pub is_whispers: bool,
+    pub pin: bool,
+    pub parent: Option<String>,
+    pub ip: Option<String>,
+    pub location: Option<String>,
+    pub avatar: Option<String>,
+    pub url: Option<String>,
+    pub created: String,
+    pub edited_at: Option<String>,
+    pub deleted_at: Option<String>,
+    /// 删除的评论可恢复的截止时间
+    pub purge_after: Option<String>,
+    /// 命中的过滤规则
+    pub rule_hit: Option<RuleHit>,
+    /// 最近一次审核的记录
+    pub moderation: Option<AdminModeration>,
+}
+
+/// 审核记录（博主视图）
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminModeration {
+    pub engine: ModerationEngine,
+    pub model: Option<String>,
+    /// 评分：评分法为 0-10 分，贝叶斯分类器为垃圾概率（0-1）
+    pub score: Option<f64>,
+    pub is_spam: bool,
+    pub reason: Option<String>,
+    pub reviewed_at: String,
+    /// 博主改判后的状态
+    pub override_state: Option<i32>,
+    pub overridden_at: Option<String>,
+}
+
+impl From<Moderation> for AdminModeration {
+    fn from(moderation: Moderation) -> Self {
+        let rfc3339 = |dt: mongodb::bson::DateTime| dt.to_chrono().to_rfc3339();
         Self {
-            comments: vec![],
-            count: 0,
+            engine: moderation.engine,
+            model: moderation.model,
+            score: moderation.score,
+            is_spam: moderation.is_spam,
+            reason: moderation.reason,
+            reviewed_at: rfc3339(moderation.reviewed_at),
+            override_state: moderation.override_state,
+            overridden_at: moderation.overridden_at.map(rfc3339),
         }
     }
 }
+
+/// 批量审核操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkCommentAction {
+    /// 未读 → 已读
+    Read,
+    /// 待审核 / 垃圾 → 已读（公开），并发送评论通知
+    Approve,
+    /// 标记为垃圾评论
+    Spam,
+    /// 删除（保留原内容，期限内可恢复）
+    Delete,
+    /// 恢复在审核队列中删除的评论
+    Restore,
+    /// 重新进行 AI 审核（需启用 AI 审核）
+    Review,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkCommentRequest {
+    pub ids: Vec<String>,
+    pub action: BulkCommentAction,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkCommentResult {
+    pub action: BulkCommentAction,
+    /// 实际被修改的评论数量（状态不符的评论会被跳过）
+    pub updated: u64,
+}
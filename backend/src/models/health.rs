@@ -0,0 +1,71 @@
+//! Health model - 存活与就绪检查结果
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 存活检查结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthStatus {
+    pub status: &'static str,
+}
+
+/// 就绪检查结果
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessReport {
+    /// 是否可以接收流量（仅取决于 MongoDB）
+    pub ready: bool,
+    pub database: DatabaseHealth,
+    pub ip_service: IpServiceHealth,
+    pub change_stream: ChangeStreamHealthReport,
+    pub options: OptionsHealth,
+    pub ai: AiHealth,
+}
+
+/// MongoDB 连接状态
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseHealth {
+    pub connected: bool,
+    pub latency_ms: Option<u64>,
+    /// 不可用原因（`unreachable` 或超时），详细错误只写入日志
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// IP 地理位置服务状态
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IpServiceHealth {
+    /// ip2region 数据库是否加载成功
+    pub loaded: bool,
+}
+
+/// Change Stream 状态
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeStreamHealthReport {
+    pub enabled: bool,
+    pub connected: bool,
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+/// 站点配置快照状态
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionsHealth {
+    /// 快照最近一次从数据库加载的时间
+    pub loaded_at: DateTime<Utc>,
+}
+
+/// AI 配置状态
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AiHealth {
+    /// 配置是否可以正常解析
+    pub config_valid: bool,
+    /// 是否启用 AI 功能
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
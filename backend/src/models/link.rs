@@ -2,12 +2,14 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// Link (Friend) model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Link {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub name: String,
     pub url: String,
@@ -15,6 +17,7 @@ pub struct Link {
     pub description: String,
     pub state: i32,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     pub email: Option<String>,
 }
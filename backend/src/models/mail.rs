@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 通知邮件类型（每种类型可单独退订）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MailKind {
+    /// 有人回复了我的评论
+    Replies,
+    /// 站点有新评论（发给站长）
+    NewComments,
+}
+
+impl MailKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MailKind::Replies => "replies",
+            MailKind::NewComments => "new_comments",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "replies" => Some(MailKind::Replies),
+            "new_comments" => Some(MailKind::NewComments),
+            _ => None,
+        }
+    }
+}
+
+/// 邮件订阅偏好（按邮箱保存，未保存时全部开启）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MailPreferences {
+    /// 评论被回复时通知
+    pub replies: bool,
+    /// 站点有新评论时通知（仅对站长有效）
+    pub new_comments: bool,
+}
+
+impl Default for MailPreferences {
+    fn default() -> Self {
+        Self {
+            replies: true,
+            new_comments: true,
+        }
+    }
+}
+
+impl MailPreferences {
+    /// 是否接收某类邮件
+    pub fn allows(&self, kind: MailKind) -> bool {
+        match kind {
+            MailKind::Replies => self.replies,
+            MailKind::NewComments => self.new_comments,
+        }
+    }
+}
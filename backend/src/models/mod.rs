@@ -15,15 +15,20 @@ pub mod time_capsule;
 pub mod account;
 pub mod jwt;
 pub mod conversions;
+pub mod health;
+pub mod mail;
+pub mod webhook;
 
 // Re-export commonly used types
-pub use response::{ApiResponse, Pagination, PaginatedData, PaginatedResponse};
+pub use response::{ApiResponse, EmptyResponse, Pagination, PaginatedData};
 pub use post::{Post, PostWithCategory};
 pub use note::Note;
 pub use category::Category;
-pub use comment::{Comment, CommentState, CommentTree, CreateCommentRequest, UpdateCommentRequest, CommentListResponse};
+pub use comment::{Comment, CommentState, CommentTree, CreateCommentRequest, UpdateCommentRequest, CommentListResponse, CommentRepliesResponse, ReactionCount, default_reactions, AdminComment, BulkCommentAction, BulkCommentRequest, BulkCommentResult, RuleAction, RuleHit, RuleKind, SpamLabel, Moderation, ModerationEngine, AdminModeration, RefType};
 pub use link::Link;
+pub use mail::{MailKind, MailPreferences};
 pub use page::Page;
+pub use webhook::{Webhook, WebhookEvent, WebhookResponse, WebhookDelivery, WebhookDeliveryStatus, CreateWebhookRequest, UpdateWebhookRequest};
 pub use recently::Recently;
 pub use user::{User, Reader, ReaderResponse, GitHubUser, QQUser};
 pub use options::*;
@@ -2,19 +2,23 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// Note (Diary) model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Note {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub nid: i32,
     pub title: String,
     pub text: String,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     #[serde(default, serialize_with = "serialize_optional_datetime")]
+    #[schema(value_type = Option<String>, format = DateTime)]
     pub modified: Option<bson::DateTime>,
     #[serde(default)]
     pub mood: Option<String>,
@@ -35,6 +39,7 @@ pub struct Note {
     #[serde(default)]
     pub password: Option<String>,
     #[serde(rename = "publicAt", default, serialize_with = "serialize_optional_datetime")]
+    #[schema(value_type = Option<String>, format = DateTime)]
     pub public_at: Option<bson::DateTime>,
     #[serde(default)]
     pub coordinates: Option<String>,
@@ -44,7 +49,7 @@ pub struct Note {
     pub ai_summary: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct NoteImage {
     #[serde(default)]
     pub src: Option<String>,
@@ -56,7 +61,7 @@ pub struct NoteImage {
     pub accent: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct NoteCount {
     pub read: i32,
     pub like: i32,
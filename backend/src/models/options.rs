@@ -1,6 +1,7 @@
 //! Site options models (safe for frontend exposure)
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 // ============================================================================
 // Raw Option Document (Internal Use Only)
@@ -18,7 +19,7 @@ pub struct RawOption {
 // ============================================================================
 
 /// SEO configuration (safe to expose)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct SeoOptions {
     pub title: String,
     pub description: String,
@@ -26,7 +27,7 @@ pub struct SeoOptions {
 }
 
 /// URL configuration (safe to expose)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct UrlOptions {
     #[serde(rename = "wsUrl")]
     pub ws_url: Option<String>,
@@ -39,14 +40,14 @@ pub struct UrlOptions {
 }
 
 /// Feature list configuration (safe to expose)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct FeatureListOptions {
     #[serde(rename = "emailSubscribe", default)]
     pub email_subscribe: bool,
 }
 
 /// Friend link options (safe to expose)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct FriendLinkOptions {
     #[serde(rename = "allowApply", default)]
     pub allow_apply: bool,
@@ -55,22 +56,35 @@ pub struct FriendLinkOptions {
 }
 
 /// Comment options - only safe fields (partial exposure)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct CommentOptionsPublic {
     #[serde(rename = "disableComment", default)]
     pub disable_comment: bool,
     #[serde(rename = "disableNoChinese", default)]
     pub disable_no_chinese: bool,
+    /// Allowed emoji reactions
+    #[serde(default = "super::comment::default_reactions")]
+    pub reactions: Vec<String>,
+}
+
+impl Default for CommentOptionsPublic {
+    fn default() -> Self {
+        Self {
+            disable_comment: false,
+            disable_no_chinese: false,
+            reactions: super::comment::default_reactions(),
+        }
+    }
 }
 
 /// OAuth public configuration (safe to expose)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct OAuthPublicOptions {
     pub providers: Vec<OAuthProvider>,
     pub github_client_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct OAuthProvider {
     #[serde(rename = "type")]
     pub provider_type: String,
@@ -78,7 +92,7 @@ pub struct OAuthProvider {
 }
 
 /// Algolia search options - only public fields (partial exposure)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct AlgoliaPublicOptions {
     pub enable: bool,
     #[serde(rename = "appId")]
@@ -88,7 +102,7 @@ pub struct AlgoliaPublicOptions {
 }
 
 /// Admin extra - only safe fields (partial exposure)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct AdminExtraPublic {
     pub title: Option<String>,
     pub background: Option<String>,
@@ -99,7 +113,7 @@ pub struct AdminExtraPublic {
 // ============================================================================
 
 /// Aggregated site configuration that is safe to expose to frontend
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 pub struct SiteConfig {
     pub seo: SeoOptions,
     pub url: UrlOptions,
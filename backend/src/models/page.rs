@@ -2,17 +2,20 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// Page model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Page {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub title: String,
     pub text: String,
     pub slug: String,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     #[serde(rename = "allowComment", default)]
     pub allow_comment: bool,
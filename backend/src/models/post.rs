@@ -2,26 +2,31 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 use super::Category;
 
 /// Post (Article) model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Post {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub title: String,
     pub text: String,
     pub slug: String,
     #[serde(rename = "categoryId", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub category_id: ObjectId,
     #[serde(default)]
     pub summary: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     #[serde(default, serialize_with = "serialize_optional_datetime")]
+    #[schema(value_type = Option<String>, format = DateTime)]
     pub modified: Option<bson::DateTime>,
     #[serde(rename = "allowComment", default)]
     pub allow_comment: bool,
@@ -36,14 +41,16 @@ pub struct Post {
 }
 
 /// Post with populated category information
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct PostWithCategory {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub title: String,
     pub text: String,
     pub slug: String,
     #[serde(rename = "categoryId", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub category_id: ObjectId,
     pub category: Option<Category>,
     #[serde(default)]
@@ -53,8 +60,10 @@ pub struct PostWithCategory {
     #[serde(default)]
     pub tags: Vec<String>,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     #[serde(default, serialize_with = "serialize_optional_datetime")]
+    #[schema(value_type = Option<String>, format = DateTime)]
     pub modified: Option<bson::DateTime>,
     #[serde(rename = "allowComment", default)]
     pub allow_comment: bool,
@@ -91,7 +100,7 @@ impl From<Post> for PostWithCategory {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct PostImage {
     #[serde(default)]
     pub src: Option<String>,
@@ -2,19 +2,23 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// Recently model (Moments)
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Recently {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub content: String,
     pub up: i32,
     pub down: i32,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     #[serde(default, serialize_with = "serialize_optional_object_id")]
+    #[schema(value_type = Option<String>)]
     pub ref_id: Option<ObjectId>,
     #[serde(default, rename = "refType")]
     pub ref_type: Option<String>,
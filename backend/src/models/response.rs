@@ -2,17 +2,26 @@
 
 use rocket::serde::json::Json;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::request_id;
 
 /// Standard API response wrapper
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ApiResponse<T> {
     pub code: u16,
     pub status: ResponseStatus,
     pub message: String,
     pub data: T,
+    /// 请求 ID（仅失败响应携带，对应响应头 X-Request-Id 和日志中的 request_id）
+    #[serde(rename = "requestId", default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// 机器可读错误码（仅失败响应携带，见 `crate::error::AppError::error_code`）
+    #[serde(rename = "errorCode", default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ResponseStatus {
     Success,
@@ -27,6 +36,8 @@ impl<T> ApiResponse<T> {
             status: ResponseStatus::Success,
             message: "Success".to_string(),
             data,
+            request_id: None,
+            error_code: None,
         }
     }
 
@@ -37,18 +48,39 @@ impl<T> ApiResponse<T> {
             status: ResponseStatus::Success,
             message,
             data,
+            request_id: None,
+            error_code: None,
         }
     }
 
-    /// Create an error response with empty data
-    pub fn error(code: u16, message: String) -> ApiResponse<()> {
-        ApiResponse {
+    /// Create an error response carrying data
+    pub fn error_with_data(code: u16, message: String, data: T) -> Self {
+        Self {
             code,
             status: ResponseStatus::Failed,
             message,
-            data: (),
+            data,
+            request_id: request_id::current(),
+            error_code: None,
         }
     }
+
+    /// Create an error response with empty data
+    pub fn error(code: u16, message: String) -> ApiResponse<()> {
+        ApiResponse::error_with_data(code, message, ())
+    }
+
+    /// Attach a request ID (for responses built outside a route handler, e.g. catchers)
+    pub fn with_request_id(mut self, request_id: String) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Attach a machine-readable error code
+    pub fn with_error_code(mut self, error_code: &str) -> Self {
+        self.error_code = Some(error_code.to_string());
+        self
+    }
 }
 
 /// Auth-specific response helpers
@@ -62,54 +94,25 @@ impl<T> ApiResponse<T> {
     pub fn json_success_with_message(data: T, message: String) -> Json<Self> {
         Json(Self::success_with_message(data, message))
     }
-
-    /// Create a JSON error response with default data
-    pub fn json_error_with_default(code: u16, message: String) -> Json<Self>
-    where
-        T: Default,
-    {
-        Json(ApiResponse {
-            code,
-            status: ResponseStatus::Failed,
-            message,
-            data: T::default(),
-        })
-    }
 }
 
-impl ApiResponse<()> {
-    /// Create a JSON error response
-    #[allow(unused)]
-    pub fn json_error(code: u16, message: String) -> Json<Self> {
-        Json(Self::error(code, message))
-    }
-
-    /// Common auth error responses
-    #[allow(unused)]
-    pub fn unauthorized(message: String) -> Json<Self> {
-        Json(Self::error(401, message))
-    }
-
-    #[allow(unused)]
-    pub fn forbidden(message: String) -> Json<Self> {
-        Json(Self::error(403, message))
-    }
-
-    pub fn not_found(message: String) -> Json<Self> {
-        Json(Self::error(404, message))
-    }
-
-    pub fn bad_request(message: String) -> Json<Self> {
-        Json(Self::error(400, message))
-    }
-
-    pub fn internal_error(message: String) -> Json<Self> {
-        Json(Self::error(500, message))
-    }
+/// `data` 为 null 的响应（失败响应，或不返回数据的操作），仅用于 OpenAPI 文档
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct EmptyResponse {
+    pub code: u16,
+    pub status: ResponseStatus,
+    pub message: String,
+    #[schema(value_type = Option<Object>)]
+    pub data: (),
+    #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(rename = "errorCode", skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
 }
 
 /// Pagination metadata
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Pagination {
     pub total: i64,
     pub current_page: i64,
@@ -120,10 +123,8 @@ pub struct Pagination {
 }
 
 /// Paginated response wrapper
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PaginatedData<T> {
     pub items: Vec<T>,
     pub pagination: Pagination,
 }
-
-pub type PaginatedResponse<T> = ApiResponse<PaginatedData<T>>;
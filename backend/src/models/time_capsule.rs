@@ -2,10 +2,11 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// 时效性等级
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TimeSensitivity {
     /// 高时效性 - 技术版本、API、框架等易过期内容
@@ -41,7 +42,7 @@ pub struct TimeCapsule {
 }
 
 /// 创建 Time Capsule 的请求体
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TimeCapsuleRequest {
     /// 文章 ID
     #[serde(rename = "refId")]
@@ -56,7 +57,7 @@ fn default_ref_type() -> String {
 }
 
 /// Time Capsule 分析响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TimeCapsuleResponse {
     pub sensitivity: TimeSensitivity,
     pub reason: String,
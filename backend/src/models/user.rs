@@ -2,12 +2,14 @@
 
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::utils::serializers::*;
 
 /// User model (non-sensitive data only)
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct User {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub username: String,
     pub name: String,
@@ -16,8 +18,10 @@ pub struct User {
     pub mail: String,
     pub url: String,
     #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: bson::DateTime,
     #[serde(rename = "lastLoginTime", serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub last_login_time: bson::DateTime,
     #[serde(rename = "socialIds", default)]
     pub social_ids: Option<UserSocialIds>,
@@ -40,7 +44,7 @@ impl Default for User {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct UserSocialIds {
     pub github: Option<String>,
     pub bilibili: Option<String>,
@@ -71,9 +75,10 @@ pub struct QQUser {
 
 /// Reader model (non-sensitive data only)
 /// This is used for MongoDB storage - keeps native BSON types
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Reader {
     #[serde(rename = "_id")]
+    #[schema(value_type = Object)]
     pub id: ObjectId,
     #[serde(default)]
     pub email: String,
@@ -88,8 +93,10 @@ pub struct Reader {
     #[serde(rename = "emailVerified", default)]
     pub email_verified: Option<bool>,
     #[serde(rename = "createdAt", default = "default_datetime", deserialize_with = "deserialize_flexible_datetime")]
+    #[schema(value_type = Object)]
     pub created_at: bson::DateTime,
     #[serde(rename = "updatedAt", default = "default_datetime", deserialize_with = "deserialize_flexible_datetime")]
+    #[schema(value_type = Object)]
     pub updated_at: bson::DateTime,
 }
 
@@ -100,9 +107,10 @@ fn default_datetime() -> bson::DateTime {
 
 /// Reader response model for API responses
 /// This converts BSON types to JSON-friendly strings
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct ReaderResponse {
     #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
     pub id: ObjectId,
     pub email: String,
     pub name: String,
@@ -113,8 +121,10 @@ pub struct ReaderResponse {
     #[serde(rename = "emailVerified")]
     pub email_verified: Option<bool>,
     #[serde(rename = "createdAt", serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub created_at: bson::DateTime,
     #[serde(rename = "updatedAt", serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
     pub updated_at: bson::DateTime,
 }
 
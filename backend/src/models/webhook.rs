@@ -0,0 +1,149 @@
+//! Webhook model - 事件订阅与投递记录
+
+use mongodb::bson::{oid::ObjectId, DateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::serializers::*;
+
+/// 可订阅的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEvent {
+    /// 新评论（仅直接公开的评论，待审核的评论通过审核时推送 `CommentApproved`）
+    #[serde(rename = "comment.created")]
+    CommentCreated,
+    /// 评论通过审核（AI 审核或博主批量通过）
+    #[serde(rename = "comment.approved")]
+    CommentApproved,
+    /// AI 审核判定评论为垃圾
+    #[serde(rename = "comment.spam")]
+    CommentSpam,
+    /// 博文发布
+    #[serde(rename = "post.published")]
+    PostPublished,
+    /// 友链申请
+    #[serde(rename = "link.applied")]
+    LinkApplied,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::CommentCreated => "comment.created",
+            WebhookEvent::CommentApproved => "comment.approved",
+            WebhookEvent::CommentSpam => "comment.spam",
+            WebhookEvent::PostPublished => "post.published",
+            WebhookEvent::LinkApplied => "link.applied",
+        }
+    }
+}
+
+/// Webhook 订阅（`webhooks` 集合）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// HMAC-SHA256 签名密钥
+    pub secret: String,
+    pub enabled: bool,
+    pub created: DateTime,
+    pub updated_at: Option<DateTime>,
+}
+
+/// Webhook 响应（不含密钥）
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookResponse {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub enabled: bool,
+    pub created: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// 签名密钥，仅在创建时返回一次
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        Self {
+            id: webhook.id.to_hex(),
+            url: webhook.url,
+            events: webhook.events,
+            enabled: webhook.enabled,
+            created: webhook.created.to_chrono().to_rfc3339(),
+            updated_at: webhook.updated_at.map(|dt| dt.to_chrono().to_rfc3339()),
+            secret: None,
+        }
+    }
+}
+
+/// 创建 Webhook 请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// 签名密钥，留空时自动生成
+    pub secret: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 更新 Webhook 请求（未提供的字段保持不变）
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub enabled: Option<bool>,
+}
+
+/// 投递状态常量
+#[allow(non_snake_case)]
+pub mod WebhookDeliveryStatus {
+    /// 等待投递或等待重试
+    pub const PENDING: &str = "pending";
+    pub const SUCCEEDED: &str = "succeeded";
+    /// 重试次数用尽
+    pub const FAILED: &str = "failed";
+}
+
+/// 投递记录（`webhook_deliveries` 集合），同时作为持久化的投递队列
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    #[serde(rename = "_id", serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
+    pub id: ObjectId,
+    #[serde(serialize_with = "serialize_object_id")]
+    #[schema(value_type = String)]
+    pub webhook: ObjectId,
+    pub event: WebhookEvent,
+    /// 事件数据（JSON）
+    pub payload: String,
+    pub status: String,
+    /// 已尝试次数
+    pub attempts: i32,
+    #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
+    pub next_attempt_at: DateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    #[serde(serialize_with = "serialize_datetime")]
+    #[schema(value_type = String, format = DateTime)]
+    pub created: DateTime,
+    #[serde(
+        default,
+        serialize_with = "serialize_optional_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub delivered_at: Option<DateTime>,
+}
@@ -1,16 +1,17 @@
 //! AI 相关路由
 
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::{doc, oid::ObjectId};
 use std::str::FromStr;
 use sha1::{Sha1, Digest};
 
+use crate::error::{ApiResult, AppError};
 use crate::models::{
-    ApiResponse, Post, Note, Page,
+    ApiResponse, EmptyResponse, Post, Note, Page,
     TimeCapsule, TimeCapsuleRequest, TimeCapsuleResponse, TimeSensitivity,
 };
-use crate::services::{AiService, ChatMessage, ChatRole};
+use crate::services::{AiService, ChatMessage, ChatRole, OptionsService};
 
 /// 计算内容 SHA1 哈希
 fn compute_sha1(content: &str) -> String {
@@ -63,7 +64,7 @@ fn build_analysis_prompt(title: &str, content: &str) -> Vec<ChatMessage> {
 
 /// 解析 AI 返回的 JSON
 fn parse_ai_response(response: &str) -> Result<(TimeSensitivity, String, Vec<String>), String> {
-    eprintln!("AI raw response: {}", response);
+    tracing::debug!("AI raw response: {}", response);
 
     // 尝试提取 JSON 部分
     let json_str = response
@@ -107,45 +108,53 @@ fn parse_ai_response(response: &str) -> Result<(TimeSensitivity, String, Vec<Str
 }
 
 /// 分析文章时效性
+#[utoipa::path(
+    tag = "ai",
+    request_body = TimeCapsuleRequest,
+    responses(
+        (status = 200, description = "时效性分析结果", body = ApiResponse<TimeCapsuleResponse>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+        (status = 502, description = "AI 服务调用失败", body = EmptyResponse),
+        (status = 503, description = "AI 服务未启用", body = EmptyResponse),
+    ),
+)]
 #[post("/ai/time-capsule", data = "<request>")]
 pub async fn analyze_time_capsule(
     db: &State<Database>,
+    options: &State<OptionsService>,
     request: Json<TimeCapsuleRequest>,
-) -> Result<Json<ApiResponse<TimeCapsuleResponse>>, Status> {
+) -> ApiResult<TimeCapsuleResponse> {
     // 1. 获取文章内容
     let (title, content) = match request.ref_type.as_str() {
         "post" => {
-            let object_id = ObjectId::from_str(&request.ref_id)
-                .map_err(|_| Status::BadRequest)?;
+            let object_id = ObjectId::from_str(&request.ref_id)?;
             let collection = db.collection::<Post>("posts");
             let post = collection
                 .find_one(doc! { "_id": object_id })
-                .await
-                .map_err(|_| Status::InternalServerError)?
-                .ok_or(Status::NotFound)?;
+                .await?
+                .ok_or_else(|| AppError::not_found("博文不存在"))?;
             (post.title, post.text)
         }
         "note" => {
-            let object_id = ObjectId::from_str(&request.ref_id)
-                .map_err(|_| Status::BadRequest)?;
+            let object_id = ObjectId::from_str(&request.ref_id)?;
             let collection = db.collection::<Note>("notes");
             let note = collection
                 .find_one(doc! { "_id": object_id })
-                .await
-                .map_err(|_| Status::InternalServerError)?
-                .ok_or(Status::NotFound)?;
+                .await?
+                .ok_or_else(|| AppError::not_found("手记不存在"))?;
             (format!("日记 #{}", note.nid), note.text)
         }
         "page" => {
             let collection = db.collection::<Page>("pages");
             let page = collection
                 .find_one(doc! { "slug": &request.ref_id })
-                .await
-                .map_err(|_| Status::InternalServerError)?
-                .ok_or(Status::NotFound)?;
+                .await?
+                .ok_or_else(|| AppError::not_found("页面不存在"))?;
             (page.title, page.text)
         }
-        _ => return Err(Status::BadRequest),
+        other => return Err(AppError::validation(format!("不支持的 refType: {}", other))),
     };
 
     // 2. 计算当前内容的 SHA1
@@ -157,7 +166,7 @@ pub async fn analyze_time_capsule(
         .sort(doc! { "created": -1 })
         .build();
 
-    eprintln!("Looking for refId: {}, current_hash: {}", &request.ref_id, content_hash);
+    tracing::debug!("Looking for refId: {}, current_hash: {}", &request.ref_id, content_hash);
 
     match capsules_collection
         .find_one(doc! { "refId": &request.ref_id })
@@ -165,10 +174,10 @@ pub async fn analyze_time_capsule(
         .await
     {
         Ok(Some(existing)) => {
-            eprintln!("Found existing record, db_hash: {}, current_hash: {}", existing.hash, content_hash);
+            tracing::debug!("Found existing record, db_hash: {}, current_hash: {}", existing.hash, content_hash);
             // 4. 如果 hash 匹配，直接返回缓存结果
             if existing.hash == content_hash {
-                eprintln!("Hash matched, returning cached result");
+                tracing::debug!("Hash matched, returning cached result");
                 return Ok(Json(ApiResponse::success(TimeCapsuleResponse {
                     sensitivity: existing.sensitivity,
                     reason: existing.reason,
@@ -176,42 +185,32 @@ pub async fn analyze_time_capsule(
                     is_new: false,
                 })));
             }
-            eprintln!("Hash mismatch, will call AI");
+            tracing::debug!("Hash mismatch, will call AI");
         }
         Ok(None) => {
-            eprintln!("No existing record found");
+            tracing::debug!("No existing record found");
         }
         Err(e) => {
-            eprintln!("Database query error: {:?}", e);
+            tracing::error!("Database query error: {:?}", e);
         }
     }
 
     // 调用 AI 服务分析
-    let ai_service = AiService::from_database(db.inner())
-        .await
-        .map_err(|e| {
-            eprintln!("Failed to init AI service: {}", e);
-            Status::InternalServerError
-        })?;
+    let ai_service = AiService::from_options(options)
+        .map_err(|e| AppError::internal(format!("初始化 AI 服务失败: {}", e)))?;
 
     if !ai_service.is_enabled() {
-        return Err(Status::ServiceUnavailable);
+        return Err(AppError::unavailable("AI 服务未启用"));
     }
 
     let messages = build_analysis_prompt(&title, &content);
     let ai_response = ai_service
         .chat(messages, Some(0.3), None)
         .await
-        .map_err(|e| {
-            eprintln!("AI request failed: {}", e);
-            Status::InternalServerError
-        })?;
+        .map_err(|e| AppError::upstream(format!("AI 请求失败: {}", e)))?;
 
     let (sensitivity, reason, markers) = parse_ai_response(&ai_response)
-        .map_err(|e| {
-            eprintln!("Failed to parse AI response: {}", e);
-            Status::InternalServerError
-        })?;
+        .map_err(AppError::upstream)?;
 
     // 保存到数据库
     let new_capsule = TimeCapsule {
@@ -227,11 +226,7 @@ pub async fn analyze_time_capsule(
 
     capsules_collection
         .insert_one(&new_capsule)
-        .await
-        .map_err(|e| {
-            eprintln!("Failed to save time capsule: {}", e);
-            Status::InternalServerError
-        })?;
+        .await?;
 
     Ok(Json(ApiResponse::success(TimeCapsuleResponse {
         sensitivity,
@@ -242,11 +237,20 @@ pub async fn analyze_time_capsule(
 }
 
 /// 获取文章的 Time Capsule 分析结果
+#[utoipa::path(
+    tag = "ai",
+    params(("ref_id" = String, Path, description = "文章 ID")),
+    responses(
+        (status = 200, description = "已有的时效性分析结果", body = ApiResponse<TimeCapsuleResponse>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/ai/time-capsule/<ref_id>")]
 pub async fn get_time_capsule(
     db: &State<Database>,
     ref_id: &str,
-) -> Result<Json<ApiResponse<TimeCapsuleResponse>>, Status> {
+) -> ApiResult<TimeCapsuleResponse> {
     let capsules_collection = db.collection::<TimeCapsule>("time_capsules");
 
     let find_options = mongodb::options::FindOneOptions::builder()
@@ -256,9 +260,8 @@ pub async fn get_time_capsule(
     let capsule = capsules_collection
         .find_one(doc! { "refId": ref_id })
         .with_options(find_options)
-        .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+        .await?
+        .ok_or_else(|| AppError::not_found("暂无时效性分析结果"))?;
 
     Ok(Json(ApiResponse::success(TimeCapsuleResponse {
         sensitivity: capsule.sensitivity,
@@ -2,26 +2,37 @@
 
 use rocket::serde::json::Json;
 use rocket::serde::Deserialize;
+use utoipa::ToSchema;
 use rocket::State;
 use mongodb::Database;
 
+use crate::error::{ApiResult, AppError};
 use crate::guards::AuthGuard;
-use crate::models::{ApiResponse, ReaderResponse};
+use crate::models::{ApiResponse, EmptyResponse, ReaderResponse};
 use crate::services::{ReaderRepository, AccountRepository};
 
 /// 更新头像请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAvatarRequest {
     pub provider: String, // "github" | "qq" | "gravatar"
 }
 use crate::services::auth::avatar::AvatarService;
 
+#[utoipa::path(
+    tag = "auth",
+    request_body = UpdateAvatarRequest,
+    responses(
+        (status = 200, description = "头像已更新", body = ApiResponse<ReaderResponse>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[put("/avatar", data = "<request>")]
-pub async fn update_avatar(auth: AuthGuard, request: Json<UpdateAvatarRequest>, db: &State<Database>) -> Result<Json<ApiResponse<ReaderResponse>>, Json<ApiResponse<()>>> {
+pub async fn update_avatar(auth: AuthGuard, request: Json<UpdateAvatarRequest>, db: &State<Database>) -> ApiResult<ReaderResponse> {
     let reader_repo = ReaderRepository::new(db);
-    let mut reader = reader_repo.find_by_id(auth.user_id).await
-        .map_err(|_| ApiResponse::internal_error("数据库查询失败".to_string()))?
-        .ok_or_else(|| ApiResponse::not_found("用户不存在".to_string()))?;
+    let mut reader = reader_repo.find_by_id(auth.user_id).await?
+        .ok_or_else(|| AppError::not_found("用户不存在"))?;
 
     let new_avatar = match request.provider.as_str() {
         "gravatar" => AvatarService::get_gravatar_url(&reader.email),
@@ -29,13 +40,13 @@ pub async fn update_avatar(auth: AuthGuard, request: Json<UpdateAvatarRequest>,
             let account_repo = AccountRepository::new(db);
             let accounts = account_repo.find_by_user_id(auth.user_id).await.unwrap_or_default();
             AvatarService::get_oauth_avatar(&request.provider, &accounts)
-                .ok_or_else(|| ApiResponse::not_found("未关联该账号".to_string()))?
+                .ok_or_else(|| AppError::not_found("未关联该账号"))?
         }
-        _ => return Err(ApiResponse::bad_request("不支持的提供商".to_string())),
+        _ => return Err(AppError::validation("不支持的提供商")),
     };
 
     reader.image = new_avatar;
-    reader_repo.update_reader(&reader).await.map_err(|_| ApiResponse::internal_error("更新失败".to_string()))?;
+    reader_repo.update_reader(&reader).await?;
 
     Ok(ApiResponse::json_success(reader.into()))
 }
\ No newline at end of file
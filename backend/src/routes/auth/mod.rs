@@ -9,16 +9,35 @@ pub use user::{get_current_user, get_accounts};
 pub use bind::{bind_anonymous_identity, skip_bind, get_bindable_identities};
 pub use avatar::update_avatar;
 
+use crate::fairings::rate_limited;
+use crate::services::RateLimitGroup;
+
+/// 认证路由的 OpenAPI 文档（挂载于 /api/auth）
+#[derive(utoipa::OpenApi)]
+#[openapi(paths(
+    oauth::oauth_redirect,
+    oauth::oauth_callback,
+    user::get_current_user,
+    user::get_accounts,
+    bind::bind_anonymous_identity,
+    bind::skip_bind,
+    avatar::update_avatar,
+    bind::get_bindable_identities,
+))]
+pub struct AuthApi;
+
 /// 注册所有认证路由
 pub fn routes() -> Vec<rocket::Route> {
-    routes![
+    let mut routes = routes![
         oauth_redirect,
-        oauth_callback,
         get_current_user,
         get_accounts,
         bind_anonymous_identity,
         skip_bind,
         update_avatar,
         get_bindable_identities,
-    ]
+    ];
+    // OAuth 回调会请求第三方 API，受限流约束
+    routes.extend(rate_limited(RateLimitGroup::OAuthCallback, routes![oauth_callback]));
+    routes
 }
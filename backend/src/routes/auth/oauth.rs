@@ -2,31 +2,36 @@
 
 use mongodb::Database;
 use rocket::http::{Cookie, CookieJar, SameSite};
-use rocket::serde::json::Json;
 use rocket::{response::Redirect, State};
 
 use crate::config::OAuthConfig;
-use crate::models::ApiResponse;
+use crate::error::{AppError, AuthError};
+use crate::models::EmptyResponse;
 use crate::services::auth::identity::{IdentityService, OAuthUserPayload};
-use crate::services::{GitHubOAuthService, OptionsRepository, QQOAuthService};
+use crate::services::{GitHubOAuthService, OptionsService, QQOAuthService};
 
 /// OAuth 重定向端点
 ///
 /// 路由: GET /api/auth/oauth/<provider>
+#[utoipa::path(
+    tag = "auth",
+    params(("provider" = String, Path, description = "github 或 qq")),
+    responses(
+        (status = 303, description = "跳转到 OAuth 提供商授权页"),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/oauth/<provider>")]
 pub async fn oauth_redirect(
     provider: &str,
     config: &State<OAuthConfig>,
-    db: &State<Database>,
-) -> Result<Redirect, Json<ApiResponse<()>>> {
-    log::info!("OAuth 重定向请求: provider={}", provider);
+    options: &State<OptionsService>,
+) -> Result<Redirect, AppError> {
+    tracing::info!("OAuth 重定向请求: provider={}", provider);
 
     // 1. 获取最新的 OAuth 配置（数据库优先）
-    let options_repo = OptionsRepository::new(db);
-    let db_oauth_options = options_repo.get_oauth_config().await.map_err(|e| {
-        log::error!("读取数据库配置失败: {}", e);
-        ApiResponse::internal_error("读取配置失败".to_string())
-    })?;
+    let db_oauth_options = options.oauth_options();
 
     let redirect_url = match provider {
         "github" => {
@@ -36,9 +41,7 @@ pub async fn oauth_redirect(
                 .unwrap_or_else(|| config.github_client_id.clone());
 
             if client_id.is_empty() {
-                return Err(ApiResponse::internal_error(
-                    "GitHub OAuth 未配置".to_string(),
-                ));
+                return Err(AuthError::ConfigError("GitHub OAuth 未配置".to_string()).into());
             }
 
             format!(
@@ -53,10 +56,7 @@ pub async fn oauth_redirect(
             qq_service.get_authorize_url()
         }
         _ => {
-            return Err(ApiResponse::bad_request(format!(
-                "不支持的提供商: {}",
-                provider
-            )))
+            return Err(AuthError::UnsupportedProvider(provider.to_string()).into())
         }
     };
 
@@ -66,28 +66,38 @@ pub async fn oauth_redirect(
 /// OAuth 回调端点
 ///
 /// 路由: GET /api/auth/oauth/<provider>/callback?code=xxx
+#[utoipa::path(
+    tag = "auth",
+    params(("provider" = String, Path, description = "github 或 qq"), ("code" = String, Query, description = "授权码")),
+    responses(
+        (status = 303, description = "登录完成，写入令牌后跳转回前端"),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/oauth/<provider>/callback?<code>")]
 pub async fn oauth_callback(
     provider: &str,
     code: String,
     config: &State<OAuthConfig>,
     db: &State<Database>,
+    options: &State<OptionsService>,
     cookies: &CookieJar<'_>,
-) -> Result<Redirect, Json<ApiResponse<()>>> {
-    log::info!("OAuth 回调处理开始: provider={}", provider);
+) -> Result<Redirect, AppError> {
+    tracing::info!("OAuth 回调处理开始: provider={}", provider);
 
     // 1. 获取第三方用户信息并转换为标准 Payload
     let payload_result = match provider {
-        "github" => handle_github_logic(&code, config, db).await,
+        "github" => handle_github_logic(&code, config, options).await,
         "qq" => handle_qq_logic(&code, config, db).await,
-        _ => Err(ApiResponse::bad_request("不支持的提供商".to_string())),
+        _ => Err(AuthError::UnsupportedProvider(provider.to_string()).into()),
     };
 
     // 如果获取第三方信息失败，重定向到前端并带上错误参数
     let payload = match payload_result {
         Ok(p) => p,
         Err(e) => {
-            let err_msg = e.0.message;
+            let err_msg = e.public_message();
             return Ok(Redirect::to(format!(
                 "{}/auth/callback?error={}",
                 config.frontend_url,
@@ -112,7 +122,7 @@ pub async fn oauth_callback(
     // 3. 颁发 JWT 令牌
     let token = id_service
         .issue_token(user_id, is_owner)
-        .map_err(|e| ApiResponse::internal_error(e))?;
+        .map_err(AppError::Internal)?;
 
     // 4. 设置 HttpOnly Cookie（用于后端 API 鉴权）
     let mut cookie = Cookie::new("auth_token", token.clone());
@@ -139,10 +149,9 @@ pub async fn oauth_callback(
 async fn handle_github_logic(
     code: &str,
     config: &OAuthConfig,
-    db: &Database,
-) -> Result<OAuthUserPayload, Json<ApiResponse<()>>> {
-    let options_repo = OptionsRepository::new(db);
-    let db_oauth = options_repo.get_oauth_config().await.unwrap_or_default();
+    options: &OptionsService,
+) -> Result<OAuthUserPayload, AppError> {
+    let db_oauth = options.oauth_options();
 
     let client_id = db_oauth
         .github_client_id
@@ -154,14 +163,14 @@ async fn handle_github_logic(
         .unwrap_or_else(|| config.github_client_secret.clone());
 
     if client_id.is_empty() || client_secret.is_empty() {
-        return Err(ApiResponse::internal_error("GitHub 配置缺失".into()));
+        return Err(AuthError::ConfigError("GitHub 配置缺失".into()).into());
     }
 
     let github_service = GitHubOAuthService::new(client_id, client_secret);
     let (user, access_token, scope) = github_service
         .oauth_flow(code)
         .await
-        .map_err(|e| ApiResponse::internal_error(format!("GitHub API 调用失败: {}", e)))?;
+        .map_err(|e| AuthError::OAuthFlowFailed(format!("GitHub API 调用失败: {}", e)))?;
 
     Ok(OAuthUserPayload {
         provider: "github".to_string(),
@@ -180,12 +189,12 @@ async fn handle_qq_logic(
     code: &str,
     config: &OAuthConfig,
     _db: &Database,
-) -> Result<OAuthUserPayload, Json<ApiResponse<()>>> {
+) -> Result<OAuthUserPayload, AppError> {
     let qq_service = QQOAuthService::new(config.qq_redirect_uri());
     let (user, openid, access_token) = qq_service
         .oauth_flow(code)
         .await
-        .map_err(|e| ApiResponse::internal_error(format!("QQ API 调用失败: {}", e)))?;
+        .map_err(|e| AuthError::OAuthFlowFailed(format!("QQ API 调用失败: {}", e)))?;
 
     Ok(OAuthUserPayload {
         provider: "qq".to_string(),
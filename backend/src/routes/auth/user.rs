@@ -1,12 +1,21 @@
-use rocket::serde::json::Json;
 use rocket::State;
 use mongodb::Database;
+use crate::error::{ApiResult, AppError};
 use crate::guards::AuthGuard;
-use crate::models::{ApiResponse, ReaderResponse, AccountResponse};
+use crate::models::{ApiResponse, EmptyResponse, ReaderResponse, AccountResponse};
 use crate::services::{ReaderRepository, AccountRepository};
 
+#[utoipa::path(
+    tag = "auth",
+    responses(
+        (status = 200, description = "当前登录用户", body = ApiResponse<ReaderResponse>),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[get("/me")]
-pub async fn get_current_user(auth: AuthGuard, db: &State<Database>) -> Result<Json<ApiResponse<ReaderResponse>>, Json<ApiResponse<()>>> {
+pub async fn get_current_user(auth: AuthGuard, db: &State<Database>) -> ApiResult<ReaderResponse> {
     let reader_repo = ReaderRepository::new(db);
     
     // 1. 尝试获取真实 Reader
@@ -16,8 +25,7 @@ pub async fn get_current_user(auth: AuthGuard, db: &State<Database>) -> Result<J
 
     // 2. 否则获取 Account 信息作为临时身份
     let account_repo = AccountRepository::new(db);
-    let accounts = account_repo.find_by_user_id(auth.user_id).await
-        .map_err(|_| ApiResponse::internal_error("数据库错误".to_string()))?;
+    let accounts = account_repo.find_by_user_id(auth.user_id).await?;
 
     if let Some(acc) = accounts.first() {
         let mut resp: ReaderResponse = acc.into();
@@ -25,14 +33,21 @@ pub async fn get_current_user(auth: AuthGuard, db: &State<Database>) -> Result<J
         return Ok(ApiResponse::json_success_with_message(resp, "临时身份".into()));
     }
 
-    Err(ApiResponse::not_found("用户不存在".into()))
+    Err(AppError::not_found("用户不存在"))
 }
 
+#[utoipa::path(
+    tag = "auth",
+    responses(
+        (status = 200, description = "当前用户绑定的 OAuth 账号", body = ApiResponse<Vec<AccountResponse>>),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[get("/accounts")]
-pub async fn get_accounts(auth: AuthGuard, db: &State<Database>) -> Result<Json<ApiResponse<Vec<AccountResponse>>>, Json<ApiResponse<()>>> {
+pub async fn get_accounts(auth: AuthGuard, db: &State<Database>) -> ApiResult<Vec<AccountResponse>> {
     let account_repo = AccountRepository::new(db);
-    let accounts = account_repo.find_by_user_id(auth.user_id).await
-        .map_err(|_| ApiResponse::internal_error("数据库错误".to_string()))?;
+    let accounts = account_repo.find_by_user_id(auth.user_id).await?;
 
     Ok(ApiResponse::json_success(
         accounts.into_iter().map(Into::into).collect()
@@ -1,26 +1,33 @@
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::doc;
 use futures::stream::TryStreamExt;
 
-use crate::models::{Category, ApiResponse};
+use crate::error::ApiResult;
+use crate::models::{Category, ApiResponse, EmptyResponse};
 
 /// List all categories
+#[utoipa::path(
+    tag = "categories",
+    responses(
+        (status = 200, description = "分类列表", body = ApiResponse<Vec<Category>>),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/categories")]
 pub async fn list_categories(
     db: &State<Database>,
-) -> Result<Json<ApiResponse<Vec<Category>>>, Status> {
+) -> ApiResult<Vec<Category>> {
     let collection = db.collection::<Category>("categories");
     
     let find_options = mongodb::options::FindOptions::builder()
         .sort(doc! { "created": -1 })
         .build();
 
-    let mut cursor = collection.find(doc! {}).with_options(find_options).await
-        .map_err(|_| Status::InternalServerError)?;
+    let mut cursor = collection.find(doc! {}).with_options(find_options).await?;
 
     let mut items = Vec::new();
-    while let Some(category) = cursor.try_next().await.map_err(|_| Status::InternalServerError)? {
+    while let Some(category) = cursor.try_next().await? {
         items.push(category);
     }
 
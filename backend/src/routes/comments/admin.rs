@@ -1,29 +1,52 @@
 //! 管理员评论操作路由
 
+use futures::stream::TryStreamExt;
 use mongodb::bson::{doc, oid::ObjectId};
 use rocket::serde::json::Json;
-use rocket::{State, http::Status, patch, delete};
+use rocket::{State, get, patch, post, delete};
 use std::str::FromStr;
+use tracing::Instrument;
 
-use crate::models::{ApiResponse, Comment};
+use crate::error::{ApiResult, AppError};
+use crate::models::{
+    ApiResponse, EmptyResponse, Comment, CommentState, AdminComment, BulkCommentAction,
+    BulkCommentRequest, BulkCommentResult, PaginatedData, Pagination, RefType, SpamLabel, WebhookEvent,
+};
 use crate::guards::OwnerGuard;
+use crate::services::comment::bayes::BayesClassifier;
+use crate::services::webhook::service::comment_payload;
+use crate::services::{
+    CommentNotifier, CommentService, Mailer, OptionsService, ReviewQueue, SpamDetector, WebhookService,
+};
+
+/// 单次批量操作的评论数量上限
+const MAX_BULK_IDS: usize = 100;
 
 /**
  * PATCH /api/comments/<id>/hide
  * 隐藏评论（仅管理员）- 使用 is_whispers 字段
  */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    responses(
+        (status = 200, description = "评论已隐藏", body = EmptyResponse),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[patch("/<id>/hide")]
 pub async fn hide_comment(
     db: &State<mongodb::Database>,
     _owner: OwnerGuard,
     id: String,
-) -> Result<Json<ApiResponse<()>>, Status> {
+) -> ApiResult<()> {
     let collection = db.collection::<Comment>("comments");
 
-    let oid = match ObjectId::from_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => return Err(Status::BadRequest),
-    };
+    let oid = ObjectId::from_str(&id)?;
 
     let update = doc! {
         "$set": {
@@ -31,34 +54,42 @@ pub async fn hide_comment(
         }
     };
 
-    match collection.update_one(doc! { "_id": oid }, update).await {
-        Ok(_) => Ok(Json(ApiResponse::success_with_message(
-            (),
-            "Comment hidden successfully".to_string(),
-        ))),
-        Err(e) => {
-            eprintln!("Failed to hide comment: {}", e);
-            Err(Status::InternalServerError)
-        }
+    let result = collection.update_one(doc! { "_id": oid }, update).await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("评论不存在"));
     }
+
+    Ok(Json(ApiResponse::success_with_message(
+        (),
+        "Comment hidden successfully".to_string(),
+    )))
 }
 
 /**
  * DELETE /api/comments/<id>/hide
  * 取消隐藏评论（仅管理员）- 使用 is_whispers 字段
  */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    responses(
+        (status = 200, description = "评论已取消隐藏", body = EmptyResponse),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[delete("/<id>/hide")]
 pub async fn unhide_comment(
     db: &State<mongodb::Database>,
     _owner: OwnerGuard,
     id: String,
-) -> Result<Json<ApiResponse<()>>, Status> {
+) -> ApiResult<()> {
     let collection = db.collection::<Comment>("comments");
 
-    let oid = match ObjectId::from_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => return Err(Status::BadRequest),
-    };
+    let oid = ObjectId::from_str(&id)?;
 
     let update = doc! {
         "$set": {
@@ -66,34 +97,42 @@ pub async fn unhide_comment(
         }
     };
 
-    match collection.update_one(doc! { "_id": oid }, update).await {
-        Ok(_) => Ok(Json(ApiResponse::success_with_message(
-            (),
-            "Comment unhidden successfully".to_string(),
-        ))),
-        Err(e) => {
-            eprintln!("Failed to unhide comment: {}", e);
-            Err(Status::InternalServerError)
-        }
+    let result = collection.update_one(doc! { "_id": oid }, update).await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("评论不存在"));
     }
+
+    Ok(Json(ApiResponse::success_with_message(
+        (),
+        "Comment unhidden successfully".to_string(),
+    )))
 }
 
 /**
  * PATCH /api/comments/<id>/pin
  * 置顶评论（仅管理员）
  */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    responses(
+        (status = 200, description = "评论已置顶", body = EmptyResponse),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[patch("/<id>/pin")]
 pub async fn pin_comment(
     db: &State<mongodb::Database>,
     _owner: OwnerGuard,
     id: String,
-) -> Result<Json<ApiResponse<()>>, Status> {
+) -> ApiResult<()> {
     let collection = db.collection::<Comment>("comments");
 
-    let oid = match ObjectId::from_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => return Err(Status::BadRequest),
-    };
+    let oid = ObjectId::from_str(&id)?;
 
     let update = doc! {
         "$set": {
@@ -101,34 +140,42 @@ pub async fn pin_comment(
         }
     };
 
-    match collection.update_one(doc! { "_id": oid }, update).await {
-        Ok(_) => Ok(Json(ApiResponse::success_with_message(
-            (),
-            "Comment pinned successfully".to_string(),
-        ))),
-        Err(e) => {
-            eprintln!("Failed to pin comment: {}", e);
-            Err(Status::InternalServerError)
-        }
+    let result = collection.update_one(doc! { "_id": oid }, update).await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("评论不存在"));
     }
+
+    Ok(Json(ApiResponse::success_with_message(
+        (),
+        "Comment pinned successfully".to_string(),
+    )))
 }
 
 /**
  * DELETE /api/comments/<id>/pin
  * 取消置顶评论（仅管理员）
  */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    responses(
+        (status = 200, description = "评论已取消置顶", body = EmptyResponse),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[delete("/<id>/pin")]
 pub async fn unpin_comment(
     db: &State<mongodb::Database>,
     _owner: OwnerGuard,
     id: String,
-) -> Result<Json<ApiResponse<()>>, Status> {
+) -> ApiResult<()> {
     let collection = db.collection::<Comment>("comments");
 
-    let oid = match ObjectId::from_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => return Err(Status::BadRequest),
-    };
+    let oid = ObjectId::from_str(&id)?;
 
     let update = doc! {
         "$set": {
@@ -136,14 +183,278 @@ pub async fn unpin_comment(
         }
     };
 
-    match collection.update_one(doc! { "_id": oid }, update).await {
-        Ok(_) => Ok(Json(ApiResponse::success_with_message(
-            (),
-            "Comment unpinned successfully".to_string(),
-        ))),
-        Err(e) => {
-            eprintln!("Failed to unpin comment: {}", e);
-            Err(Status::InternalServerError)
+    let result = collection.update_one(doc! { "_id": oid }, update).await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("评论不存在"));
+    }
+
+    Ok(Json(ApiResponse::success_with_message(
+        (),
+        "Comment unpinned successfully".to_string(),
+    )))
+}
+/**
+ * DELETE /api/comments/<id>/purge
+ * 彻底删除评论及其全部回复（仅管理员），返回删除的评论数量
+ */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    responses(
+        (status = 200, description = "评论及其回复已彻底删除", body = ApiResponse<u64>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[delete("/<id>/purge")]
+pub async fn purge_comment(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+    id: String,
+) -> ApiResult<u64> {
+    let oid = ObjectId::from_str(&id)?;
+
+    let deleted = CommentService::new(db.inner())
+        .hard_delete_cascade(oid)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::not_found("评论不存在"))?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        deleted,
+        "Comment purged successfully".to_string(),
+    )))
+}
+
+/**
+ * GET /api/comments/admin?<state>&<refType>&<q>&<page>&<size>&<deleted>
+ * 审核队列（仅管理员）：跨文章按状态列出评论（最新的在前），附带所属文章标题
+ */
+#[utoipa::path(
+    tag = "comments",
+    params(
+        ("state" = Option<String>, Query, description = "评论状态：unread、read、spam、pending 或 0-3"),
+        ("refType" = Option<String>, Query, description = "posts、pages 或 notes"),
+        ("q" = Option<String>, Query, description = "在内容、作者和邮箱中搜索"),
+        ("page" = Option<i64>, Query, description = "页码，从 1 开始"),
+        ("size" = Option<i64>, Query, description = "每页数量，默认 20，最大 100"),
+        ("deleted" = Option<bool>, Query, description = "为 true 时列出已删除的评论"),
+    ),
+    responses(
+        (status = 200, description = "评论列表", body = ApiResponse<PaginatedData<AdminComment>>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[get("/admin?<state>&<refType>&<q>&<page>&<size>&<deleted>")]
+#[allow(clippy::too_many_arguments, non_snake_case)]
+pub async fn list_moderation_queue(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+    state: Option<String>,
+    refType: Option<String>,
+    q: Option<String>,
+    page: Option<i64>,
+    size: Option<i64>,
+    deleted: Option<bool>,
+) -> ApiResult<PaginatedData<AdminComment>> {
+    let comment_service = CommentService::new(db.inner());
+    let collection = db.collection::<Comment>("comments");
+    let page = page.unwrap_or(1).max(1);
+    let size = size.unwrap_or(20).clamp(1, 100);
+
+    let state = match state.as_deref().filter(|s| !s.is_empty()) {
+        Some(value) => Some(
+            CommentState::parse(value).ok_or_else(|| AppError::validation("无效的评论状态"))?,
+        ),
+        None => None,
+    };
+    let ref_type = match refType.as_deref().filter(|t| !t.is_empty()) {
+        Some(value) => Some(RefType::parse(value).ok_or_else(|| AppError::validation("无效的评论目标类型"))?),
+        None => None,
+    };
+    let filter = CommentService::moderation_filter(
+        state,
+        ref_type,
+        q.as_deref(),
+        deleted.unwrap_or(false),
+    );
+
+    let total = collection.count_documents(filter.clone()).await? as i64;
+    let comments: Vec<Comment> = collection
+        .find(filter)
+        .sort(doc! { "created": -1, "_id": -1 })
+        .skip(((page - 1) * size) as u64)
+        .limit(size)
+        .await?
+        .try_collect()
+        .await?;
+
+    let titles = comment_service
+        .find_ref_titles(&comments)
+        .await
+        .map_err(AppError::Database)?;
+    let items = comments
+        .into_iter()
+        .map(|comment| CommentService::to_admin_comment(comment, &titles))
+        .collect();
+
+    let total_page = (total as f64 / size as f64).ceil() as i64;
+    let pagination = Pagination {
+        total,
+        current_page: page,
+        total_page,
+        size,
+        has_next_page: page < total_page,
+        has_prev_page: page > 1,
+    };
+
+    Ok(Json(ApiResponse::success(PaginatedData { items, pagination })))
+}
+
+/**
+ * POST /api/comments/admin/bulk
+ * 批量审核（仅管理员）：标记已读、通过、标记垃圾、删除、恢复、重新审核
+ *
+ * 状态不符的评论会被跳过（例如已读的评论不会被“通过”）；
+ * 通过审核的评论会推送 `comment.approved` Webhook 并发送评论通知邮件；
+ * 重新审核的评论加入审核队列，由 AI 审核后更新状态
+ */
+#[utoipa::path(
+    tag = "comments",
+    request_body = BulkCommentRequest,
+    responses(
+        (status = 200, description = "操作结果", body = ApiResponse<BulkCommentResult>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/admin/bulk", data = "<request>")]
+pub async fn bulk_moderate(
+    db: &State<mongodb::Database>,
+    options: &State<OptionsService>,
+    mailer: &State<Mailer>,
+    _owner: OwnerGuard,
+    request: Json<BulkCommentRequest>,
+) -> ApiResult<BulkCommentResult> {
+    if request.ids.is_empty() || request.ids.len() > MAX_BULK_IDS {
+        return Err(AppError::validation(format!("ids 数量必须在 1 到 {} 之间", MAX_BULK_IDS)));
+    }
+    let ids = request
+        .ids
+        .iter()
+        .map(|id| ObjectId::from_str(id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let comment_service = CommentService::new(db.inner());
+    let action = request.action;
+    let updated = match action {
+        BulkCommentAction::Read => {
+            let read = comment_service
+                .set_state(&ids, &[CommentState::UNREAD], CommentState::READ)
+                .await
+                .map_err(AppError::Database)?;
+            let count = read.len() as u64;
+            train_classifier(db.inner(), read, SpamLabel::Ham);
+            count
+        }
+        BulkCommentAction::Approve => {
+            let approved = comment_service
+                .set_state(&ids, &[CommentState::PENDING, CommentState::SPAM], CommentState::READ)
+                .await
+                .map_err(AppError::Database)?;
+            let count = approved.len() as u64;
+            comment_service
+                .record_override(&approved, CommentState::READ)
+                .await
+                .map_err(AppError::Database)?;
+            ReviewQueue::new(db.inner()).cancel(&approved).await.map_err(AppError::Database)?;
+            train_classifier(db.inner(), approved.clone(), SpamLabel::Ham);
+            if !approved.is_empty() {
+                let db_clone = db.inner().clone();
+                let mailer_clone = mailer.inner().clone();
+                tokio::spawn(
+                    async move { publish_approved(&db_clone, &mailer_clone, approved).await }
+                        .in_current_span(),
+                );
+            }
+            count
+        }
+        BulkCommentAction::Spam => {
+            let spam = comment_service
+                .set_state(
+                    &ids,
+                    &[CommentState::UNREAD, CommentState::READ, CommentState::PENDING],
+                    CommentState::SPAM,
+                )
+                .await
+                .map_err(AppError::Database)?;
+            let count = spam.len() as u64;
+            comment_service
+                .record_override(&spam, CommentState::SPAM)
+                .await
+                .map_err(AppError::Database)?;
+            ReviewQueue::new(db.inner()).cancel(&spam).await.map_err(AppError::Database)?;
+            train_classifier(db.inner(), spam, SpamLabel::Spam);
+            count
+        }
+        BulkCommentAction::Delete => comment_service.trash(&ids).await.map_err(AppError::Database)?,
+        BulkCommentAction::Restore => comment_service.restore(&ids).await.map_err(AppError::Database)?,
+        BulkCommentAction::Review => {
+            if !SpamDetector::is_ai_review_enabled(options) {
+                return Err(AppError::validation("未启用 AI 审核"));
+            }
+            let queue = ReviewQueue::new(db.inner());
+            let reviewable = comment_service.reviewable(&ids).await.map_err(AppError::Database)?;
+            for id in &reviewable {
+                queue.enqueue(*id, false).await.map_err(AppError::Database)?;
+            }
+            reviewable.len() as u64
+        }
+    };
+
+    tracing::info!("批量审核 {:?}: 请求 {} 条，修改 {} 条", action, ids.len(), updated);
+    Ok(Json(ApiResponse::success(BulkCommentResult { action, updated })))
+}
+
+/// 在后台用博主的审核结果训练贝叶斯分类器
+fn train_classifier(db: &mongodb::Database, ids: Vec<ObjectId>, label: SpamLabel) {
+    if ids.is_empty() {
+        return;
+    }
+    let db = db.clone();
+    tokio::spawn(
+        async move {
+            match BayesClassifier::new(&db).train(&ids, label).await {
+                Ok(trained) => tracing::info!("贝叶斯分类器已训练 {} 条{}样本", trained, label.as_str()),
+                Err(e) => tracing::error!("训练贝叶斯分类器失败: {}", e),
+            }
         }
+        .in_current_span(),
+    );
+}
+
+/// 博主通过审核的评论：推送 Webhook 并发送评论通知
+async fn publish_approved(db: &mongodb::Database, mailer: &Mailer, ids: Vec<ObjectId>) {
+    let webhooks = WebhookService::new(db);
+    for id in ids {
+        match db.collection::<Comment>("comments").find_one(doc! { "_id": id }).await {
+            Ok(Some(comment)) => {
+                webhooks.emit(WebhookEvent::CommentApproved, comment_payload(&comment)).await;
+            }
+            Ok(None) => continue,
+            Err(e) => tracing::error!("读取评论 {} 失败: {}", id, e),
+        }
+        CommentNotifier::comment_published(db, mailer, id).await;
     }
-}
\ No newline at end of file
+}
@@ -2,13 +2,17 @@
 
 use mongodb::bson::{doc, oid::ObjectId, DateTime};
 use rocket::serde::json::Json;
-use rocket::{http::Status, post, State};
+use rocket::{post, State};
 use std::str::FromStr;
+use tracing::Instrument;
 
 use crate::config::OAuthConfig;
+use crate::error::{ApiResult, AppError, AuthError};
 use crate::guards::{OptionalAuthGuard, ClientIp};
-use crate::models::{ApiResponse, Comment, CommentState, CreateCommentRequest};
-use crate::services::{verify_turnstile, AccountRepository, CommentService, IpService, ReaderRepository, SpamDetector};
+use crate::models::{ApiResponse, EmptyResponse, Comment, CommentState, CommentTree, CreateCommentRequest, Moderation, RuleAction, WebhookEvent};
+use crate::services::{verify_turnstile, AccountRepository, CommentService, CommentNotifier, IpService, Mailer, OptionsService, ReaderRepository, ReviewQueue, SpamDetector, WebhookService};
+use crate::services::comment::rules::{CommentRules, RuleInput};
+use crate::services::webhook::service::comment_payload;
 
 /**
  * POST /api/comments
@@ -18,19 +22,42 @@ use crate::services::{verify_turnstile, AccountRepository, CommentService, IpSer
  * 1. 匿名评论：必须提供 author 和 mail，需要通过 Turnstile 验证
  * 2. 登录评论：通过 JWT 获取用户信息，author 和 mail 可选，无需 Turnstile
  *
+ * 评论目标须存在、已发布且允许评论，站点未关闭评论；回复的评论须属于同一目标（博主不受发布状态和评论开关限制）
+ *
  * AI 垃圾检测采用异步模式：
  * - 评论先以"待审核"状态存入数据库，立即返回成功
- * - 后台异步调用 AI 进行审核，审核完成后更新状态
+ * - 审核队列在后台调用 AI 进行审核，审核完成后更新状态
+ *
+ * AI 审核之前先运行过滤规则（见 `CommentRules`）：命中时拒绝发表，
+ * 或直接标记为垃圾 / 待博主审核，不再调用 AI
+ *
+ * 评论公开后（审核通过，或未启用 AI 审核时立即）向站长和被回复者发送邮件通知
  */
+#[utoipa::path(
+    tag = "comments",
+    request_body = CreateCommentRequest,
+    responses(
+        (status = 200, description = "评论已创建", body = ApiResponse<CommentTree>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "令牌对应的用户不存在", body = EmptyResponse),
+        (status = 403, description = "站点或该内容已关闭评论", body = EmptyResponse),
+        (status = 404, description = "评论目标或回复的评论不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+        (status = 502, description = "人机验证服务异常", body = EmptyResponse),
+    ),
+)]
 #[post("/", data = "<request>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_comment(
     db: &State<mongodb::Database>,
     oauth_config: &State<OAuthConfig>,
+    options: &State<OptionsService>,
     ip_service: &State<Option<IpService>>,
+    mailer: &State<Mailer>,
     auth: OptionalAuthGuard,
     client_ip: ClientIp,
     request: Json<CreateCommentRequest>,
-) -> Result<Json<ApiResponse<Comment>>, Status> {
+) -> ApiResult<CommentTree> {
     let comment_service = CommentService::new(db.inner());
     let reader_repo = ReaderRepository::new(db.inner());
     let collection = db.collection::<Comment>("comments");
@@ -39,10 +66,10 @@ pub async fn create_comment(
     let ip_address = client_ip.0;
     let location = ip_service.as_ref().and_then(|service| service.search_simple(&ip_address));
     
-    log::info!("收到评论请求 - IP: {}, 位置: {:?}", ip_address, location);
+    tracing::info!("收到评论请求 - IP: {}, 位置: {:?}", ip_address, location);
 
     // 确定作者信息和头像
-    let (author, mail, avatar_url, source, _reader_id) = if let Some(user_id) = auth.user_id {
+    let (author, mail, avatar_url, source, reader_id) = if let Some(user_id) = auth.user_id {
         // 已登录用户：从 Reader 获取信息，无需 Turnstile 验证码
         match reader_repo.find_by_id(user_id).await {
             Ok(Some(reader)) => {
@@ -71,7 +98,7 @@ pub async fn create_comment(
                         }
                     }
                     Err(e) => {
-                        log::warn!("查询用户 OAuth 账号失败: {}", e);
+                        tracing::warn!("查询用户 OAuth 账号失败: {}", e);
                         Some("oauth".to_string())
                     }
                 };
@@ -79,26 +106,23 @@ pub async fn create_comment(
                 (author, mail, avatar, source, Some(user_id))
             }
             Ok(None) => {
-                log::warn!("用户 {} 不存在", user_id);
-                return Ok(ApiResponse::json_error_with_default(401, "用户不存在".to_string()));
-            }
-            Err(e) => {
-                log::error!("查询用户失败: {}", e);
-                return Err(Status::InternalServerError);
+                tracing::warn!("用户 {} 不存在", user_id);
+                return Err(AuthError::InvalidToken.into());
             }
+            Err(e) => return Err(e.into()),
         }
     } else {
         // 未登录用户：必须提供 author 和 mail，并进行 Turnstile 验证
         let author = match &request.author {
             Some(a) if !a.trim().is_empty() => a.clone(),
             _ => {
-                return Ok(ApiResponse::json_error_with_default(400, "未登录用户必须提供昵称".to_string()));
+                return Err(AppError::validation("未登录用户必须提供昵称"));
             }
         };
         let mail = match &request.mail {
             Some(m) if !m.trim().is_empty() => m.clone(),
             _ => {
-                return Ok(ApiResponse::json_error_with_default(400, "未登录用户必须提供邮箱".to_string()));
+                return Err(AppError::validation("未登录用户必须提供邮箱"));
             }
         };
 
@@ -106,31 +130,24 @@ pub async fn create_comment(
         let turnstile_token = match &request.turnstile_token {
             Some(token) if !token.trim().is_empty() => token,
             _ => {
-                return Ok(ApiResponse::json_error_with_default(400, "请完成人机验证".to_string()));
+                return Err(AppError::validation("请完成人机验证"));
             }
         };
 
         match verify_turnstile(turnstile_token, &oauth_config.turnstile_secret).await {
             Ok(true) => {
-                log::info!("Turnstile 验证通过");
+                tracing::info!("Turnstile 验证通过");
             }
             Ok(false) => {
-                return Ok(ApiResponse::json_error_with_default(400, "人机验证失败，请重试".to_string()));
+                return Err(AppError::validation("人机验证失败，请重试"));
             }
             Err(e) => {
-                log::error!("Turnstile 验证错误: {}", e);
-                return Ok(ApiResponse::json_error_with_default(500, format!("验证服务异常: {}", e)));
+                return Err(AppError::upstream(format!("验证服务异常: {}", e)));
             }
         }
 
         // 查找或创建匿名 Reader
-        let reader_id = match reader_repo.find_or_create_anonymous(&author, &mail).await {
-            Ok(id) => id,
-            Err(e) => {
-                log::error!("创建匿名 Reader 失败: {}", e);
-                return Err(Status::InternalServerError);
-            }
-        };
+        let reader_id = reader_repo.find_or_create_anonymous(&author, &mail).await?;
 
         let avatar = CommentService::generate_avatar_url(&mail);
         (author, mail, avatar, None, Some(reader_id))
@@ -138,64 +155,72 @@ pub async fn create_comment(
 
     // 验证必填字段
     if request.text.trim().is_empty() {
-        return Ok(ApiResponse::json_error_with_default(400, "评论内容不能为空".to_string()));
+        return Err(AppError::validation("评论内容不能为空"));
     }
 
     // 解析 ref ObjectId
-    let ref_oid = match ObjectId::from_str(&request.r#ref) {
-        Ok(oid) => oid,
-        Err(_) => {
-            return Ok(ApiResponse::json_error_with_default(400, "Invalid ref id".to_string()));
-        }
-    };
+    let ref_oid = ObjectId::from_str(&request.r#ref)
+        .map_err(|_| AppError::validation("Invalid ref id"))?;
 
     // 解析 parent ObjectId（如果有）
-    let parent_oid = if let Some(parent_str) = &request.parent {
-        match ObjectId::from_str(parent_str) {
-            Ok(oid) => Some(oid),
-            Err(_) => None,
+    let parent_oid = request
+        .parent
+        .as_deref()
+        .map(|parent| ObjectId::from_str(parent).map_err(|_| AppError::validation("Invalid parent id")))
+        .transpose()?;
+
+    // 校验评论目标与回复关系
+    comment_service
+        .validate_target(options, ref_oid, request.ref_type, parent_oid, &auth)
+        .await?;
+
+    // 规则过滤（站长不受限制）：拒绝时直接返回错误，其余命中记录在评论上
+    let rule_hit = match options.comment_options() {
+        Ok(comment_options) if !auth.is_owner => {
+            let input = RuleInput {
+                text: &request.text,
+                author: &author,
+                mail: &mail,
+                ip: &ip_address,
+            };
+            CommentRules::check(db.inner(), &comment_options, &input)
+                .await
+                .map_err(AppError::Database)?
         }
-    } else {
-        None
+        _ => None,
     };
+    if let Some(hit) = &rule_hit {
+        tracing::warn!("评论命中过滤规则 {:?} ({:?}): {}", hit.rule, hit.action, hit.detail);
+        if hit.action == RuleAction::Reject {
+            return Err(AppError::validation(CommentRules::reject_message(hit.rule)));
+        }
+    }
 
-    // 生成 key
-    let key = match comment_service
-        .generate_comment_key(ref_oid, &request.ref_type, parent_oid)
+    // 分配 key 和评论索引（计数器原子递增，被拒绝的评论不占用序号）
+    let key = comment_service
+        .generate_comment_key(ref_oid, request.ref_type.as_str(), parent_oid)
         .await
-    {
-        Ok(key) => key,
-        Err(e) => {
-            eprintln!("Failed to generate comment key: {}", e);
-            return Err(Status::InternalServerError);
-        }
-    };
+        .map_err(AppError::Database)?;
 
-    // 获取当前评论索引
-    let comments_index = match comment_service
-        .get_comment_index(ref_oid, &request.ref_type)
+    let comments_index = comment_service
+        .get_comment_index(ref_oid, request.ref_type.as_str())
         .await
-    {
-        Ok(index) => index,
-        Err(e) => {
-            eprintln!("Failed to get comment index: {}", e);
-            return Err(Status::InternalServerError);
-        }
-    };
+        .map_err(AppError::Database)?;
 
-    // 检查是否启用 AI 审核，决定初始状态
-    let ai_review_enabled = SpamDetector::is_ai_review_enabled(db.inner()).await;
-    let initial_state = if ai_review_enabled {
-        CommentState::PENDING // 待审核
-    } else {
-        CommentState::UNREAD // 未读+正常
+    // 决定初始状态：命中规则时按规则处理（不再交给 AI），否则检查是否启用 AI 审核
+    let ai_review_enabled = rule_hit.is_none() && SpamDetector::is_ai_review_enabled(options);
+    let initial_state = match rule_hit.as_ref().map(|hit| hit.action) {
+        Some(RuleAction::Spam) => CommentState::SPAM,
+        Some(_) => CommentState::PENDING, // 等待博主审核
+        None if ai_review_enabled => CommentState::PENDING, // 待审核
+        None => CommentState::UNREAD, // 未读+正常
     };
 
     // 创建评论
     let comment = Comment {
         id: None,
         r#ref: ref_oid,
-        ref_type: request.ref_type.clone(),
+        ref_type: request.ref_type.as_str().to_string(),
         author: author.clone(),
         mail: mail.clone(),
         text: request.text.clone(),
@@ -214,6 +239,13 @@ pub async fn create_comment(
         url: request.url.clone(),
         parent: parent_oid,
         ua: request.ua.clone(),
+        reader_id,
+        edited_at: None,
+        deleted_at: None,
+        purge_after: None,
+        moderation: rule_hit.as_ref().map(Moderation::rule),
+        rule_hit,
+        trained_as: None,
     };
 
     match collection.insert_one(&comment).await {
@@ -222,8 +254,7 @@ pub async fn create_comment(
             let comment_id = match result.inserted_id.as_object_id() {
                 Some(id) => id,
                 None => {
-                    log::error!("Failed to get ObjectId from insert result");
-                    return Err(Status::InternalServerError);
+                    return Err(AppError::internal("Failed to get ObjectId from insert result"));
                 }
             };
             created_comment.id = Some(comment_id);
@@ -235,40 +266,43 @@ pub async fn create_comment(
                     .await;
             }
 
-            // 如果启用了 AI 审核，启动异步审核任务
-            if ai_review_enabled {
-                let db_clone = db.inner().clone();
-                let text_clone = request.text.clone();
-                let author_clone = author.clone();
-                let mail_clone = mail.clone();
-
-                // 使用 tokio::spawn 启动异步任务
-                tokio::spawn(async move {
-                    SpamDetector::review_async(
-                        &db_clone,
-                        comment_id,
-                        &text_clone,
-                        &author_clone,
-                        &mail_clone,
-                    )
+            // 只推送直接公开的评论；待审核的评论通过审核后推送 comment.approved
+            if initial_state == CommentState::UNREAD {
+                WebhookService::new(db.inner())
+                    .emit(WebhookEvent::CommentCreated, comment_payload(&created_comment))
                     .await;
-                });
+            }
 
-                log::info!("评论 {} 已创建，异步审核任务已启动", comment_id);
+            // 如果启用了 AI 审核，加入审核队列（入队失败时由下次启动补建任务）
+            if ai_review_enabled {
+                match ReviewQueue::new(db.inner()).enqueue(comment_id, true).await {
+                    Ok(()) => tracing::info!("评论 {} 已创建，已加入审核队列", comment_id),
+                    Err(e) => tracing::error!("评论 {} 加入审核队列失败: {}", comment_id, e),
+                }
+            } else if mailer.is_enabled() {
+                let db_clone = db.inner().clone();
+                let mailer_clone = mailer.inner().clone();
+                tokio::spawn(
+                    async move {
+                        CommentNotifier::comment_published(&db_clone, &mailer_clone, comment_id).await;
+                    }
+                    .in_current_span(),
+                );
             }
 
+            let created_comment = comment_service
+                .to_public_comment(&created_comment)
+                .await
+                .map_err(AppError::Database)?;
             Ok(Json(ApiResponse::success_with_message(
                 created_comment,
-                if ai_review_enabled {
-                    "评论已提交，正在审核中".to_string()
-                } else {
+                if initial_state == CommentState::UNREAD {
                     "评论发布成功".to_string()
+                } else {
+                    "评论已提交，正在审核中".to_string()
                 },
             )))
         }
-        Err(e) => {
-            eprintln!("Failed to create comment: {}", e);
-            Err(Status::InternalServerError)
-        }
+        Err(e) => Err(e.into()),
     }
 }
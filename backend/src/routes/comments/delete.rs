@@ -2,35 +2,119 @@
 
 use mongodb::bson::{doc, oid::ObjectId};
 use rocket::serde::json::Json;
-use rocket::{State, http::Status, delete};
+use rocket::{State, delete};
 use std::str::FromStr;
 
-use crate::models::{ApiResponse, Comment};
+use crate::error::{ApiResult, AppError, AuthError};
+use crate::guards::AuthGuard;
+use crate::models::{ApiResponse, EmptyResponse, Comment};
+use crate::services::CommentService;
 
 /**
  * DELETE /api/comments/<id>
- * 删除评论
+ * 删除评论（仅作者本人或站长）
+ *
+ * 软删除：评论保留为墓碑（清空作者与内容），其回复仍然正常显示；
+ * 没有回复的墓碑由后台任务定期清理。彻底删除见 `DELETE /api/comments/<id>/purge`
  */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    responses(
+        (status = 200, description = "评论已删除", body = EmptyResponse),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "不是评论作者", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[delete("/<id>")]
 pub async fn delete_comment(
     db: &State<mongodb::Database>,
+    auth: AuthGuard,
     id: String,
-) -> Result<Json<ApiResponse<()>>, Status> {
+) -> ApiResult<()> {
+    let comment_service = CommentService::new(db.inner());
     let collection = db.collection::<Comment>("comments");
 
-    let oid = match ObjectId::from_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => return Err(Status::BadRequest),
-    };
-
-    match collection.delete_one(doc! { "_id": oid }).await {
-        Ok(_) => Ok(Json(ApiResponse::success_with_message(
-            (),
-            "Comment deleted successfully".to_string(),
-        ))),
-        Err(e) => {
-            eprintln!("Failed to delete comment: {}", e);
-            Err(Status::InternalServerError)
+    let oid = ObjectId::from_str(&id)?;
+
+    let comment = collection
+        .find_one(doc! { "_id": oid })
+        .await?
+        .filter(|comment| comment.deleted_at.is_none())
+        .ok_or_else(|| AppError::not_found("评论不存在"))?;
+
+    if !auth.is_owner {
+        let is_author = comment_service
+            .is_author(&comment, auth.user_id)
+            .await
+            .map_err(AppError::Database)?;
+        if !is_author {
+            return Err(AuthError::InsufficientPermissions.into());
         }
     }
-}
\ No newline at end of file
+
+    if !comment_service.soft_delete(oid).await.map_err(AppError::Database)? {
+        return Err(AppError::not_found("评论不存在"));
+    }
+
+    Ok(Json(ApiResponse::success_with_message(
+        (),
+        "Comment deleted successfully".to_string(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::comments::list::list_comments;
+    use crate::routes::comments::test_support::TestApp;
+    use rocket::http::Status;
+
+    async fn delete(app: &TestApp, id: ObjectId, user: ObjectId) -> Status {
+        app.client
+            .delete(format!("/api/comments/{}", id.to_hex()))
+            .header(TestApp::bearer(user, false))
+            .dispatch()
+            .await
+            .status()
+    }
+
+    #[rocket::async_test]
+    #[ignore = "需要本地 MongoDB（MONGODB_URI）"]
+    async fn authors_soft_delete_into_tombstones() {
+        let app = TestApp::new(routes![delete_comment, list_comments]).await;
+        let (author, replier) = (ObjectId::new(), ObjectId::new());
+        let root = app.insert_comment(author, "#1", None, 1).await;
+        let reply = app.insert_comment(replier, "#1#1", Some(root), 0).await;
+
+        assert_eq!(delete(&app, root, replier).await, Status::Forbidden);
+        assert!(app.find_comment(root).await.deleted_at.is_none());
+
+        assert_eq!(delete(&app, root, author).await, Status::Ok);
+        assert_eq!(delete(&app, root, author).await, Status::NotFound);
+
+        // 墓碑仍在评论树中，只保留结构信息，回复照常挂载
+        let response = app
+            .client
+            .get(format!("/api/comments?ref_id={}&ref_type=posts", app.post.to_hex()))
+            .remote("203.0.113.7:5000".parse().unwrap())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value = response.into_json().await.unwrap();
+        let tombstone = &body["data"]["comments"][0];
+        assert_eq!(tombstone["_id"], root.to_hex());
+        assert_eq!(tombstone["isDeleted"], true);
+        assert_eq!(tombstone["text"], "");
+        assert_eq!(tombstone["author"], "");
+        assert_eq!(tombstone["children"][0]["_id"], reply.to_hex());
+        assert_eq!(tombstone["children"][0]["text"], "评论 #1#1");
+        assert_eq!(body["data"]["count"], 1);
+
+        app.cleanup().await;
+    }
+}
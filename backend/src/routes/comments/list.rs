@@ -1,94 +1,157 @@
 //! 评论列表路由
 
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{doc, oid::ObjectId, Bson};
 use rocket::serde::json::Json;
-use rocket::{State, http::Status, get};
+use rocket::{State, get};
 use std::str::FromStr;
 use futures::stream::TryStreamExt;
 
-use crate::models::{ApiResponse, CommentListResponse};
-use crate::guards::OptionalAuthGuard;
-use crate::services::CommentService;
+use crate::error::{ApiResult, AppError};
+use crate::models::{ApiResponse, EmptyResponse, Comment, CommentListResponse, Pagination, RefType};
+use crate::guards::{ClientIp, OptionalAuthGuard};
+use crate::services::{CommentService, ReactionService};
 
 /**
- * GET /api/comments?refId=xxx&refType=posts
- * 获取指定文章/页面/日记的评论列表
+ * GET /api/comments?refId=xxx&refType=posts&page=1&size=20&replies=3
+ * 分页获取指定文章/页面/日记的根评论（置顶优先，其次按时间顺序），
+ * 每个根评论按时间顺序内联至多 `replies` 条回复，其余通过
+ * `GET /api/comments/<id>/replies` 展开。
  * 根据用户身份过滤可见评论：
  * - 管理员：看到所有评论
  * - 普通用户：看到公开评论 + 自己的私密评论
  * - 匿名用户：只看到公开评论
  */
-#[get("/?<ref_id>&<ref_type>")]
+#[utoipa::path(
+    tag = "comments",
+    params(
+        ("ref_id" = String, Query, description = "文章 / 页面 / 手记 ID"),
+        ("ref_type" = String, Query, description = "posts、pages 或 notes"),
+        ("page" = Option<i64>, Query, description = "根评论页码，从 1 开始"),
+        ("size" = Option<i64>, Query, description = "每页根评论数量，默认 20，最大 100"),
+        ("replies" = Option<usize>, Query, description = "每个根评论内联的回复数量，默认 3，最大 20"),
+    ),
+    responses(
+        (status = 200, description = "评论树", body = ApiResponse<CommentListResponse>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
+#[get("/?<ref_id>&<ref_type>&<page>&<size>&<replies>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn list_comments(
     db: &State<mongodb::Database>,
     auth: OptionalAuthGuard,
+    client_ip: ClientIp,
     ref_id: String,
     ref_type: String,
-) -> Result<Json<ApiResponse<CommentListResponse>>, Status> {
+    page: Option<i64>,
+    size: Option<i64>,
+    replies: Option<usize>,
+) -> ApiResult<CommentListResponse> {
     let comment_service = CommentService::new(db.inner());
+    let page = page.unwrap_or(1).max(1);
+    let size = size.unwrap_or(20).clamp(1, 100);
+    let inline_replies = replies.unwrap_or(3).min(20);
 
-    // 解析 ObjectId
-    let ref_oid = match ObjectId::from_str(&ref_id) {
-        Ok(oid) => oid,
-        Err(_) => {
-            return Ok(ApiResponse::json_error_with_default(400, "Invalid ref_id".to_string()));
-        }
-    };
+    // 解析 ObjectId 和目标类型
+    let ref_oid = ObjectId::from_str(&ref_id)
+        .map_err(|_| AppError::validation("Invalid ref_id"))?;
+    let ref_type = RefType::parse(&ref_type)
+        .ok_or_else(|| AppError::validation("Invalid ref_type"))?;
 
     // 构建查询过滤器
-    let filter = match comment_service.build_visibility_filter(ref_oid, &ref_type, &auth).await {
-        Ok(filter) => filter,
-        Err(e) => {
-            eprintln!("Failed to build visibility filter: {}", e);
-            return Err(Status::InternalServerError);
-        }
-    };
+    let filter = comment_service
+        .build_visibility_filter(ref_oid, ref_type.as_str(), &auth)
+        .await
+        .map_err(AppError::Database)?;
 
-    // 查询评论
-    let collection = db.collection::<crate::models::Comment>("comments");
-    let mut cursor = match collection.find(filter).await {
-        Ok(cursor) => cursor,
-        Err(e) => {
-            eprintln!("Failed to query comments: {}", e);
-            return Err(Status::InternalServerError);
-        }
-    };
+    let collection = db.collection::<Comment>("comments");
+    let mut count_filter = filter.clone();
+    count_filter.insert("deletedAt", Bson::Null);
+    let count = collection.count_documents(count_filter).await? as i64;
 
-    let mut all_comments = Vec::new();
-    while let Some(comment) = cursor.try_next().await.map_err(|e| {
-        eprintln!("Error reading comment: {}", e);
-        Status::InternalServerError
-    })? {
-        all_comments.push(comment);
-    }
+    // 分页查询根评论：置顶优先，其次按时间顺序
+    let mut root_filter = filter.clone();
+    root_filter.insert("parent", Bson::Null);
+    let total = collection.count_documents(root_filter.clone()).await? as i64;
+    let roots: Vec<Comment> = collection
+        .find(root_filter)
+        .sort(doc! { "pin": -1, "created": 1, "_id": 1 })
+        .skip(((page - 1) * size) as u64)
+        .limit(size)
+        .await?
+        .try_collect()
+        .await?;
 
-    let count = all_comments.len() as i64;
+    // 每个根评论只内联最早的若干条回复，其余通过回复展开接口分页获取
+    let thread_filters = comment_service
+        .thread_filters(&filter, &roots)
+        .await
+        .map_err(AppError::Database)?;
+    let threads: Vec<(Comment, usize, Vec<Comment>, Option<String>)> =
+        futures::future::try_join_all(roots.into_iter().filter_map(|root| {
+            let thread_filter = thread_filters.get(&root.id?)?;
+            let comment_service = &comment_service;
+            Some(async move {
+                let (reply_count, inlined, cursor) = comment_service
+                    .find_replies_page(thread_filter, None, inline_replies)
+                    .await?;
+                Ok::<_, String>((root, reply_count, inlined, cursor))
+            })
+        }))
+        .await
+        .map_err(AppError::Database)?;
 
-    // 收集所有唯一的邮箱，用于批量查询 Reader
-    let emails: Vec<String> = all_comments
+    // 收集本页出现的邮箱，用于批量查询 Reader
+    let emails: Vec<String> = threads
         .iter()
+        .flat_map(|(root, _, inlined, _)| std::iter::once(root).chain(inlined))
         .map(|c| c.mail.clone())
         .collect::<std::collections::HashSet<_>>()
         .into_iter()
         .collect();
 
     // 批量查询所有 Reader，构建邮箱到头像和站长身份的映射
-    let (email_to_avatar, email_to_is_owner) = match comment_service.build_reader_mappings(emails).await {
-        Ok(mappings) => mappings,
-        Err(e) => {
-            eprintln!("Failed to build reader mappings: {}", e);
-            return Err(Status::InternalServerError);
-        }
-    };
+    let (email_to_avatar, email_to_is_owner) = comment_service
+        .build_reader_mappings(emails)
+        .await
+        .map_err(AppError::Database)?;
 
     // 构建树形结构
-    let tree = CommentService::build_comment_tree(&all_comments, &email_to_avatar, &email_to_is_owner);
+    let mut comments: Vec<_> = threads
+        .into_iter()
+        .map(|(root, reply_count, inlined, cursor)| {
+            let mut node = CommentService::to_tree_node(&root, &email_to_avatar, &email_to_is_owner);
+            node.children =
+                CommentService::build_comment_forest(&inlined, &email_to_avatar, &email_to_is_owner);
+            node.reply_count = Some(reply_count);
+            node.replies_cursor = cursor;
+            node
+        })
+        .collect();
+
+    // 填充表情回应
+    ReactionService::new(db.inner())
+        .attach(&mut comments, &ReactionService::actor(auth.user_id, &client_ip.0))
+        .await
+        .map_err(AppError::Database)?;
+
+    let total_page = (total as f64 / size as f64).ceil() as i64;
+    let pagination = Pagination {
+        total,
+        current_page: page,
+        total_page,
+        size,
+        has_next_page: page < total_page,
+        has_prev_page: page > 1,
+    };
 
     Ok(Json(ApiResponse::success_with_message(
         CommentListResponse {
-            comments: tree,
+            comments,
             count,
+            pagination,
         },
         "Comments fetched successfully".to_string(),
     )))
-}
\ No newline at end of file
+}
@@ -1,19 +1,47 @@
 //! 评论路由模块
 
 pub mod list;
+pub mod replies;
+pub mod reactions;
 pub mod create;
 pub mod update;
 pub mod delete;
 pub mod admin;
 
+#[cfg(test)]
+mod test_support;
+
 use rocket::Route;
 
+use crate::fairings::rate_limited;
+use crate::services::RateLimitGroup;
+
+/// 评论路由的 OpenAPI 文档（挂载于 /api/comments）
+#[derive(utoipa::OpenApi)]
+#[openapi(paths(
+    list::list_comments,
+    replies::list_replies,
+    reactions::add_reaction,
+    reactions::remove_reaction,
+    create::create_comment,
+    update::update_comment,
+    delete::delete_comment,
+    admin::hide_comment,
+    admin::unhide_comment,
+    admin::pin_comment,
+    admin::unpin_comment,
+    admin::purge_comment,
+    admin::list_moderation_queue,
+    admin::bulk_moderate,
+))]
+pub struct CommentsApi;
+
 /// 获取所有评论相关的路由
 pub fn routes() -> Vec<Route> {
-    routes![
+    let mut routes = routes![
         // 基础 CRUD 操作
         list::list_comments,
-        create::create_comment,
+        replies::list_replies,
         update::update_comment,
         delete::delete_comment,
         // 管理员操作
@@ -21,5 +49,16 @@ pub fn routes() -> Vec<Route> {
         admin::unhide_comment,
         admin::pin_comment,
         admin::unpin_comment,
-    ]
+        admin::purge_comment,
+        admin::list_moderation_queue,
+        admin::bulk_moderate,
+    ];
+    // 创建评论受限流约束
+    routes.extend(rate_limited(RateLimitGroup::CommentCreate, routes![create::create_comment]));
+    // 表情回应同样受限流约束
+    routes.extend(rate_limited(
+        RateLimitGroup::CommentReaction,
+        routes![reactions::add_reaction, reactions::remove_reaction],
+    ));
+    routes
 }
\ No newline at end of file
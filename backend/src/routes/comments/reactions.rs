@@ -0,0 +1,151 @@
+//! 评论表情回应路由
+
+use mongodb::bson::oid::ObjectId;
+use rocket::serde::json::Json;
+use rocket::{State, post, delete};
+use std::str::FromStr;
+
+use crate::error::{ApiResult, AppError};
+use crate::guards::{ClientIp, OptionalAuthGuard};
+use crate::models::{default_reactions, ApiResponse, EmptyResponse, ReactionCount};
+use crate::services::{CommentService, OptionsService, ReactionService};
+
+/// 校验评论与表情，返回评论 ID 和当前访客的回应者标识
+async fn resolve_target(
+    db: &mongodb::Database,
+    options: &OptionsService,
+    auth: &OptionalAuthGuard,
+    client_ip: &ClientIp,
+    id: &str,
+    emoji: &str,
+) -> Result<(ObjectId, String), AppError> {
+    let oid = ObjectId::from_str(id)?;
+
+    let allowed = options
+        .comment_options()
+        .map(|opts| opts.reactions)
+        .unwrap_or_else(|_| default_reactions());
+    ensure_allowed(&allowed, emoji)?;
+
+    // 不可见或已删除的评论不能回应
+    let (comment, _) = CommentService::new(db)
+        .find_visible(oid, auth)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::not_found("评论不存在"))?;
+    if comment.deleted_at.is_some() {
+        return Err(AppError::not_found("评论不存在"));
+    }
+
+    Ok((oid, ReactionService::actor(auth.user_id, &client_ip.0)))
+}
+
+/// 表情必须在 `commentOptions.reactions` 中（完全匹配）
+fn ensure_allowed(allowed: &[String], emoji: &str) -> Result<(), AppError> {
+    if allowed.iter().any(|r| r == emoji) {
+        Ok(())
+    } else {
+        Err(AppError::validation("不支持的表情回应"))
+    }
+}
+
+/// 回应后该评论的最新聚合结果
+async fn current_reactions(
+    reactions: &ReactionService,
+    oid: ObjectId,
+    actor: &str,
+) -> Result<Vec<ReactionCount>, AppError> {
+    Ok(reactions
+        .summarize(&[oid], actor)
+        .await
+        .map_err(AppError::Database)?
+        .remove(&oid.to_hex())
+        .unwrap_or_default())
+}
+
+/**
+ * POST /api/comments/<id>/reactions/<emoji>
+ * 添加表情回应：登录用户按账号去重，匿名访客按 IP 去重
+ * 允许的表情由 `commentOptions.reactions` 配置
+ */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID"), ("emoji" = String, Path, description = "表情（URL 编码）")),
+    responses(
+        (status = 200, description = "该评论的回应", body = ApiResponse<Vec<ReactionCount>>),
+        (status = 400, description = "请求参数错误或不支持的表情", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 429, description = "请求过于频繁", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
+#[post("/<id>/reactions/<emoji>")]
+pub async fn add_reaction(
+    db: &State<mongodb::Database>,
+    options: &State<OptionsService>,
+    auth: OptionalAuthGuard,
+    client_ip: ClientIp,
+    id: String,
+    emoji: String,
+) -> ApiResult<Vec<ReactionCount>> {
+    let (oid, actor) = resolve_target(db.inner(), options, &auth, &client_ip, &id, &emoji).await?;
+
+    let reactions = ReactionService::new(db.inner());
+    reactions.add(oid, &emoji, &actor).await.map_err(AppError::Database)?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        current_reactions(&reactions, oid, &actor).await?,
+        "Reaction added successfully".to_string(),
+    )))
+}
+
+/**
+ * DELETE /api/comments/<id>/reactions/<emoji>
+ * 取消表情回应
+ */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID"), ("emoji" = String, Path, description = "表情（URL 编码）")),
+    responses(
+        (status = 200, description = "该评论的回应", body = ApiResponse<Vec<ReactionCount>>),
+        (status = 400, description = "请求参数错误或不支持的表情", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 429, description = "请求过于频繁", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
+#[delete("/<id>/reactions/<emoji>")]
+pub async fn remove_reaction(
+    db: &State<mongodb::Database>,
+    options: &State<OptionsService>,
+    auth: OptionalAuthGuard,
+    client_ip: ClientIp,
+    id: String,
+    emoji: String,
+) -> ApiResult<Vec<ReactionCount>> {
+    let (oid, actor) = resolve_target(db.inner(), options, &auth, &client_ip, &id, &emoji).await?;
+
+    let reactions = ReactionService::new(db.inner());
+    reactions.remove(oid, &emoji, &actor).await.map_err(AppError::Database)?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        current_reactions(&reactions, oid, &actor).await?,
+        "Reaction removed successfully".to_string(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_emoji_are_rejected() {
+        let allowed = default_reactions();
+        assert!(ensure_allowed(&allowed, "👍").is_ok());
+        assert!(matches!(ensure_allowed(&allowed, "🦀"), Err(AppError::Validation(_))));
+        // 肤色变体与默认表情不同
+        assert!(ensure_allowed(&allowed, "👍🏽").is_err());
+        assert!(ensure_allowed(&allowed, "").is_err());
+        assert!(ensure_allowed(&["🦀".to_string()], "🦀").is_ok());
+    }
+}
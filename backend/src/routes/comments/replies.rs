@@ -0,0 +1,98 @@
+//! 评论回复展开路由
+
+use mongodb::bson::oid::ObjectId;
+use rocket::serde::json::Json;
+use rocket::{State, get};
+use std::str::FromStr;
+
+use crate::error::{ApiResult, AppError};
+use crate::models::{ApiResponse, EmptyResponse, CommentRepliesResponse};
+use crate::guards::{ClientIp, OptionalAuthGuard};
+use crate::services::{CommentService, ReactionService};
+
+/**
+ * GET /api/comments/<id>/replies?cursor=xxx&size=20
+ * 按时间顺序分页展开某条评论下的全部回复（含多层嵌套）
+ * 游标取自列表中的 `repliesCursor` 或上一页的 `nextCursor`，缺省时从第一条回复开始；
+ * 可见性规则与评论列表相同
+ */
+#[utoipa::path(
+    tag = "comments",
+    params(
+        ("id" = String, Path, description = "评论 ID"),
+        ("cursor" = Option<String>, Query, description = "分页游标"),
+        ("size" = Option<usize>, Query, description = "每页回复数量，默认 20，最大 100"),
+    ),
+    responses(
+        (status = 200, description = "回复列表", body = ApiResponse<CommentRepliesResponse>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
+#[get("/<id>/replies?<cursor>&<size>")]
+pub async fn list_replies(
+    db: &State<mongodb::Database>,
+    auth: OptionalAuthGuard,
+    client_ip: ClientIp,
+    id: String,
+    cursor: Option<String>,
+    size: Option<usize>,
+) -> ApiResult<CommentRepliesResponse> {
+    let comment_service = CommentService::new(db.inner());
+    let size = size.unwrap_or(20).clamp(1, 100);
+
+    let oid = ObjectId::from_str(&id)
+        .map_err(|_| AppError::validation("Invalid comment id"))?;
+    let after = match cursor.as_deref() {
+        Some(cursor) => Some(
+            CommentService::decode_reply_cursor(cursor)
+                .ok_or_else(|| AppError::validation("Invalid cursor"))?,
+        ),
+        None => None,
+    };
+
+    // 评论本身对当前用户不可见时，按不存在处理
+    let (comment, filter) = comment_service
+        .find_visible(oid, &auth)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::not_found("Comment not found"))?;
+
+    let thread_filter = comment_service
+        .thread_filters(&filter, std::slice::from_ref(&comment))
+        .await
+        .map_err(AppError::Database)?
+        .remove(&oid)
+        .ok_or_else(|| AppError::not_found("Comment not found"))?;
+    let (count, page, next_cursor) = comment_service
+        .find_replies_page(&thread_filter, after, size)
+        .await
+        .map_err(AppError::Database)?;
+
+    let emails: Vec<String> = page
+        .iter()
+        .map(|c| c.mail.clone())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    let (email_to_avatar, email_to_is_owner) = comment_service
+        .build_reader_mappings(emails)
+        .await
+        .map_err(AppError::Database)?;
+
+    let mut comments = CommentService::build_comment_forest(&page, &email_to_avatar, &email_to_is_owner);
+    ReactionService::new(db.inner())
+        .attach(&mut comments, &ReactionService::actor(auth.user_id, &client_ip.0))
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        CommentRepliesResponse {
+            comments,
+            count,
+            next_cursor,
+        },
+        "Replies fetched successfully".to_string(),
+    )))
+}
@@ -0,0 +1,86 @@
+//! 评论路由测试环境：每个测试使用独立的 MongoDB 测试数据库（见 `db_service::test_database`）
+
+use mongodb::bson::{oid::ObjectId, DateTime};
+use mongodb::Database;
+use rocket::http::Header;
+use rocket::local::asynchronous::Client;
+use rocket::Route;
+
+use crate::config::OAuthConfig;
+use crate::models::{Comment, CommentState};
+use crate::services::db_service::test_database;
+use crate::services::OptionsService;
+use crate::utils::jwt::generate_jwt;
+
+const JWT_SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+/// 挂载了指定评论路由（`/api/comments`）的本地客户端
+pub struct TestApp {
+    pub client: Client,
+    pub db: Database,
+    pub post: ObjectId,
+}
+
+impl TestApp {
+    pub async fn new(routes: Vec<Route>) -> Self {
+        let db = test_database().await;
+        let options = OptionsService::init(&db).await.unwrap();
+        let rocket = rocket::build()
+            .manage(db.clone())
+            .manage(OAuthConfig {
+                jwt_secret: JWT_SECRET.to_string(),
+                ..Default::default()
+            })
+            .manage(options)
+            .mount("/api/comments", routes);
+        Self {
+            client: Client::tracked(rocket).await.unwrap(),
+            db,
+            post: ObjectId::new(),
+        }
+    }
+
+    /// 以指定用户身份请求的 Authorization 头
+    pub fn bearer(user: ObjectId, is_owner: bool) -> Header<'static> {
+        let token = generate_jwt(user, is_owner, JWT_SECRET).unwrap();
+        Header::new("Authorization", format!("Bearer {}", token))
+    }
+
+    /// 在测试文章下插入一条公开评论，`age_minutes` 为距今的发表时间
+    pub async fn insert_comment(
+        &self,
+        reader: ObjectId,
+        key: &str,
+        parent: Option<ObjectId>,
+        age_minutes: i64,
+    ) -> ObjectId {
+        let comment = Comment {
+            r#ref: self.post,
+            ref_type: "posts".to_string(),
+            author: "访客".to_string(),
+            mail: format!("{}@example.com", reader.to_hex()),
+            text: format!("评论 {}", key),
+            state: CommentState::READ,
+            key: key.to_string(),
+            parent,
+            reader_id: Some(reader),
+            created: DateTime::from_millis(DateTime::now().timestamp_millis() - age_minutes * 60_000),
+            ..Default::default()
+        };
+        let result = self.db.collection::<Comment>("comments").insert_one(comment).await.unwrap();
+        result.inserted_id.as_object_id().unwrap()
+    }
+
+    pub async fn find_comment(&self, id: ObjectId) -> Comment {
+        self.db
+            .collection::<Comment>("comments")
+            .find_one(mongodb::bson::doc! { "_id": id })
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    pub async fn cleanup(self) {
+        self.db.drop().await.unwrap();
+    }
+}
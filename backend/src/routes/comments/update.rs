@@ -1,49 +1,196 @@
 //! 更新评论路由
 
-use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
 use rocket::serde::json::Json;
-use rocket::{State, http::Status, put};
+use rocket::{State, put};
 use std::str::FromStr;
 
-use crate::models::{ApiResponse, Comment, UpdateCommentRequest};
+use crate::error::{ApiResult, AppError, AuthError};
+use crate::guards::AuthGuard;
+use crate::models::{ApiResponse, EmptyResponse, Comment, CommentState, CommentTree, Moderation, RuleAction, UpdateCommentRequest};
+use crate::services::comment::rules::{CommentRules, RuleInput};
+use crate::services::{CommentService, OptionsService, ReviewQueue, SpamDetector};
 
 /**
  * PUT /api/comments/<id>
  * 更新评论
+ *
+ * - 站长可以随时编辑任意评论
+ * - 作者只能在编辑窗口（`commentOptions.editWindowMinutes`）内编辑自己的评论
+ *
+ * 编辑后记录 `editedAt`；启用 AI 审核时评论回到"待审核"状态并重新审核（垃圾评论保持原状态）。
+ * 作者的编辑同样经过过滤规则（重复内容检测除外）
  */
+#[utoipa::path(
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 ID")),
+    request_body = UpdateCommentRequest,
+    responses(
+        (status = 200, description = "评论已更新", body = ApiResponse<CommentTree>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "不是评论作者或已超过可编辑时间", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 409, description = "评论已删除", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 #[put("/<id>", data = "<request>")]
 pub async fn update_comment(
     db: &State<mongodb::Database>,
+    options: &State<OptionsService>,
+    auth: AuthGuard,
     id: String,
     request: Json<UpdateCommentRequest>,
-) -> Result<Json<ApiResponse<Comment>>, Status> {
+) -> ApiResult<CommentTree> {
+    let comment_service = CommentService::new(db.inner());
     let collection = db.collection::<Comment>("comments");
 
-    let oid = match ObjectId::from_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => return Err(Status::BadRequest),
-    };
+    let oid = ObjectId::from_str(&id)?;
+
+    if request.text.trim().is_empty() {
+        return Err(AppError::validation("评论内容不能为空"));
+    }
+
+    let comment = collection
+        .find_one(doc! { "_id": oid })
+        .await?
+        .ok_or_else(|| AppError::not_found("评论不存在"))?;
+    if comment.deleted_at.is_some() {
+        return Err(AppError::conflict("评论已删除"));
+    }
 
-    let update = doc! {
-        "$set": {
-            "text": &request.text,
+    // 非站长：必须是作者本人，且在编辑窗口内
+    if !auth.is_owner {
+        let is_author = comment_service
+            .is_author(&comment, auth.user_id)
+            .await
+            .map_err(AppError::Database)?;
+        if !is_author {
+            return Err(AuthError::InsufficientPermissions.into());
         }
-    };
 
-    match collection.update_one(doc! { "_id": oid }, update).await {
-        Ok(_) => {
-            // 获取更新后的评论
-            match collection.find_one(doc! { "_id": oid }).await {
-                Ok(Some(comment)) => Ok(Json(ApiResponse::success_with_message(
-                    comment,
-                    "Comment updated successfully".to_string(),
-                ))),
-                _ => Err(Status::InternalServerError),
-            }
+        let elapsed = DateTime::now().timestamp_millis() - comment.created.timestamp_millis();
+        if elapsed >= CommentService::edit_window_millis(options) {
+            return Err(AppError::forbidden("评论已超过可编辑时间"));
+        }
+    }
+
+    // 规则过滤（站长不受限制）：拒绝时不保存修改
+    let rule_hit = match options.comment_options() {
+        Ok(comment_options) if !auth.is_owner => {
+            let input = RuleInput {
+                text: &request.text,
+                author: &comment.author,
+                mail: &comment.mail,
+                ip: comment.ip.as_deref().unwrap_or_default(),
+            };
+            CommentRules::check_content(&comment_options, &input)
         }
-        Err(e) => {
-            eprintln!("Failed to update comment: {}", e);
-            Err(Status::InternalServerError)
+        _ => None,
+    };
+    if let Some(hit) = &rule_hit {
+        tracing::warn!("评论编辑命中过滤规则 {:?} ({:?}): {}", hit.rule, hit.action, hit.detail);
+        if hit.action == RuleAction::Reject {
+            return Err(AppError::validation(CommentRules::reject_message(hit.rule)));
         }
     }
-}
\ No newline at end of file
+
+    // 重新审核（站长编辑不触发）：垃圾评论保持原状态，其余评论在启用 AI 审核时回到待审核；命中规则时按规则处理
+    let review = !auth.is_owner
+        && rule_hit.is_none()
+        && comment.state != CommentState::SPAM
+        && SpamDetector::is_ai_review_enabled(options);
+
+    let mut set = doc! {
+        "text": &request.text,
+        "editedAt": DateTime::now(),
+    };
+    if review {
+        set.insert("state", CommentState::PENDING);
+    }
+    if let Some(hit) = &rule_hit {
+        let state = match hit.action {
+            RuleAction::Spam => CommentState::SPAM,
+            _ if comment.state == CommentState::SPAM => CommentState::SPAM,
+            _ => CommentState::PENDING,
+        };
+        set.insert("state", state);
+        set.insert("ruleHit", mongodb::bson::to_bson(hit).map_err(|e| AppError::internal(e.to_string()))?);
+        set.insert(
+            "moderation",
+            mongodb::bson::to_bson(&Moderation::rule(hit)).map_err(|e| AppError::internal(e.to_string()))?,
+        );
+    }
+
+    let result = collection.update_one(doc! { "_id": oid }, doc! { "$set": set }).await?;
+    if result.matched_count == 0 {
+        return Err(AppError::not_found("评论不存在"));
+    }
+
+    if review {
+        match ReviewQueue::new(db.inner()).enqueue(oid, false).await {
+            Ok(()) => tracing::info!("评论 {} 已编辑，已加入审核队列", oid),
+            Err(e) => tracing::error!("评论 {} 加入审核队列失败: {}", oid, e),
+        }
+    }
+
+    // 获取更新后的评论
+    let comment = collection
+        .find_one(doc! { "_id": oid })
+        .await?
+        .ok_or_else(|| AppError::not_found("评论不存在"))?;
+    let comment = comment_service
+        .to_public_comment(&comment)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        comment,
+        "Comment updated successfully".to_string(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::comments::test_support::TestApp;
+    use rocket::http::{ContentType, Status};
+
+    async fn edit(app: &TestApp, id: ObjectId, user: ObjectId, is_owner: bool) -> Status {
+        app.client
+            .put(format!("/api/comments/{}", id.to_hex()))
+            .header(ContentType::JSON)
+            .header(TestApp::bearer(user, is_owner))
+            .body(r#"{"text":"修改后的内容"}"#)
+            .dispatch()
+            .await
+            .status()
+    }
+
+    #[rocket::async_test]
+    #[ignore = "需要本地 MongoDB（MONGODB_URI）"]
+    async fn only_authors_within_window_or_owner_can_edit() {
+        let app = TestApp::new(routes![update_comment]).await;
+        let (author, stranger, owner) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        let fresh = app.insert_comment(author, "#1", None, 1).await;
+        let stale = app.insert_comment(author, "#2", None, 60 * 24).await;
+
+        assert_eq!(edit(&app, fresh, stranger, false).await, Status::Forbidden);
+        assert_eq!(app.find_comment(fresh).await.text, "评论 #1");
+
+        assert_eq!(edit(&app, fresh, author, false).await, Status::Ok);
+        let edited = app.find_comment(fresh).await;
+        assert_eq!(edited.text, "修改后的内容");
+        assert!(edited.edited_at.is_some());
+
+        // 超过编辑窗口（默认 15 分钟）后作者不能再编辑，站长不受限制
+        assert_eq!(edit(&app, stale, author, false).await, Status::Forbidden);
+        assert_eq!(app.find_comment(stale).await.text, "评论 #2");
+        assert_eq!(edit(&app, stale, owner, true).await, Status::Ok);
+        assert_eq!(app.find_comment(stale).await.text, "修改后的内容");
+
+        app.cleanup().await;
+    }
+}
@@ -1,16 +1,18 @@
 //! Site configuration routes
 
-use mongodb::Database;
 use rocket::serde::json::Json;
 use rocket::{get, State};
 use crate::models::{ApiResponse, SiteConfig};
-use crate::services;
+use crate::services::OptionsService;
 
 /// Get site configuration (safe for frontend)
+#[utoipa::path(
+    tag = "config",
+    responses(
+        (status = 200, description = "站点公开配置", body = ApiResponse<SiteConfig>),
+    ),
+)]
 #[get("/config")]
-pub async fn get_site_config(database: &State<Database>) -> Json<ApiResponse<SiteConfig>> {
-    match services::get_site_config(database).await {
-        Ok(config) => Json(ApiResponse::success(config)),
-        Err(e) => ApiResponse::json_error_with_default(500, format!("Failed to fetch site config: {}", e)),
-    }
+pub fn get_site_config(options: &State<OptionsService>) -> Json<ApiResponse<SiteConfig>> {
+    Json(ApiResponse::success(options.site_config()))
 }
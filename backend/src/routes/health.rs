@@ -0,0 +1,121 @@
+//! Health check routes - liveness and readiness probes
+//!
+//! 挂载于 `/`（`/health`、`/ready`），`/api` 下保留同名别名
+
+use mongodb::bson::doc;
+use mongodb::Database;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::{get, routes, Route, State};
+use std::time::{Duration, Instant};
+
+use crate::models::health::*;
+use crate::models::ApiResponse;
+use crate::services::{ChangeStreamHealth, IpService, OptionsService};
+
+/// MongoDB ping 超时时间
+const DB_PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 探针路由
+pub fn routes() -> Vec<Route> {
+    routes![health, ready]
+}
+
+/// Liveness probe - 进程存活即返回 200，不依赖任何外部服务
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "服务存活", body = ApiResponse<HealthStatus>),
+    ),
+)]
+#[get("/health")]
+pub fn health() -> Json<ApiResponse<HealthStatus>> {
+    Json(ApiResponse::success(HealthStatus { status: "ok" }))
+}
+
+/// Readiness probe - 检查各子系统状态
+///
+/// 只有 MongoDB 不可用时返回 503；IP 服务、Change Stream、AI 属于可降级功能，仅报告状态。
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "服务就绪", body = ApiResponse<ReadinessReport>),
+        (status = 503, description = "MongoDB 不可用", body = ApiResponse<ReadinessReport>),
+    ),
+)]
+#[get("/ready")]
+pub async fn ready(
+    database: &State<Database>,
+    ip_service: &State<Option<IpService>>,
+    change_stream: &State<ChangeStreamHealth>,
+    options: &State<OptionsService>,
+) -> Custom<Json<ApiResponse<ReadinessReport>>> {
+    let database_health = check_database(database).await;
+
+    let stream_status = change_stream.status();
+    let ai = match options.ai_config() {
+        Ok(config) => AiHealth {
+            config_valid: true,
+            enabled: config.enabled,
+            error: None,
+        },
+        Err(e) => AiHealth {
+            config_valid: false,
+            enabled: false,
+            error: Some(e),
+        },
+    };
+
+    let report = ReadinessReport {
+        ready: database_health.connected,
+        database: database_health,
+        ip_service: IpServiceHealth {
+            loaded: ip_service.is_some(),
+        },
+        change_stream: ChangeStreamHealthReport {
+            enabled: stream_status.enabled,
+            connected: stream_status.connected,
+            last_event_at: stream_status.last_event_at,
+        },
+        options: OptionsHealth {
+            loaded_at: options.loaded_at(),
+        },
+        ai,
+    };
+
+    if report.ready {
+        Custom(Status::Ok, Json(ApiResponse::success(report)))
+    } else {
+        let response = ApiResponse::error_with_data(503, "Service not ready".to_string(), report);
+        Custom(Status::ServiceUnavailable, Json(response))
+    }
+}
+
+/// Ping MongoDB 并记录延迟
+async fn check_database(database: &Database) -> DatabaseHealth {
+    let start = Instant::now();
+    let result = tokio::time::timeout(DB_PING_TIMEOUT, database.run_command(doc! { "ping": 1 })).await;
+
+    match result {
+        Ok(Ok(_)) => DatabaseHealth {
+            connected: true,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+            error: None,
+        },
+        // 驱动错误可能包含主机、副本集和认证信息，只写入日志
+        Ok(Err(e)) => {
+            tracing::error!("MongoDB ping 失败: {}", e);
+            DatabaseHealth {
+                connected: false,
+                latency_ms: None,
+                error: Some("unreachable".to_string()),
+            }
+        }
+        Err(_) => DatabaseHealth {
+            connected: false,
+            latency_ms: None,
+            error: Some(format!("ping timed out after {}s", DB_PING_TIMEOUT.as_secs())),
+        },
+    }
+}
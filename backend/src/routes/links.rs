@@ -1,17 +1,26 @@
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::doc;
 use futures::stream::TryStreamExt;
 
-use crate::models::{Link, ApiResponse, PaginatedResponse, PaginatedData, Pagination};
+use crate::error::ApiResult;
+use crate::models::{Link, ApiResponse, EmptyResponse, PaginatedData, Pagination};
 
 /// List approved friend links with pagination
+#[utoipa::path(
+    tag = "links",
+    params(("page" = Option<i64>, Query, description = "页码，从 1 开始"), ("size" = Option<i64>, Query, description = "每页数量")),
+    responses(
+        (status = 200, description = "友链列表", body = ApiResponse<PaginatedData<Link>>),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/links?<page>&<size>")]
 pub async fn list_links(
     db: &State<Database>,
     page: Option<i64>,
     size: Option<i64>,
-) -> Result<Json<PaginatedResponse<Link>>, Status> {
+) -> ApiResult<PaginatedData<Link>> {
     let page = page.unwrap_or(1).max(1);
     let size = size.unwrap_or(10).clamp(1, 100);
     let skip = (page - 1) * size;
@@ -25,14 +34,12 @@ pub async fn list_links(
         .limit(size)
         .build();
 
-    let total = collection.count_documents(filter.clone()).await
-        .map_err(|_| Status::InternalServerError)?;
+    let total = collection.count_documents(filter.clone()).await?;
 
-    let mut cursor = collection.find(filter).with_options(find_options).await
-        .map_err(|_| Status::InternalServerError)?;
+    let mut cursor = collection.find(filter).with_options(find_options).await?;
 
     let mut items = Vec::new();
-    while let Some(link) = cursor.try_next().await.map_err(|_| Status::InternalServerError)? {
+    while let Some(link) = cursor.try_next().await? {
         items.push(link);
     }
 
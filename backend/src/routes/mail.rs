@@ -0,0 +1,145 @@
+//! 邮件订阅路由 - 退订链接与订阅偏好
+
+use rocket::serde::json::Json;
+use rocket::{get, post, put, State};
+
+use crate::error::{ApiResult, AppError};
+use crate::guards::AuthGuard;
+use crate::models::{ApiResponse, EmptyResponse, MailKind, MailPreferences};
+use crate::services::{Mailer, MailPreferenceRepository, ReaderRepository};
+
+/// 校验退订签名并退订
+async fn unsubscribe(
+    db: &mongodb::Database,
+    mailer: &Mailer,
+    email: &str,
+    kind: &str,
+    token: &str,
+) -> ApiResult<()> {
+    let kind = MailKind::parse(kind).ok_or_else(|| AppError::validation("未知的邮件类型"))?;
+    if !mailer.verify_unsubscribe(email, kind, token) {
+        return Err(AppError::validation("退订链接无效"));
+    }
+
+    MailPreferenceRepository::new(db).unsubscribe(email, kind).await?;
+    tracing::info!("{} 已退订 {} 邮件", email, kind.as_str());
+
+    Ok(Json(ApiResponse::success_with_message((), "已退订".to_string())))
+}
+
+/**
+ * GET /api/mail/unsubscribe?<email>&<kind>&<token>
+ * 邮件中的退订链接（token 为邮箱与类型的 HMAC 签名）
+ */
+#[utoipa::path(
+    tag = "mail",
+    params(
+        ("email" = String, Query, description = "退订邮箱"),
+        ("kind" = String, Query, description = "邮件类型：replies / new_comments"),
+        ("token" = String, Query, description = "退订签名"),
+    ),
+    responses(
+        (status = 200, description = "已退订", body = EmptyResponse),
+        (status = 400, description = "链接无效", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
+#[get("/mail/unsubscribe?<email>&<kind>&<token>")]
+pub async fn unsubscribe_link(
+    db: &State<mongodb::Database>,
+    mailer: &State<Mailer>,
+    email: String,
+    kind: String,
+    token: String,
+) -> ApiResult<()> {
+    unsubscribe(db.inner(), mailer.inner(), &email, &kind, &token).await
+}
+
+/**
+ * POST /api/mail/unsubscribe?<email>&<kind>&<token>
+ * 一键退订（RFC 8058，邮件客户端根据 `List-Unsubscribe-Post` 头发起）
+ */
+#[utoipa::path(
+    tag = "mail",
+    params(
+        ("email" = String, Query, description = "退订邮箱"),
+        ("kind" = String, Query, description = "邮件类型：replies / new_comments"),
+        ("token" = String, Query, description = "退订签名"),
+    ),
+    responses(
+        (status = 200, description = "已退订", body = EmptyResponse),
+        (status = 400, description = "链接无效", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
+#[post("/mail/unsubscribe?<email>&<kind>&<token>")]
+pub async fn unsubscribe_one_click(
+    db: &State<mongodb::Database>,
+    mailer: &State<Mailer>,
+    email: String,
+    kind: String,
+    token: String,
+) -> ApiResult<()> {
+    unsubscribe(db.inner(), mailer.inner(), &email, &kind, &token).await
+}
+
+/// 当前登录读者的邮箱
+async fn reader_email(db: &mongodb::Database, auth: &AuthGuard) -> Result<String, AppError> {
+    ReaderRepository::new(db)
+        .find_by_id(auth.user_id)
+        .await?
+        .map(|reader| reader.email)
+        .filter(|email| !email.is_empty())
+        .ok_or_else(|| AppError::not_found("账号未绑定邮箱"))
+}
+
+/**
+ * GET /api/mail/preferences
+ * 获取当前读者的邮件订阅偏好
+ */
+#[utoipa::path(
+    tag = "mail",
+    responses(
+        (status = 200, description = "订阅偏好", body = ApiResponse<MailPreferences>),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 404, description = "账号未绑定邮箱", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[get("/mail/preferences")]
+pub async fn get_preferences(
+    db: &State<mongodb::Database>,
+    auth: AuthGuard,
+) -> ApiResult<MailPreferences> {
+    let email = reader_email(db.inner(), &auth).await?;
+    let preferences = MailPreferenceRepository::new(db.inner()).get(&email).await?;
+    Ok(Json(ApiResponse::success(preferences)))
+}
+
+/**
+ * PUT /api/mail/preferences
+ * 更新当前读者的邮件订阅偏好
+ */
+#[utoipa::path(
+    tag = "mail",
+    request_body = MailPreferences,
+    responses(
+        (status = 200, description = "订阅偏好已更新", body = ApiResponse<MailPreferences>),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 404, description = "账号未绑定邮箱", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[put("/mail/preferences", data = "<request>")]
+pub async fn update_preferences(
+    db: &State<mongodb::Database>,
+    auth: AuthGuard,
+    request: Json<MailPreferences>,
+) -> ApiResult<MailPreferences> {
+    let email = reader_email(db.inner(), &auth).await?;
+    let preferences = request.into_inner();
+    MailPreferenceRepository::new(db.inner()).set(&email, preferences).await?;
+    Ok(Json(ApiResponse::success(preferences)))
+}
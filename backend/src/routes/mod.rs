@@ -3,10 +3,14 @@ pub mod auth;
 pub mod categories;
 pub mod comments;
 pub mod config;
+pub mod health;
 pub mod links;
+pub mod mail;
 pub mod nbnhhsh;
 pub mod notes;
+pub mod openapi;
 pub mod pages;
 pub mod posts;
 pub mod recentlies;
 pub mod users;
+pub mod webhooks;
@@ -1,15 +1,19 @@
 use rocket::serde::json::Json;
 use rocket::serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+use crate::models::EmptyResponse;
 
 /// Request body for nbnhhsh guess
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct GuessRequest {
     pub text: String,
 }
 
 /// Response item from nbnhhsh API
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct GuessResult {
     pub name: String,
@@ -20,24 +24,30 @@ pub struct GuessResult {
 }
 
 /// Proxy endpoint for nbnhhsh guess API
+#[utoipa::path(
+    tag = "nbnhhsh",
+    request_body = GuessRequest,
+    responses(
+        (status = 200, description = "缩写释义", body = Vec<GuessResult>),
+        (status = 502, description = "上游接口调用失败", body = EmptyResponse),
+    ),
+)]
 #[post("/nbnhhsh/guess", data = "<request>")]
-pub async fn guess(request: Json<GuessRequest>) -> Json<Vec<GuessResult>> {
+pub async fn guess(request: Json<GuessRequest>) -> Result<Json<Vec<GuessResult>>, AppError> {
     let client = reqwest::Client::new();
     
-    let result = client
+    let response = client
         .post("https://lab.magiconch.com/api/nbnhhsh/guess")
         .json(&serde_json::json!({ "text": request.text }))
         .send()
-        .await;
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| AppError::upstream(format!("nbnhhsh 请求失败: {}", e)))?;
+
+    let data = response
+        .json::<Vec<GuessResult>>()
+        .await
+        .map_err(|e| AppError::upstream(format!("nbnhhsh 响应解析失败: {}", e)))?;
 
-    match result {
-        Ok(response) => {
-            if let Ok(data) = response.json::<Vec<GuessResult>>().await {
-                Json(data)
-            } else {
-                Json(vec![])
-            }
-        }
-        Err(_) => Json(vec![]),
-    }
+    Ok(Json(data))
 }
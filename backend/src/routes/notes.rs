@@ -1,11 +1,13 @@
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::{doc, oid::ObjectId};
 use futures::stream::TryStreamExt;
 use std::str::FromStr;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::models::{Note, ApiResponse, PaginatedResponse, PaginatedData, Pagination, AiSummary};
+use crate::error::{ApiResult, AppError};
+use crate::models::{Note, ApiResponse, EmptyResponse, PaginatedData, Pagination, AiSummary};
 
 /// Helper function to get the latest AI summary for a given ref ID
 async fn get_ai_summary(db: &Database, ref_id: &str, lang: &str) -> Option<String> {
@@ -36,12 +38,20 @@ async fn get_ai_summary(db: &Database, ref_id: &str, lang: &str) -> Option<Strin
 }
 
 /// List published notes with pagination
+#[utoipa::path(
+    tag = "notes",
+    params(("page" = Option<i64>, Query, description = "页码，从 1 开始"), ("size" = Option<i64>, Query, description = "每页数量")),
+    responses(
+        (status = 200, description = "已发布手记列表", body = ApiResponse<PaginatedData<Note>>),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/notes?<page>&<size>")]
 pub async fn list_notes(
     db: &State<Database>,
     page: Option<i64>,
     size: Option<i64>,
-) -> Result<Json<PaginatedResponse<Note>>, Status> {
+) -> ApiResult<PaginatedData<Note>> {
     let page = page.unwrap_or(1).max(1);
     let size = size.unwrap_or(10).clamp(1, 100);
     let skip = (page - 1) * size;
@@ -57,24 +67,13 @@ pub async fn list_notes(
         .build();
 
     // Get total count
-    let total = collection.count_documents(filter.clone()).await
-        .map_err(|e| {
-            eprintln!("Error counting documents: {:?}", e);
-            Status::InternalServerError
-        })?;
+    let total = collection.count_documents(filter.clone()).await?;
 
     // Fetch notes
-    let mut cursor = collection.find(filter).with_options(find_options).await
-        .map_err(|e| {
-            eprintln!("Error finding documents: {:?}", e);
-            Status::InternalServerError
-        })?;
+    let mut cursor = collection.find(filter).with_options(find_options).await?;
 
     let mut items = Vec::new();
-    while let Some(mut note) = cursor.try_next().await.map_err(|e| {
-        eprintln!("Error deserializing note: {:?}", e);
-        Status::InternalServerError
-    })? {
+    while let Some(mut note) = cursor.try_next().await? {
         // Fetch AI summary (default to Chinese)
         note.ai_summary = get_ai_summary(db, &note.id.to_hex(), "zh").await;
         
@@ -95,17 +94,26 @@ pub async fn list_notes(
 }
 
 /// Get note by ID
+#[utoipa::path(
+    tag = "notes",
+    params(("id" = String, Path, description = "手记 ID")),
+    responses(
+        (status = 200, description = "手记详情", body = ApiResponse<Note>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/notes/<id>")]
 pub async fn get_note_by_id(
     db: &State<Database>,
     id: String,
-) -> Result<Json<ApiResponse<Note>>, Status> {
-    let object_id = ObjectId::from_str(&id).map_err(|_| Status::BadRequest)?;
+) -> ApiResult<Note> {
+    let object_id = ObjectId::from_str(&id)?;
     
     let collection = db.collection::<Note>("notes");
-    let mut note = collection.find_one(doc! { "_id": object_id, "isPublished": true }).await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+    let mut note = collection.find_one(doc! { "_id": object_id, "isPublished": true }).await?
+        .ok_or_else(|| AppError::not_found("手记不存在"))?;
 
     // Fetch AI summary (default to Chinese)
     note.ai_summary = get_ai_summary(db, &note.id.to_hex(), "zh").await;
@@ -114,15 +122,23 @@ pub async fn get_note_by_id(
 }
 
 /// Get note by numeric ID (nid)
+#[utoipa::path(
+    tag = "notes",
+    params(("nid" = i32, Path, description = "手记编号")),
+    responses(
+        (status = 200, description = "手记详情", body = ApiResponse<Note>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/notes/nid/<nid>")]
 pub async fn get_note_by_nid(
     db: &State<Database>,
     nid: i32,
-) -> Result<Json<ApiResponse<Note>>, Status> {
+) -> ApiResult<Note> {
     let collection = db.collection::<Note>("notes");
-    let mut note = collection.find_one(doc! { "nid": nid, "isPublished": true }).await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+    let mut note = collection.find_one(doc! { "nid": nid, "isPublished": true }).await?
+        .ok_or_else(|| AppError::not_found("手记不存在"))?;
 
     // Fetch AI summary (default to Chinese)
     note.ai_summary = get_ai_summary(db, &note.id.to_hex(), "zh").await;
@@ -131,13 +147,13 @@ pub async fn get_note_by_nid(
 }
 
 /// Get adjacent notes (previous and next) by nid
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AdjacentNotes {
     pub prev: Option<AdjacentNote>,
     pub next: Option<AdjacentNote>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AdjacentNote {
     pub nid: i32,
     pub title: String,
@@ -152,11 +168,20 @@ struct MinimalNote {
     pub title: String,
 }
 
+#[utoipa::path(
+    tag = "notes",
+    params(("nid" = i32, Path, description = "手记编号")),
+    responses(
+        (status = 200, description = "上一篇 / 下一篇", body = ApiResponse<AdjacentNotes>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/notes/nid/<nid>/adjacent")]
 pub async fn get_adjacent_notes(
     db: &State<Database>,
     nid: i32,
-) -> Result<Json<ApiResponse<AdjacentNotes>>, Status> {
+) -> ApiResult<AdjacentNotes> {
     let collection = db.collection::<MinimalNote>("notes");
     
     // Find previous note (smaller nid, get the largest one)
@@ -170,11 +195,7 @@ pub async fn get_adjacent_notes(
     
     let prev_note = collection.find_one(prev_filter)
         .with_options(prev_options)
-        .await
-        .map_err(|e| {
-            eprintln!("Error finding previous note: {:?}", e);
-            Status::InternalServerError
-        })?;
+        .await?;
     
     // Find next note (larger nid, get the smallest one)
     let next_filter = doc! { 
@@ -187,11 +208,7 @@ pub async fn get_adjacent_notes(
     
     let next_note = collection.find_one(next_filter)
         .with_options(next_options)
-        .await
-        .map_err(|e| {
-            eprintln!("Error finding next note: {:?}", e);
-            Status::InternalServerError
-        })?;
+        .await?;
     
     let adjacent = AdjacentNotes {
         prev: prev_note.map(|note| AdjacentNote {
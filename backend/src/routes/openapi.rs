@@ -0,0 +1,163 @@
+//! OpenAPI routes - generated specification and interactive docs
+
+use rocket::get;
+use rocket::http::ContentType;
+use rocket::Route;
+use std::sync::LazyLock;
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::openapi::OpenApi as OpenApiSpec;
+use utoipa::{Modify, OpenApi};
+use utoipa_scalar::{Scalar, Servable};
+
+use super::{ai, auth, categories, comments, config, health, links, mail, nbnhhsh, notes, pages, posts, recentlies, users, webhooks};
+
+/// 挂载于 / 的探针路由（/api 下的别名不单独列出）
+#[derive(OpenApi)]
+#[openapi(paths(health::health, health::ready))]
+struct ProbeApi;
+
+/// 挂载于 /api 的路由
+#[derive(OpenApi)]
+#[openapi(paths(
+    posts::list_posts,
+    posts::get_post_by_id,
+    posts::get_post_by_slug,
+    posts::get_adjacent_posts,
+    notes::list_notes,
+    notes::get_note_by_id,
+    notes::get_note_by_nid,
+    notes::get_adjacent_notes,
+    categories::list_categories,
+    links::list_links,
+    recentlies::list_recentlies,
+    users::get_user_profile,
+    users::list_readers,
+    users::get_reader_by_id,
+    nbnhhsh::guess,
+    pages::get_page_by_slug,
+    config::get_site_config,
+    ai::analyze_time_capsule,
+    ai::get_time_capsule,
+    mail::unsubscribe_link,
+    mail::unsubscribe_one_click,
+    mail::get_preferences,
+    mail::update_preferences,
+    webhooks::list_webhooks,
+    webhooks::create_webhook,
+    webhooks::update_webhook,
+    webhooks::delete_webhook,
+    webhooks::list_deliveries,
+))]
+struct CoreApi;
+
+/// 完整的 API 文档
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Neo Space API",
+        description = "Neo Space 博客后端 API。所有 JSON 响应都包裹在 `ApiResponse` 中，失败响应附带 `requestId`。"
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "health", description = "存活与就绪检查"),
+        (name = "posts", description = "博文"),
+        (name = "notes", description = "手记"),
+        (name = "categories", description = "分类"),
+        (name = "links", description = "友链"),
+        (name = "recentlies", description = "动态"),
+        (name = "users", description = "博主与读者"),
+        (name = "pages", description = "独立页面"),
+        (name = "config", description = "站点配置"),
+        (name = "ai", description = "AI 功能"),
+        (name = "nbnhhsh", description = "缩写释义代理"),
+        (name = "auth", description = "OAuth 登录与账号"),
+        (name = "comments", description = "评论"),
+        (name = "mail", description = "邮件通知订阅"),
+        (name = "webhooks", description = "Webhook 事件推送"),
+    )
+)]
+pub struct ApiDoc;
+
+/// JWT Bearer 认证（Authorization: Bearer <token>）
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(Http::builder().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+/// 按挂载点拼接路径；挂载点上的根路由（`/`）不保留结尾斜杠
+fn mount_path(base: &str, path: &str) -> String {
+    if path == "/" {
+        base.to_string()
+    } else {
+        format!("{}{}", base, path)
+    }
+}
+
+impl ApiDoc {
+    /// 生成完整文档（路由路径与 main.rs 中的挂载点保持一致）
+    pub fn build() -> OpenApiSpec {
+        ApiDoc::openapi()
+            .nest_with_path_composer("", ProbeApi::openapi(), mount_path)
+            .nest_with_path_composer("/api", CoreApi::openapi(), mount_path)
+            .nest_with_path_composer("/api/auth", auth::AuthApi::openapi(), mount_path)
+            .nest_with_path_composer("/api/comments", comments::CommentsApi::openapi(), mount_path)
+    }
+}
+
+static SPEC: LazyLock<OpenApiSpec> = LazyLock::new(ApiDoc::build);
+
+static SPEC_JSON: LazyLock<String> = LazyLock::new(|| {
+    SPEC.to_json().expect("Failed to serialize OpenAPI specification")
+});
+
+/// OpenAPI 3 文档
+#[get("/openapi.json")]
+pub fn openapi_json() -> (ContentType, &'static str) {
+    (ContentType::JSON, SPEC_JSON.as_str())
+}
+
+/// 文档路由：/openapi.json 与交互式文档页面 /docs（挂载于 /api）
+pub fn routes() -> Vec<Route> {
+    let mut routes = routes![openapi_json];
+    routes.extend(Vec::<Route>::from(Scalar::with_url("/docs", SPEC.clone())));
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_covers_mounted_routes() {
+        let spec = ApiDoc::build();
+        let paths = &spec.paths.paths;
+
+        for path in [
+            "/health",
+            "/ready",
+            "/api/posts",
+            "/api/posts/{id}",
+            "/api/notes/nid/{nid}/adjacent",
+            "/api/auth/oauth/{provider}/callback",
+            "/api/auth/me",
+            "/api/comments",
+            "/api/comments/{id}/pin",
+            "/api/webhooks/{id}/deliveries",
+        ] {
+            assert!(paths.contains_key(path), "missing path: {}", path);
+        }
+
+        let components = spec.components.as_ref().unwrap();
+        for schema in ["CommentTree", "PostWithCategory", "EmptyResponse"] {
+            assert!(components.schemas.contains_key(schema), "missing schema: {}", schema);
+        }
+        assert!(components.security_schemes.contains_key("bearer_auth"));
+    }
+}
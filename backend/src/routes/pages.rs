@@ -1,20 +1,29 @@
 
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::doc;
 
-use crate::models::{Page, ApiResponse};
+use crate::error::{ApiResult, AppError};
+use crate::models::{Page, ApiResponse, EmptyResponse};
 
 /// Get page by slug
+#[utoipa::path(
+    tag = "pages",
+    params(("slug" = String, Path, description = "页面 slug")),
+    responses(
+        (status = 200, description = "页面详情", body = ApiResponse<Page>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/pages/<slug>")]
 pub async fn get_page_by_slug(
     db: &State<Database>,
     slug: &str,
-) -> Result<Json<ApiResponse<Page>>, Status> {
+) -> ApiResult<Page> {
     let collection = db.collection::<Page>("pages");
-    let page = collection.find_one(doc! { "slug": slug }).await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+    let page = collection.find_one(doc! { "slug": slug }).await?
+        .ok_or_else(|| AppError::not_found("页面不存在"))?;
 
     Ok(Json(ApiResponse::success(page)))
 }
@@ -1,18 +1,27 @@
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::{doc, oid::ObjectId};
 use futures::stream::TryStreamExt;
 use std::str::FromStr;
 
-use crate::models::{Post, PostWithCategory, Category, ApiResponse, PaginatedResponse, PaginatedData, Pagination, AiSummary};
+use crate::error::{ApiResult, AppError};
+use crate::models::{Post, PostWithCategory, Category, ApiResponse, EmptyResponse, PaginatedData, Pagination, AiSummary};
 
 /// List published posts with pagination
+#[utoipa::path(
+    tag = "posts",
+    params(("page" = Option<i64>, Query, description = "页码，从 1 开始"), ("size" = Option<i64>, Query, description = "每页数量")),
+    responses(
+        (status = 200, description = "已发布博文列表", body = ApiResponse<PaginatedData<PostWithCategory>>),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/posts?<page>&<size>")]
 pub async fn list_posts(
     db: &State<Database>,
     page: Option<i64>,
     size: Option<i64>,
-) -> Result<Json<PaginatedResponse<PostWithCategory>>, Status> {
+) -> ApiResult<PaginatedData<PostWithCategory>> {
     let page = page.unwrap_or(1).max(1);
     let size = size.unwrap_or(10).clamp(1, 100);
     let skip = (page - 1) * size;
@@ -29,32 +38,17 @@ pub async fn list_posts(
         .build();
 
     // Get total count
-    let total = posts_collection.count_documents(filter.clone()).await
-        .map_err(|e| {
-            eprintln!("Error counting posts: {:?}", e);
-            Status::InternalServerError
-        })?;
+    let total = posts_collection.count_documents(filter.clone()).await?;
 
     // Fetch posts
-    let mut cursor = posts_collection.find(filter).with_options(find_options).await
-        .map_err(|e| {
-            eprintln!("Error finding posts: {:?}", e);
-            Status::InternalServerError
-        })?;
+    let mut cursor = posts_collection.find(filter).with_options(find_options).await?;
 
     let mut items = Vec::new();
-    while let Some(post) = cursor.try_next().await.map_err(|e| {
-        eprintln!("Error iterating posts cursor: {:?}", e);
-        Status::InternalServerError
-    })? {
+    while let Some(post) = cursor.try_next().await? {
         // Fetch category information
         let category = categories_collection
             .find_one(doc! { "_id": post.category_id })
-            .await
-            .map_err(|e| {
-                eprintln!("Error finding category: {:?}", e);
-                Status::InternalServerError
-            })?;
+            .await?;
 
         let post_id = post.id.to_hex();
         let mut post_with_category = PostWithCategory::from(post);
@@ -98,25 +92,33 @@ async fn get_ai_summary(db: &Database, ref_id: &str, lang: &str) -> Option<Strin
 }
 
 /// Get post by ID
+#[utoipa::path(
+    tag = "posts",
+    params(("id" = String, Path, description = "博文 ID")),
+    responses(
+        (status = 200, description = "博文详情", body = ApiResponse<PostWithCategory>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/posts/<id>")]
 pub async fn get_post_by_id(
     db: &State<Database>,
     id: String,
-) -> Result<Json<ApiResponse<PostWithCategory>>, Status> {
-    let object_id = ObjectId::from_str(&id).map_err(|_| Status::BadRequest)?;
+) -> ApiResult<PostWithCategory> {
+    let object_id = ObjectId::from_str(&id)?;
     
     let posts_collection = db.collection::<Post>("posts");
     let categories_collection = db.collection::<Category>("categories");
     
-    let post = posts_collection.find_one(doc! { "_id": object_id, "isPublished": true }).await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+    let post = posts_collection.find_one(doc! { "_id": object_id, "isPublished": true }).await?
+        .ok_or_else(|| AppError::not_found("博文不存在"))?;
 
     // Fetch category information
     let category = categories_collection
         .find_one(doc! { "_id": post.category_id })
-        .await
-        .map_err(|_| Status::InternalServerError)?;
+        .await?;
 
     let mut post_with_category = PostWithCategory::from(post);
     post_with_category.category = category;
@@ -128,17 +130,25 @@ pub async fn get_post_by_id(
 }
 
 /// Get post by slug
+#[utoipa::path(
+    tag = "posts",
+    params(("slug" = String, Path, description = "博文 slug")),
+    responses(
+        (status = 200, description = "博文详情", body = ApiResponse<PostWithCategory>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/posts/slug/<slug>")]
 pub async fn get_post_by_slug(
     db: &State<Database>,
     slug: &str,
-) -> Result<Json<ApiResponse<PostWithCategory>>, Status> {
+) -> ApiResult<PostWithCategory> {
     let posts_collection = db.collection::<Post>("posts");
     let categories_collection = db.collection::<Category>("categories");
     
-    let post = posts_collection.find_one(doc! { "slug": slug, "isPublished": true }).await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+    let post = posts_collection.find_one(doc! { "slug": slug, "isPublished": true }).await?
+        .ok_or_else(|| AppError::not_found("博文不存在"))?;
 
     // Get post ID as string for AI summary lookup
     let post_id = post.id.to_hex();
@@ -146,8 +156,7 @@ pub async fn get_post_by_slug(
     // Fetch category information
     let category = categories_collection
         .find_one(doc! { "_id": post.category_id })
-        .await
-        .map_err(|_| Status::InternalServerError)?;
+        .await?;
 
     let mut post_with_category = PostWithCategory::from(post);
     post_with_category.category = category;
@@ -160,14 +169,15 @@ pub async fn get_post_by_slug(
 
 /// Get adjacent posts (previous and next) by slug
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AdjacentPosts {
     pub prev: Option<AdjacentPost>,
     pub next: Option<AdjacentPost>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AdjacentPost {
     pub slug: String,
     pub title: String,
@@ -187,23 +197,28 @@ struct MinimalPost {
     pub created: bson::DateTime,
 }
 
+#[utoipa::path(
+    tag = "posts",
+    params(("slug" = String, Path, description = "博文 slug")),
+    responses(
+        (status = 200, description = "上一篇 / 下一篇", body = ApiResponse<AdjacentPosts>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/posts/slug/<slug>/adjacent")]
 pub async fn get_adjacent_posts(
     db: &State<Database>,
     slug: &str,
-) -> Result<Json<ApiResponse<AdjacentPosts>>, Status> {
+) -> ApiResult<AdjacentPosts> {
     let posts_collection = db.collection::<MinimalPost>("posts");
     let categories_collection = db.collection::<Category>("categories");
     
     // First, get the current post to find its creation date
     let current_post = posts_collection
         .find_one(doc! { "slug": slug, "isPublished": true })
-        .await
-        .map_err(|e| {
-            eprintln!("Error finding current post: {:?}", e);
-            Status::InternalServerError
-        })?
-        .ok_or(Status::NotFound)?;
+        .await?
+        .ok_or_else(|| AppError::not_found("博文不存在"))?;
     
     // Find previous post (older, smaller created date)
     let prev_filter = doc! { 
@@ -216,11 +231,7 @@ pub async fn get_adjacent_posts(
     
     let prev_post = posts_collection.find_one(prev_filter)
         .with_options(prev_options)
-        .await
-        .map_err(|e| {
-            eprintln!("Error finding previous post: {:?}", e);
-            Status::InternalServerError
-        })?;
+        .await?;
     
     // Find next post (newer, larger created date)
     let next_filter = doc! { 
@@ -233,11 +244,7 @@ pub async fn get_adjacent_posts(
     
     let next_post = posts_collection.find_one(next_filter)
         .with_options(next_options)
-        .await
-        .map_err(|e| {
-            eprintln!("Error finding next post: {:?}", e);
-            Status::InternalServerError
-        })?;
+        .await?;
     
     // Build adjacent posts with category slugs
     let prev = if let Some(post) = prev_post {
@@ -1,17 +1,26 @@
-use rocket::{State, serde::json::Json, http::Status};
+use rocket::{State, serde::json::Json};
 use mongodb::Database;
 use mongodb::bson::doc;
 use futures::stream::TryStreamExt;
 
-use crate::models::{Recently, ApiResponse, PaginatedResponse, PaginatedData, Pagination};
+use crate::error::ApiResult;
+use crate::models::{Recently, ApiResponse, EmptyResponse, PaginatedData, Pagination};
 
 /// List recentlies with pagination
+#[utoipa::path(
+    tag = "recentlies",
+    params(("page" = Option<i64>, Query, description = "页码，从 1 开始"), ("size" = Option<i64>, Query, description = "每页数量")),
+    responses(
+        (status = 200, description = "动态列表", body = ApiResponse<PaginatedData<Recently>>),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/recentlies?<page>&<size>")]
 pub async fn list_recentlies(
     db: &State<Database>,
     page: Option<i64>,
     size: Option<i64>,
-) -> Result<Json<PaginatedResponse<Recently>>, Status> {
+) -> ApiResult<PaginatedData<Recently>> {
     let page = page.unwrap_or(1).max(1);
     let size = size.unwrap_or(10).clamp(1, 100);
     let skip = (page - 1) * size;
@@ -25,24 +34,13 @@ pub async fn list_recentlies(
         .build();
 
     // Get total count
-    let total = collection.count_documents(doc! {}).await
-        .map_err(|e| {
-            eprintln!("Error counting recentlies: {:?}", e);
-            Status::InternalServerError
-        })?;
+    let total = collection.count_documents(doc! {}).await?;
 
     // Fetch items
-    let mut cursor = collection.find(doc! {}).with_options(find_options).await
-        .map_err(|e| {
-            eprintln!("Error finding recentlies: {:?}", e);
-            Status::InternalServerError
-        })?;
+    let mut cursor = collection.find(doc! {}).with_options(find_options).await?;
 
     let mut items = Vec::new();
-    while let Some(result) = cursor.try_next().await.map_err(|e| {
-        eprintln!("Error iterating recentlies cursor: {:?}", e);
-        Status::InternalServerError
-    })? {
+    while let Some(result) = cursor.try_next().await? {
         items.push(result);
     }
 
@@ -2,12 +2,21 @@ use rocket::serde::json::Json;
 use rocket::{get, State};
 use mongodb::{Database, Collection};
 use bson::oid::ObjectId;
-use crate::models::{ApiResponse, User, Reader};
+use crate::error::{ApiResult, AppError};
+use crate::models::{ApiResponse, EmptyResponse, User, Reader};
 use crate::services::ReaderRepository;
 
 /// 获取用户资料（非敏感数据）
+#[utoipa::path(
+    tag = "users",
+    responses(
+        (status = 200, description = "博主资料", body = ApiResponse<User>),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/user/profile")]
-pub async fn get_user_profile(database: &State<Database>) -> Json<ApiResponse<User>> {
+pub async fn get_user_profile(database: &State<Database>) -> ApiResult<User> {
     let collection: Collection<mongodb::bson::Document> = database.collection("users");
 
     // 只投影非敏感字段
@@ -28,44 +37,53 @@ pub async fn get_user_profile(database: &State<Database>) -> Json<ApiResponse<Us
         .projection(projection)
         .build();
 
-    match collection.find_one(mongodb::bson::doc! {}).with_options(options).await {
-        Ok(Some(doc)) => {
-            match mongodb::bson::from_document::<User>(doc) {
-                Ok(user) => Json(ApiResponse::success(user)),
-                Err(e) => ApiResponse::json_error_with_default(500, format!("解析用户数据失败: {}", e)),
-            }
-        }
-        Ok(None) => ApiResponse::json_error_with_default(404, "未找到用户".to_string()),
-        Err(e) => ApiResponse::json_error_with_default(500, format!("获取用户资料失败: {}", e)),
-    }
+    let doc = collection
+        .find_one(mongodb::bson::doc! {})
+        .with_options(options)
+        .await?
+        .ok_or_else(|| AppError::not_found("未找到用户"))?;
+    let user = mongodb::bson::from_document::<User>(doc)
+        .map_err(|e| AppError::internal(format!("解析用户数据失败: {}", e)))?;
+
+    Ok(Json(ApiResponse::success(user)))
 }
 
 /// 获取所有 readers（非敏感数据）
+#[utoipa::path(
+    tag = "users",
+    responses(
+        (status = 200, description = "读者列表", body = ApiResponse<Vec<Reader>>),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/readers")]
-pub async fn list_readers(database: &State<Database>) -> Json<ApiResponse<Vec<Reader>>> {
+pub async fn list_readers(database: &State<Database>) -> ApiResult<Vec<Reader>> {
     let repo = ReaderRepository::new(database);
-    
-    match repo.get_all().await {
-        Ok(readers) => Json(ApiResponse::success(readers)),
-        Err(e) => ApiResponse::json_error_with_default(500, format!("获取 readers 失败: {}", e)),
-    }
+    let readers = repo.get_all().await?;
+
+    Ok(Json(ApiResponse::success(readers)))
 }
 
 /// 通过 ID 获取 reader（非敏感数据）
+#[utoipa::path(
+    tag = "users",
+    params(("id" = String, Path, description = "读者 ID")),
+    responses(
+        (status = 200, description = "读者详情", body = ApiResponse<Reader>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+)]
 #[get("/readers/<id>")]
-pub async fn get_reader_by_id(id: String, database: &State<Database>) -> Json<ApiResponse<Reader>> {
+pub async fn get_reader_by_id(id: String, database: &State<Database>) -> ApiResult<Reader> {
     let repo = ReaderRepository::new(database);
-    
-    let object_id = match ObjectId::parse_str(&id) {
-        Ok(oid) => oid,
-        Err(_) => {
-            return ApiResponse::json_error_with_default(400, "无效的 ID 格式".to_string());
-        }
-    };
+    let object_id = ObjectId::parse_str(&id)?;
+
+    let reader = repo
+        .find_by_id(object_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("未找到 Reader"))?;
 
-    match repo.find_by_id(object_id).await {
-        Ok(Some(reader)) => Json(ApiResponse::success(reader)),
-        Ok(None) => ApiResponse::json_error_with_default(404, "未找到 Reader".to_string()),
-        Err(e) => ApiResponse::json_error_with_default(500, format!("获取 reader 失败: {}", e)),
-    }
+    Ok(Json(ApiResponse::success(reader)))
 }
\ No newline at end of file
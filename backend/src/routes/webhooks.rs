@@ -0,0 +1,222 @@
+//! Webhook 管理路由（仅博主）
+
+use mongodb::bson::oid::ObjectId;
+use rocket::serde::json::Json;
+use rocket::{delete, get, patch, post, State};
+use std::str::FromStr;
+
+use crate::error::{ApiResult, AppError};
+use crate::guards::OwnerGuard;
+use crate::models::{
+    ApiResponse, CreateWebhookRequest, EmptyResponse, PaginatedData, Pagination,
+    UpdateWebhookRequest, WebhookDelivery, WebhookEvent, WebhookResponse,
+};
+use crate::services::WebhookService;
+
+/// 校验推送地址和订阅事件
+fn validate(url: Option<&str>, events: Option<&[WebhookEvent]>) -> Result<(), AppError> {
+    if let Some(url) = url {
+        let valid = reqwest::Url::parse(url)
+            .map(|url| matches!(url.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if !valid {
+            return Err(AppError::validation("url 必须是 http(s) 地址"));
+        }
+    }
+    if events.is_some_and(|events| events.is_empty()) {
+        return Err(AppError::validation("至少订阅一个事件"));
+    }
+    Ok(())
+}
+
+/**
+ * GET /api/webhooks
+ * 列出所有 Webhook（不含密钥）
+ */
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Webhook 列表", body = ApiResponse<Vec<WebhookResponse>>),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[get("/webhooks")]
+pub async fn list_webhooks(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+) -> ApiResult<Vec<WebhookResponse>> {
+    let webhooks = WebhookService::new(db.inner())
+        .list()
+        .await
+        .map_err(AppError::Database)?;
+    Ok(Json(ApiResponse::success(
+        webhooks.into_iter().map(WebhookResponse::from).collect(),
+    )))
+}
+
+/**
+ * POST /api/webhooks
+ * 创建 Webhook，响应中返回一次签名密钥
+ */
+#[utoipa::path(
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook 已创建", body = ApiResponse<WebhookResponse>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[post("/webhooks", data = "<request>")]
+pub async fn create_webhook(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+    request: Json<CreateWebhookRequest>,
+) -> ApiResult<WebhookResponse> {
+    validate(Some(&request.url), Some(&request.events))?;
+
+    let webhook = WebhookService::new(db.inner())
+        .create(request.into_inner())
+        .await
+        .map_err(AppError::Database)?;
+    tracing::info!("已创建 Webhook {} -> {}", webhook.id, webhook.url);
+
+    let secret = webhook.secret.clone();
+    let mut response = WebhookResponse::from(webhook);
+    response.secret = Some(secret);
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/**
+ * PATCH /api/webhooks/<id>
+ * 修改 Webhook 的地址、订阅事件或启用状态
+ */
+#[utoipa::path(
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook ID")),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook 已更新", body = ApiResponse<WebhookResponse>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[patch("/webhooks/<id>", data = "<request>")]
+pub async fn update_webhook(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+    id: String,
+    request: Json<UpdateWebhookRequest>,
+) -> ApiResult<WebhookResponse> {
+    let oid = ObjectId::from_str(&id)?;
+    validate(request.url.as_deref(), request.events.as_deref())?;
+
+    let webhook = WebhookService::new(db.inner())
+        .update(oid, request.into_inner())
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::not_found("Webhook 不存在"))?;
+    Ok(Json(ApiResponse::success(WebhookResponse::from(webhook))))
+}
+
+/**
+ * DELETE /api/webhooks/<id>
+ * 删除 Webhook 及其投递记录
+ */
+#[utoipa::path(
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Webhook 已删除", body = EmptyResponse),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[delete("/webhooks/<id>")]
+pub async fn delete_webhook(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+    id: String,
+) -> ApiResult<()> {
+    let oid = ObjectId::from_str(&id)?;
+
+    let deleted = WebhookService::new(db.inner())
+        .delete(oid)
+        .await
+        .map_err(AppError::Database)?;
+    if !deleted {
+        return Err(AppError::not_found("Webhook 不存在"));
+    }
+
+    Ok(Json(ApiResponse::success_with_message((), "Webhook 已删除".to_string())))
+}
+
+/**
+ * GET /api/webhooks/<id>/deliveries?<page>&<size>
+ * Webhook 的投递记录（最新的在前）
+ */
+#[utoipa::path(
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook ID"),
+        ("page" = Option<i64>, Query, description = "页码，从 1 开始"),
+        ("size" = Option<i64>, Query, description = "每页数量，默认 20，最大 100"),
+    ),
+    responses(
+        (status = 200, description = "投递记录", body = ApiResponse<PaginatedData<WebhookDelivery>>),
+        (status = 400, description = "请求参数错误", body = EmptyResponse),
+        (status = 401, description = "未登录或令牌无效", body = EmptyResponse),
+        (status = 403, description = "需要博主权限", body = EmptyResponse),
+        (status = 404, description = "资源不存在", body = EmptyResponse),
+        (status = 500, description = "服务器内部错误", body = EmptyResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+#[get("/webhooks/<id>/deliveries?<page>&<size>")]
+pub async fn list_deliveries(
+    db: &State<mongodb::Database>,
+    _owner: OwnerGuard,
+    id: String,
+    page: Option<i64>,
+    size: Option<i64>,
+) -> ApiResult<PaginatedData<WebhookDelivery>> {
+    let oid = ObjectId::from_str(&id)?;
+    let page = page.unwrap_or(1).max(1);
+    let size = size.unwrap_or(20).clamp(1, 100);
+
+    let service = WebhookService::new(db.inner());
+    if service.find(oid).await.map_err(AppError::Database)?.is_none() {
+        return Err(AppError::not_found("Webhook 不存在"));
+    }
+
+    let (items, total) = service
+        .deliveries(oid, page as u64, size)
+        .await
+        .map_err(AppError::Database)?;
+
+    let total_page = (total as f64 / size as f64).ceil() as i64;
+    let pagination = Pagination {
+        total: total as i64,
+        current_page: page,
+        total_page,
+        size,
+        has_next_page: page < total_page,
+        has_prev_page: page > 1,
+    };
+
+    Ok(Json(ApiResponse::success(PaginatedData { items, pagination })))
+}
@@ -1,4 +1,4 @@
-//! AI Service - 从数据库获取 AI 配置并提供文本生成能力
+//! AI Service - 从站点配置（options 集合）获取 AI 配置并提供文本生成能力
 //!
 //! 使用 OpenAI 兼容 SDK，支持任意 OpenAI 兼容的 API 端点
 
@@ -13,11 +13,11 @@ use async_openai::{
     },
     Client,
 };
-use mongodb::bson::doc;
-use mongodb::Database;
+use mongodb::bson::Bson;
 use serde::Deserialize;
 
-use crate::models::RawOption;
+use super::metrics::metrics;
+use super::options_service::OptionsService;
 
 /// AI 配置（从数据库读取）
 #[derive(Debug, Clone)]
@@ -28,6 +28,25 @@ pub struct AiConfig {
     pub api_key: String,
 }
 
+impl AiConfig {
+    /// 从 `ai` 配置项解析
+    pub fn from_option(value: Option<&Bson>) -> Result<Self, String> {
+        let doc = value
+            .ok_or_else(|| "AI configuration not found".to_string())?
+            .as_document()
+            .ok_or_else(|| "AI options is not a document".to_string())?;
+        let ai_options: AiOptionsValue =
+            bson::from_document(doc.clone()).map_err(|e| format!("Failed to parse AI options: {}", e))?;
+
+        Ok(Self {
+            enabled: ai_options.enable_summary,
+            endpoint: ai_options.open_ai_endpoint,
+            model: ai_options.open_ai_preferred_model,
+            api_key: ai_options.open_ai_key,
+        })
+    }
+}
+
 /// 数据库中的 AI 配置原始结构
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -58,31 +77,6 @@ pub struct ChatMessage {
     pub content: String,
 }
 
-/// 从数据库获取 AI 配置
-pub async fn get_ai_config(database: &Database) -> Result<AiConfig, String> {
-    let collection = database.collection::<RawOption>("options");
-
-    let option = collection
-        .find_one(doc! { "name": "ai" })
-        .await
-        .map_err(|e| format!("Database error: {}", e))?
-        .ok_or_else(|| "AI configuration not found".to_string())?;
-
-    let doc = option
-        .value
-        .as_document()
-        .ok_or_else(|| "AI options is not a document".to_string())?;
-    let ai_options: AiOptionsValue =
-        bson::from_document(doc.clone()).map_err(|e| format!("Failed to parse AI options: {}", e))?;
-
-    Ok(AiConfig {
-        enabled: ai_options.enable_summary,
-        endpoint: ai_options.open_ai_endpoint,
-        model: ai_options.open_ai_preferred_model,
-        api_key: ai_options.open_ai_key,
-    })
-}
-
 /// AI 服务
 pub struct AiService {
     config: AiConfig,
@@ -102,10 +96,9 @@ impl AiService {
         }
     }
 
-    /// 从数据库配置创建服务实例
-    pub async fn from_database(database: &Database) -> Result<Self, String> {
-        let config = get_ai_config(database).await?;
-        Ok(Self::new(config))
+    /// 从站点配置快照创建服务实例
+    pub fn from_options(options: &OptionsService) -> Result<Self, String> {
+        Ok(Self::new(options.ai_config()?))
     }
 
     /// 检查服务是否启用
@@ -138,7 +131,7 @@ impl AiService {
         // 转换消息格式
         let openai_messages: Vec<ChatCompletionRequestMessage> = messages
             .into_iter()
-            .map(|msg| Self::convert_message(msg))
+            .map(Self::convert_message)
             .collect::<Result<Vec<_>, _>>()?;
 
         let mut request_builder = CreateChatCompletionRequestArgs::default();
@@ -157,18 +150,26 @@ impl AiService {
             .build()
             .map_err(|e| format!("Failed to build request: {}", e))?;
 
-        let response = self
+        let timer = metrics().ai_request_duration_seconds.start_timer();
+        let result = self
             .client
             .chat()
             .create(request)
             .await
-            .map_err(|e| format!("API request failed: {}", e))?;
-
-        response
-            .choices
-            .first()
-            .and_then(|c| c.message.content.clone())
-            .ok_or_else(|| "No response generated".to_string())
+            .map_err(|e| format!("API request failed: {}", e))
+            .and_then(|response| {
+                response
+                    .choices
+                    .first()
+                    .and_then(|c| c.message.content.clone())
+                    .ok_or_else(|| "No response generated".to_string())
+            });
+        timer.observe_duration();
+
+        if result.is_err() {
+            metrics().ai_request_failures_total.inc();
+        }
+        result
     }
 
     /// 转换消息格式
@@ -1,10 +1,17 @@
 #![allow(unused)]
-//! Cache service - Moka-based in-memory cache with TTL
+//! Cache service - pluggable cache with TTL (in-process Moka or shared Redis)
 
 use moka::future::Cache;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::config::{CacheBackendKind, CacheConfig};
+use super::metrics::metrics;
+use super::rate_limiter::{BucketSpec, RateLimitDecision, TokenBucket};
+use super::redis_cache::RedisCacheBackend;
+
 /// 缓存键类型
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CacheKey {
@@ -23,28 +30,130 @@ pub enum CacheKey {
 }
 
 impl CacheKey {
-    /// 转换为字符串键
-    pub fn to_string(&self) -> String {
+    /// 键前缀（字符串键中第一个 `:` 之前的部分）
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            CacheKey::Post(_) => "post",
+            CacheKey::PostList { .. } => "posts",
+            CacheKey::Note(_) => "note",
+            CacheKey::NoteList { .. } => "notes",
+            CacheKey::Page(_) => "page",
+            CacheKey::Categories => "categories",
+        }
+    }
+
+    /// 缓存值所依赖的数据标签
+    ///
+    /// 除自身前缀外，博文详情和博文列表都内嵌了分类信息，
+    /// 因此分类变更时需要一并失效。
+    pub fn tags(&self) -> &'static [&'static str] {
         match self {
-            CacheKey::Post(id) => format!("post:{}", id),
-            CacheKey::PostList { page, size } => format!("posts:page:{}:size:{}", page, size),
-            CacheKey::Note(id) => format!("note:{}", id),
-            CacheKey::NoteList { page, size } => format!("notes:page:{}:size:{}", page, size),
-            CacheKey::Page(slug) => format!("page:{}", slug),
-            CacheKey::Categories => "categories".to_string(),
+            CacheKey::Post(_) => &["post", "categories"],
+            CacheKey::PostList { .. } => &["posts", "categories"],
+            CacheKey::Note(_) => &["note"],
+            CacheKey::NoteList { .. } => &["notes"],
+            CacheKey::Page(_) => &["page"],
+            CacheKey::Categories => &["categories"],
         }
     }
+
+    /// 是否带有指定标签
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().contains(&tag)
+    }
 }
 
-/// 缓存服务
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheKey::Post(id) => write!(f, "post:{}", id),
+            CacheKey::PostList { page, size } => write!(f, "posts:page:{}:size:{}", page, size),
+            CacheKey::Note(id) => write!(f, "note:{}", id),
+            CacheKey::NoteList { page, size } => write!(f, "notes:page:{}:size:{}", page, size),
+            CacheKey::Page(slug) => write!(f, "page:{}", slug),
+            CacheKey::Categories => write!(f, "categories"),
+        }
+    }
+}
+
+impl FromStr for CacheKey {
+    type Err = String;
+
+    /// 从字符串键还原 CacheKey（用于跨实例广播失效消息）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的缓存键: {}", s);
+        let parse_list = |rest: &str| -> Result<(i64, i64), String> {
+            let parts: Vec<&str> = rest.split(':').collect();
+            match parts.as_slice() {
+                ["page", page, "size", size] => Ok((
+                    page.parse().map_err(|_| invalid())?,
+                    size.parse().map_err(|_| invalid())?,
+                )),
+                _ => Err(invalid()),
+            }
+        };
+
+        if s == "categories" {
+            return Ok(CacheKey::Categories);
+        }
+
+        let (prefix, rest) = s.split_once(':').ok_or_else(invalid)?;
+        match prefix {
+            "post" => Ok(CacheKey::Post(rest.to_string())),
+            "note" => Ok(CacheKey::Note(rest.to_string())),
+            "page" => Ok(CacheKey::Page(rest.to_string())),
+            "posts" => parse_list(rest).map(|(page, size)| CacheKey::PostList { page, size }),
+            "notes" => parse_list(rest).map(|(page, size)| CacheKey::NoteList { page, size }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// 缓存后端
+///
+/// 进程内的 Moka 实现见 [`MokaCacheBackend`]，多实例部署使用的 Redis 实现见
+/// [`crate::services::redis_cache::RedisCacheBackend`]。
+#[rocket::async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// 后端名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 获取缓存值
+    async fn get(&self, key: &CacheKey) -> Option<Vec<u8>>;
+
+    /// 设置缓存值
+    async fn set(&self, key: &CacheKey, value: Vec<u8>);
+
+    /// 删除单个缓存键
+    async fn invalidate(&self, key: &CacheKey);
+
+    /// 批量删除缓存（按键前缀精确匹配）
+    async fn invalidate_by_prefix(&self, prefix: &str);
+
+    /// 批量删除缓存（按标签匹配）
+    async fn invalidate_by_tag(&self, tag: &str);
+
+    /// 清除所有缓存
+    async fn clear(&self);
+
+    /// 获取缓存统计信息
+    async fn stats(&self) -> CacheStats;
+
+    /// 从令牌桶 `key` 中取出一个令牌（限流用）
+    async fn take_token(&self, key: &str, spec: &BucketSpec) -> RateLimitDecision;
+}
+
+/// 进程内 Moka 缓存后端
 #[derive(Clone)]
-pub struct CacheService {
-    cache: Arc<Cache<String, Vec<u8>>>,
+pub struct MokaCacheBackend {
+    cache: Arc<Cache<CacheKey, Vec<u8>>>,
+    /// 限流令牌桶（长时间未访问的桶必然已补满，直接淘汰）
+    buckets: Arc<Cache<String, Arc<std::sync::Mutex<TokenBucket>>>>,
 }
 
-impl CacheService {
-    /// 创建新的缓存服务实例
-    /// 
+impl MokaCacheBackend {
+    /// 创建 Moka 缓存后端
+    ///
     /// # 参数
     /// - `max_capacity`: 最大缓存条目数
     /// - `ttl_seconds`: 缓存过期时间（秒）
@@ -52,78 +161,196 @@ impl CacheService {
         let cache = Cache::builder()
             .max_capacity(max_capacity)
             .time_to_live(Duration::from_secs(ttl_seconds))
+            .support_invalidation_closures()
+            .build();
+
+        let buckets = Cache::builder()
+            .max_capacity(100_000)
+            .time_to_idle(Duration::from_secs(24 * 3600))
             .build();
 
-        log::info!(
-            "缓存服务初始化完成 - 容量: {}, TTL: {}秒",
+        Self {
+            cache: Arc::new(cache),
+            buckets: Arc::new(buckets),
+        }
+    }
+
+    /// 按条件删除缓存
+    ///
+    /// Moka 的条件失效是惰性的：被命中的条目不会再被 `get` 返回，
+    /// 实际的删除在后台维护任务中完成。
+    fn invalidate_if<F>(&self, predicate: F)
+    where
+        F: Fn(&CacheKey) -> bool + Send + Sync + 'static,
+    {
+        if let Err(e) = self.cache.invalidate_entries_if(move |key, _| predicate(key)) {
+            // 仅在构建时未开启 support_invalidation_closures 时出现，兜底清空全部缓存
+            tracing::error!("条件清除缓存失败，执行全局清除: {:?}", e);
+            self.cache.invalidate_all();
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl CacheBackend for MokaCacheBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        self.cache.get(key).await
+    }
+
+    async fn set(&self, key: &CacheKey, value: Vec<u8>) {
+        self.cache.insert(key.clone(), value).await;
+    }
+
+    async fn invalidate(&self, key: &CacheKey) {
+        self.cache.invalidate(key).await;
+    }
+
+    async fn invalidate_by_prefix(&self, prefix: &str) {
+        let prefix = prefix.to_string();
+        self.invalidate_if(move |key| key.prefix() == prefix);
+    }
+
+    async fn invalidate_by_tag(&self, tag: &str) {
+        let tag = tag.to_string();
+        self.invalidate_if(move |key| key.has_tag(&tag));
+    }
+
+    async fn clear(&self) {
+        self.cache.invalidate_all();
+    }
+
+    async fn stats(&self) -> CacheStats {
+        self.cache.run_pending_tasks().await;
+
+        CacheStats {
+            entry_count: self.cache.entry_count(),
+            weighted_size: self.cache.weighted_size(),
+        }
+    }
+
+    async fn take_token(&self, key: &str, spec: &BucketSpec) -> RateLimitDecision {
+        let now = std::time::Instant::now();
+        let bucket = self
+            .buckets
+            .get_with(key.to_string(), async {
+                Arc::new(std::sync::Mutex::new(TokenBucket::full(spec, now)))
+            })
+            .await;
+
+        let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.take(spec, now)
+    }
+}
+
+/// 缓存服务
+#[derive(Clone)]
+pub struct CacheService {
+    backend: Arc<dyn CacheBackend>,
+}
+
+impl CacheService {
+    /// 创建基于进程内 Moka 缓存的服务实例
+    ///
+    /// # 参数
+    /// - `max_capacity`: 最大缓存条目数
+    /// - `ttl_seconds`: 缓存过期时间（秒）
+    pub fn new(max_capacity: u64, ttl_seconds: u64) -> Self {
+        tracing::info!(
+            "缓存服务初始化完成 - 后端: memory, 容量: {}, TTL: {}秒",
             max_capacity,
             ttl_seconds
         );
 
-        Self {
-            cache: Arc::new(cache),
+        Self::with_backend(Arc::new(MokaCacheBackend::new(max_capacity, ttl_seconds)))
+    }
+
+    /// 使用指定后端创建服务实例
+    pub fn with_backend(backend: Arc<dyn CacheBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// 根据配置创建服务实例
+    pub async fn from_config(config: &CacheConfig) -> Result<Self, String> {
+        match config.backend {
+            CacheBackendKind::Memory => Ok(Self::new(config.max_capacity, config.ttl_seconds)),
+            CacheBackendKind::Redis => {
+                let backend = RedisCacheBackend::connect(config).await?;
+                tracing::info!(
+                    "缓存服务初始化完成 - 后端: redis, 本地容量: {}, TTL: {}秒",
+                    config.max_capacity,
+                    config.ttl_seconds
+                );
+                Ok(Self::with_backend(Arc::new(backend)))
+            }
         }
     }
 
+    /// 当前使用的后端名称
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
     /// 获取缓存值
     pub async fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
-        let key_str = key.to_string();
-        let value = self.cache.get(&key_str).await;
-        
+        let value = self.backend.get(key).await;
+        metrics().observe_cache(value.is_some());
+
         if value.is_some() {
-            log::info!("[Cache] ✓ 命中缓存: {}", key_str);
+            tracing::info!("[Cache] ✓ 命中缓存: {}", key);
         } else {
-            log::debug!("[Cache] 未命中: {}", key_str);
+            tracing::debug!("[Cache] 未命中: {}", key);
         }
-        
+
         value
     }
 
     /// 设置缓存值
     pub async fn set(&self, key: &CacheKey, value: Vec<u8>) {
-        let key_str = key.to_string();
         let size = value.len();
-        self.cache.insert(key_str.clone(), value).await;
-        log::info!("[Cache] 写入缓存: {} ({} bytes)", key_str, size);
+        self.backend.set(key, value).await;
+        tracing::info!("[Cache] 写入缓存: {} ({} bytes)", key, size);
     }
 
     /// 删除单个缓存键
     pub async fn invalidate(&self, key: &CacheKey) {
-        let key_str = key.to_string();
-        log::info!("清除缓存: {}", key_str);
-        self.cache.invalidate(&key_str).await;
+        tracing::info!("清除缓存: {}", key);
+        self.backend.invalidate(key).await;
     }
 
     /// 批量删除缓存（通过前缀匹配）
+    ///
+    /// 前缀按键的第一段精确匹配，例如 `posts` 只会命中博文列表，不会命中 `post:{id}`。
     pub async fn invalidate_by_prefix(&self, prefix: &str) {
-        log::info!("批量清除缓存 (前缀: {})", prefix);
-        
-        // 遍历所有键并删除匹配的
-        self.cache.run_pending_tasks().await;
-        
-        // 注意: Moka 不支持直接的前缀删除，需要手动遍历
-        // 这里我们使用 invalidate_all 作为简化实现
-        // 在生产环境中，建议维护一个键的索引来实现精确的前缀删除
-        if prefix == "posts" || prefix == "notes" || prefix == "pages" {
-            self.cache.invalidate_all();
-            log::warn!("执行全局缓存清除 (前缀: {})", prefix);
-        }
+        tracing::info!("批量清除缓存 (前缀: {})", prefix);
+        self.backend.invalidate_by_prefix(prefix).await;
+    }
+
+    /// 批量删除缓存（通过标签匹配）
+    ///
+    /// 例如 `categories` 会同时命中分类列表、博文详情和博文列表。
+    pub async fn invalidate_by_tag(&self, tag: &str) {
+        tracing::info!("批量清除缓存 (标签: {})", tag);
+        self.backend.invalidate_by_tag(tag).await;
     }
 
     /// 清除所有缓存
     pub async fn clear(&self) {
-        log::warn!("清除所有缓存");
-        self.cache.invalidate_all();
+        tracing::warn!("清除所有缓存");
+        self.backend.clear().await;
     }
 
     /// 获取缓存统计信息
     pub async fn stats(&self) -> CacheStats {
-        self.cache.run_pending_tasks().await;
-        
-        CacheStats {
-            entry_count: self.cache.entry_count(),
-            weighted_size: self.cache.weighted_size(),
-        }
+        self.backend.stats().await
+    }
+
+    /// 从令牌桶中取出一个令牌（Redis 后端时各实例共享同一个桶）
+    pub async fn take_token(&self, key: &str, spec: &BucketSpec) -> RateLimitDecision {
+        self.backend.take_token(key, spec).await
     }
 }
 
@@ -133,3 +360,165 @@ pub struct CacheStats {
     pub entry_count: u64,
     pub weighted_size: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每种 CacheKey 变体各取一个样例
+    fn all_keys() -> Vec<CacheKey> {
+        vec![
+            CacheKey::Post("p1".to_string()),
+            CacheKey::PostList { page: 1, size: 10 },
+            CacheKey::Note("n1".to_string()),
+            CacheKey::NoteList { page: 2, size: 20 },
+            CacheKey::Page("about".to_string()),
+            CacheKey::Categories,
+        ]
+    }
+
+    async fn populated_cache() -> CacheService {
+        let service = CacheService::new(100, 60);
+        for key in all_keys() {
+            service.set(&key, key.to_string().into_bytes()).await;
+        }
+        service
+    }
+
+    async fn remaining_keys(service: &CacheService) -> Vec<CacheKey> {
+        let mut remaining = Vec::new();
+        for key in all_keys() {
+            if service.get(&key).await.is_some() {
+                remaining.push(key);
+            }
+        }
+        remaining
+    }
+
+    #[test]
+    fn test_key_string_format() {
+        let expected = [
+            "post:p1",
+            "posts:page:1:size:10",
+            "note:n1",
+            "notes:page:2:size:20",
+            "page:about",
+            "categories",
+        ];
+        for (key, expected) in all_keys().iter().zip(expected) {
+            assert_eq!(key.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_prefix_matches_first_segment() {
+        for key in all_keys() {
+            let key_str = key.to_string();
+            let first_segment = key_str.split(':').next().unwrap();
+            assert_eq!(key.prefix(), first_segment);
+            assert!(key.has_tag(key.prefix()), "{} 应带有自身前缀标签", key);
+        }
+    }
+
+    #[test]
+    fn test_key_round_trip() {
+        for key in all_keys() {
+            assert_eq!(key.to_string().parse::<CacheKey>(), Ok(key.clone()));
+        }
+        assert!("posts:page:x:size:10".parse::<CacheKey>().is_err());
+        assert!("links".parse::<CacheKey>().is_err());
+    }
+
+    #[test]
+    fn test_category_tag() {
+        let tagged: Vec<CacheKey> = all_keys()
+            .into_iter()
+            .filter(|k| k.has_tag("categories"))
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![
+                CacheKey::Post("p1".to_string()),
+                CacheKey::PostList { page: 1, size: 10 },
+                CacheKey::Categories,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_single_key() {
+        for key in all_keys() {
+            let service = populated_cache().await;
+            service.invalidate(&key).await;
+
+            let expected: Vec<CacheKey> = all_keys().into_iter().filter(|k| *k != key).collect();
+            assert_eq!(remaining_keys(&service).await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_by_prefix_is_exact() {
+        for key in all_keys() {
+            let service = populated_cache().await;
+            service.invalidate_by_prefix(key.prefix()).await;
+
+            let expected: Vec<CacheKey> = all_keys().into_iter().filter(|k| *k != key).collect();
+            assert_eq!(remaining_keys(&service).await, expected, "前缀: {}", key.prefix());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_by_prefix_keeps_other_pages() {
+        let service = CacheService::new(100, 60);
+        let page_1 = CacheKey::PostList { page: 1, size: 10 };
+        let page_2 = CacheKey::PostList { page: 2, size: 10 };
+        let detail = CacheKey::Post("p1".to_string());
+        for key in [&page_1, &page_2, &detail] {
+            service.set(key, vec![1]).await;
+        }
+
+        service.invalidate_by_prefix("posts").await;
+
+        assert!(service.get(&page_1).await.is_none());
+        assert!(service.get(&page_2).await.is_none());
+        assert!(service.get(&detail).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_by_unknown_prefix_is_noop() {
+        let service = populated_cache().await;
+        service.invalidate_by_prefix("links").await;
+        assert_eq!(remaining_keys(&service).await, all_keys());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_by_tag() {
+        let service = populated_cache().await;
+        service.invalidate_by_tag("categories").await;
+        assert_eq!(
+            remaining_keys(&service).await,
+            vec![
+                CacheKey::Note("n1".to_string()),
+                CacheKey::NoteList { page: 2, size: 20 },
+                CacheKey::Page("about".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entries_written_after_invalidation_survive() {
+        let service = populated_cache().await;
+        service.invalidate_by_prefix("posts").await;
+
+        let key = CacheKey::PostList { page: 1, size: 10 };
+        service.set(&key, vec![42]).await;
+        assert_eq!(service.get(&key).await, Some(vec![42]));
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let service = populated_cache().await;
+        service.clear().await;
+        assert!(remaining_keys(&service).await.is_empty());
+    }
+}
@@ -2,50 +2,125 @@
 
 use mongodb::{
     bson::{doc, Document},
-    change_stream::event::ChangeStreamEvent,
+    change_stream::event::{ChangeStreamEvent, OperationType},
     options::ChangeStreamOptions,
     Database,
 };
+use chrono::{DateTime, Utc};
 use futures::stream::TryStreamExt;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tokio::time::sleep;
 
 use super::cache_service::{CacheKey, CacheService};
+use super::metrics::metrics;
+use super::options_service::OptionsService;
 use super::revalidation_service::RevalidationService;
+use super::webhook::service::WebhookService;
+use crate::models::WebhookEvent;
+use serde_json::json;
+
+/// 友链状态：待审核（友链申请）
+const LINK_STATE_AUDIT: i32 = 1;
+
+/// Change Stream 运行状态快照
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChangeStreamStatus {
+    /// 监听任务是否已启动
+    pub enabled: bool,
+    /// 当前是否已建立连接
+    pub connected: bool,
+    /// 最近一次处理事件的时间
+    pub last_event_at: Option<DateTime<Utc>>,
+}
+
+/// Change Stream 健康状态（在监听任务和健康检查路由之间共享）
+#[derive(Debug, Clone, Default)]
+pub struct ChangeStreamHealth {
+    inner: Arc<RwLock<ChangeStreamStatus>>,
+}
+
+impl ChangeStreamHealth {
+    /// 获取当前状态
+    pub fn status(&self) -> ChangeStreamStatus {
+        *self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ChangeStreamStatus)) {
+        f(&mut self.inner.write().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    fn mark_enabled(&self) {
+        self.update(|s| s.enabled = true);
+    }
+
+    fn set_connected(&self, connected: bool) {
+        self.update(|s| s.connected = connected);
+    }
+
+    fn record_event(&self) {
+        self.update(|s| s.last_event_at = Some(Utc::now()));
+    }
+}
 
 /// Change Stream 监听服务
+///
+/// 负责清除本地缓存、刷新站点配置快照，并在配置了 Revalidation 时通知 Next.js；
+/// 博文发布和友链申请同时推送 Webhook 事件。
 pub struct ChangeStreamService {
     db: Database,
     cache_service: CacheService,
-    revalidation_service: RevalidationService,
+    options_service: OptionsService,
+    revalidation_service: Option<RevalidationService>,
+    health: ChangeStreamHealth,
 }
 
 impl ChangeStreamService {
     /// 创建新的 Change Stream 服务实例
+    ///
+    /// `revalidation_service` 为 None 时只处理本地缓存和站点配置
     pub fn new(
         db: Database,
         cache_service: CacheService,
-        revalidation_service: RevalidationService,
+        options_service: OptionsService,
+        revalidation_service: Option<RevalidationService>,
+        health: ChangeStreamHealth,
     ) -> Self {
         Self {
             db,
             cache_service,
+            options_service,
             revalidation_service,
+            health,
+        }
+    }
+
+    /// 通知 Next.js 重新验证标签，未配置 Revalidation 或请求失败时返回 false
+    async fn revalidate(&self, tag: &str) -> bool {
+        match &self.revalidation_service {
+            Some(service) => service.revalidate_tag(tag).await.is_ok(),
+            None => false,
         }
     }
 
     /// 启动 Change Stream 监听（带自动重连）
     pub async fn start_watching(&self) {
-        log::info!("启动 MongoDB Change Stream 监听服务...");
+        tracing::info!("启动 MongoDB Change Stream 监听服务...");
+        self.health.mark_enabled();
 
         loop {
-            match self.watch_collections().await {
+            let result = self.watch_collections().await;
+            self.health.set_connected(false);
+            // 在断开时计数，处于退避等待中的断开也会被统计
+            metrics().change_stream_reconnects_total.inc();
+
+            match result {
                 Ok(_) => {
-                    log::warn!("Change Stream 正常结束，准备重新连接...");
+                    tracing::warn!("Change Stream 正常结束，准备重新连接...");
                 }
                 Err(e) => {
-                    log::error!("Change Stream 错误: {:?}", e);
-                    log::info!("5秒后尝试重新连接...");
+                    tracing::error!("Change Stream 错误: {:?}", e);
+                    tracing::info!("5秒后尝试重新连接...");
                 }
             }
 
@@ -61,7 +136,7 @@ impl ChangeStreamService {
             doc! {
                 "$match": {
                     "operationType": { "$in": ["insert", "update", "replace", "delete"] },
-                    "ns.coll": { "$in": ["posts", "notes", "pages", "categories"] }
+                    "ns.coll": { "$in": ["posts", "notes", "pages", "categories", "options", "links"] }
                 }
             },
         ];
@@ -70,7 +145,7 @@ impl ChangeStreamService {
             .full_document(Some(mongodb::options::FullDocumentType::UpdateLookup))
             .build();
 
-        log::info!("正在建立 Change Stream 连接...");
+        tracing::info!("正在建立 Change Stream 连接...");
         let mut change_stream = self
             .db
             .watch()
@@ -78,11 +153,16 @@ impl ChangeStreamService {
             .with_options(options)
             .await?;
 
-        log::info!("✓ Change Stream 连接成功，开始监听数据变更");
+        tracing::info!("✓ Change Stream 连接成功，开始监听数据变更");
+        self.health.set_connected(true);
+
+        // 断开期间（以及启动加载到建立连接之间）的配置变更不会产生事件，连接后补一次全量加载
+        self.reload_options().await;
 
         // 持续监听变更事件
         while let Some(event) = change_stream.try_next().await? {
             self.handle_change_event(event).await;
+            self.health.record_event();
         }
 
         Ok(())
@@ -97,11 +177,15 @@ impl ChangeStreamService {
             .and_then(|ns| ns.coll.as_deref())
             .unwrap_or("unknown");
 
-        log::info!(
+        tracing::info!(
             "检测到数据变更 - 集合: {}, 操作: {}",
             collection_name,
             operation_type
         );
+        metrics()
+            .change_stream_events_total
+            .with_label_values(&[collection_name, operation_type.as_str()])
+            .inc();
 
         // 根据集合类型处理缓存失效
         match collection_name {
@@ -117,8 +201,14 @@ impl ChangeStreamService {
             "categories" => {
                 self.handle_category_change().await;
             }
+            "options" => {
+                self.handle_options_change().await;
+            }
+            "links" => {
+                self.handle_link_change(&event).await;
+            }
             _ => {
-                log::debug!("忽略集合: {}", collection_name);
+                tracing::debug!("忽略集合: {}", collection_name);
             }
         }
     }
@@ -150,13 +240,14 @@ impl ChangeStreamService {
             }
         }
 
-        // 1. 清除本地缓存（仅清除具体文章）
+        // 1. 清除本地缓存：具体文章 + 博文列表（列表内嵌了标题、摘要等字段，任何变更都会影响）
         if let Some(ref id) = post_id {
             self.cache_service
                 .invalidate(&CacheKey::Post(id.clone()))
                 .await;
-            log::info!("已清除博文本地缓存: {}", id);
+            tracing::info!("已清除博文本地缓存: {}", id);
         }
+        self.cache_service.invalidate_by_prefix("posts").await;
 
         // 2. 通知 Next.js 重新验证（细粒度刷新）
         let mut revalidated_tags = Vec::new();
@@ -164,7 +255,7 @@ impl ChangeStreamService {
         // 刷新具体文章（按 ID）
         if let Some(ref id) = post_id {
             let tag = format!("post-{}", id);
-            if self.revalidation_service.revalidate_tag(&tag).await.is_ok() {
+            if self.revalidate(&tag).await {
                 revalidated_tags.push(tag);
             }
         }
@@ -172,36 +263,57 @@ impl ChangeStreamService {
         // 刷新具体文章（按 slug）
         if let Some(ref slug) = post_slug {
             let tag = format!("post-slug-{}", slug);
-            if self.revalidation_service.revalidate_tag(&tag).await.is_ok() {
+            if self.revalidate(&tag).await {
                 revalidated_tags.push(tag);
             }
         }
 
-        // 仅在数量变化（insert/delete）时刷新列表页、首页和 Moka 缓存
+        // 仅在数量变化（insert/delete）时刷新列表页和首页
         if is_count_change {
             // 刷新博文列表
-            if self.revalidation_service.revalidate_tag("posts").await.is_ok() {
+            if self.revalidate("posts").await {
                 revalidated_tags.push("posts".to_string());
             }
 
             // 刷新首页
-            if self.revalidation_service.revalidate_tag("home").await.is_ok() {
+            if self.revalidate("home").await {
                 revalidated_tags.push("home".to_string());
             }
 
-            // 清除 Moka 列表缓存
-            self.cache_service.invalidate_by_prefix("posts").await;
-
-            log::info!(
+            tracing::info!(
                 "✓ 博文数量变化 ({}) - 已刷新列表页和首页",
                 operation_type
             );
         }
 
-        log::info!(
+        tracing::info!(
             "✓ 博文缓存已刷新 - id: {:?}, slug: {:?}, tags: {:?}",
             post_id, post_slug, revalidated_tags
         );
+
+        // 3. 博文发布（以已发布状态新建，或 isPublished 改为 true）时推送 Webhook
+        let published = match event.operation_type {
+            OperationType::Insert => event
+                .full_document
+                .as_ref()
+                .is_some_and(|doc| doc.get_bool("isPublished").unwrap_or(false)),
+            OperationType::Update => event
+                .update_description
+                .as_ref()
+                .is_some_and(|desc| desc.updated_fields.get_bool("isPublished").unwrap_or(false)),
+            _ => false,
+        };
+        if let (true, Some(post)) = (published, &event.full_document) {
+            let payload = json!({
+                "id": post_id,
+                "title": post.get_str("title").unwrap_or_default(),
+                "slug": post_slug,
+                "summary": post.get_str("summary").ok(),
+            });
+            WebhookService::new(&self.db)
+                .emit_once(WebhookEvent::PostPublished, payload, &event_key(event))
+                .await;
+        }
     }
 
     /// 处理手记变更
@@ -231,13 +343,14 @@ impl ChangeStreamService {
             }
         }
 
-        // 1. 清除本地缓存（仅清除具体手记）
+        // 1. 清除本地缓存：具体手记 + 手记列表
         if let Some(ref id) = note_id {
             self.cache_service
                 .invalidate(&CacheKey::Note(id.clone()))
                 .await;
-            log::info!("已清除手记本地缓存: {}", id);
+            tracing::info!("已清除手记本地缓存: {}", id);
         }
+        self.cache_service.invalidate_by_prefix("notes").await;
 
         // 2. 通知 Next.js 重新验证（细粒度刷新）
         let mut revalidated_tags = Vec::new();
@@ -245,7 +358,7 @@ impl ChangeStreamService {
         // 刷新具体手记（按 ID）
         if let Some(ref id) = note_id {
             let tag = format!("note-{}", id);
-            if self.revalidation_service.revalidate_tag(&tag).await.is_ok() {
+            if self.revalidate(&tag).await {
                 revalidated_tags.push(tag);
             }
         }
@@ -253,33 +366,30 @@ impl ChangeStreamService {
         // 刷新具体手记（按 nid）
         if let Some(nid) = note_nid {
             let tag = format!("note-nid-{}", nid);
-            if self.revalidation_service.revalidate_tag(&tag).await.is_ok() {
+            if self.revalidate(&tag).await {
                 revalidated_tags.push(tag);
             }
         }
 
-        // 仅在数量变化（insert/delete）时刷新列表页、首页和 Moka 缓存
+        // 仅在数量变化（insert/delete）时刷新列表页和首页
         if is_count_change {
             // 刷新手记列表
-            if self.revalidation_service.revalidate_tag("notes").await.is_ok() {
+            if self.revalidate("notes").await {
                 revalidated_tags.push("notes".to_string());
             }
 
             // 刷新首页
-            if self.revalidation_service.revalidate_tag("home").await.is_ok() {
+            if self.revalidate("home").await {
                 revalidated_tags.push("home".to_string());
             }
 
-            // 清除 Moka 列表缓存
-            self.cache_service.invalidate_by_prefix("notes").await;
-
-            log::info!(
+            tracing::info!(
                 "✓ 手记数量变化 ({}) - 已刷新列表页和首页",
                 operation_type
             );
         }
 
-        log::info!(
+        tracing::info!(
             "✓ 手记缓存已刷新 - id: {:?}, nid: {:?}, tags: {:?}",
             note_id, note_nid, revalidated_tags
         );
@@ -295,11 +405,18 @@ impl ChangeStreamService {
             .map(|s| s.to_string());
 
         // 1. 清除本地缓存（仅清除具体页面）
-        if let Some(ref slug) = page_slug {
-            self.cache_service
-                .invalidate(&CacheKey::Page(slug.clone()))
-                .await;
-            log::info!("已清除页面本地缓存: {}", slug);
+        // 删除事件不带 full_document，无法得知 slug，只能清除全部页面缓存
+        match page_slug {
+            Some(ref slug) => {
+                self.cache_service
+                    .invalidate(&CacheKey::Page(slug.clone()))
+                    .await;
+                tracing::info!("已清除页面本地缓存: {}", slug);
+            }
+            None => {
+                self.cache_service.invalidate_by_prefix("page").await;
+                tracing::info!("页面 slug 未知，已清除全部页面本地缓存");
+            }
         }
 
         // 2. 通知 Next.js 重新验证（仅刷新具体页面，不刷新整个 pages 标签）
@@ -307,12 +424,12 @@ impl ChangeStreamService {
 
         if let Some(ref slug) = page_slug {
             let tag = format!("page-{}", slug);
-            if self.revalidation_service.revalidate_tag(&tag).await.is_ok() {
+            if self.revalidate(&tag).await {
                 revalidated_tags.push(tag);
             }
         }
 
-        log::info!(
+        tracing::info!(
             "✓ 页面缓存已刷新 - slug: {:?}, tags: {:?}",
             page_slug, revalidated_tags
         );
@@ -320,25 +437,64 @@ impl ChangeStreamService {
 
     /// 处理分类变更
     async fn handle_category_change(&self) {
-        // 1. 清除本地缓存
-        self.cache_service
-            .invalidate(&CacheKey::Categories)
+        // 1. 清除本地缓存：分类列表，以及内嵌分类信息的博文详情和博文列表
+        self.cache_service.invalidate_by_tag("categories").await;
+
+        tracing::info!("已清除分类缓存");
+
+        // 2. 通知 Next.js 重新验证
+        if self.revalidate("categories").await {
+            tracing::info!("✓ 已通知 Next.js 重新验证分类页面");
+        }
+    }
+
+    /// 处理友链变更：新的友链申请（待审核状态）推送 Webhook
+    async fn handle_link_change(&self, event: &ChangeStreamEvent<Document>) {
+        if event.operation_type != OperationType::Insert {
+            return;
+        }
+        let Some(link) = event.full_document.as_ref() else {
+            return;
+        };
+        if link.get_i32("state").ok() != Some(LINK_STATE_AUDIT) {
+            return;
+        }
+
+        let payload = json!({
+            "id": link.get_object_id("_id").ok().map(|id| id.to_hex()),
+            "name": link.get_str("name").unwrap_or_default(),
+            "url": link.get_str("url").unwrap_or_default(),
+            "avatar": link.get_str("avatar").ok(),
+            "description": link.get_str("description").ok(),
+        });
+        WebhookService::new(&self.db)
+            .emit_once(WebhookEvent::LinkApplied, payload, &event_key(event))
             .await;
+    }
 
-        // 分类变更会影响博文列表，也需要清除
-        self.cache_service.invalidate_by_prefix("posts").await;
+    /// 处理站点配置变更
+    async fn handle_options_change(&self) {
+        // 1. 刷新内存中的配置快照
+        self.reload_options().await;
 
-        log::info!("已清除分类缓存");
+        // 2. 通知 Next.js 重新验证站点配置
+        if self.revalidate("site-config").await {
+            tracing::info!("✓ 已通知 Next.js 重新验证站点配置");
+        }
+    }
 
-        // 2. 通知 Next.js 重新验证
-        if let Err(e) = self
-            .revalidation_service
-            .revalidate_tag("categories")
-            .await
-        {
-            log::error!("通知 Next.js 重新验证失败: {:?}", e);
-        } else {
-            log::info!("✓ 已通知 Next.js 重新验证分类页面");
+    /// 重新加载站点配置快照
+    async fn reload_options(&self) {
+        if let Err(e) = self.options_service.reload().await {
+            tracing::error!("刷新站点配置失败，继续使用旧快照: {}", e);
         }
     }
 }
+
+/// 事件的去重键：resume token 在所有监听同一集群的副本上相同
+fn event_key(event: &ChangeStreamEvent<Document>) -> String {
+    match mongodb::bson::to_bson(&event.id) {
+        Ok(token) => token.to_string(),
+        Err(_) => format!("{:?}", event.id),
+    }
+}
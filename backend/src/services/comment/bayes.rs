@@ -0,0 +1,284 @@
+//! 本地朴素贝叶斯垃圾评论分类器
+//!
+//! 以博主的审核操作为样本增量训练：标为已读或通过审核视为正常评论，标记垃圾视为垃圾评论。
+//! 模型即每个词出现在多少条垃圾 / 正常评论中，保存在 `spam_classifier` 集合
+//! （`{ _id: 词, spam, ham }`，另有一条 `_id: "__totals__"` 记录样本总数）。
+//!
+//! 分词：中日韩文字按相邻二字切分，其它文字按单词切分，链接额外记录域名。
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::{Collection, Database};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use crate::models::{Comment, SpamLabel};
+
+/// 样本总数记录的 ID（分词结果不含下划线，不会与词冲突）
+const TOTALS_ID: &str = "__totals__";
+/// 垃圾和正常样本都达到该数量后才给出判断
+pub const MIN_TRAINING_DOCS: i64 = 10;
+/// 每条评论最多取的词数
+const MAX_TOKENS: usize = 300;
+/// 单词长度上限（过长的多为乱码或编码内容）
+const MAX_WORD_LEN: usize = 30;
+
+/// 链接域名
+static URL_HOST: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"https?://(?:www\.)?([^/\s?#:]+)").unwrap());
+
+/// 中日韩文字（汉字、假名、谚文）
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{4E00}'..='\u{9FFF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{AC00}'..='\u{D7AF}')
+}
+
+/// 分词（去重，按出现顺序最多取 `MAX_TOKENS` 个）
+pub fn tokenize(text: &str) -> Vec<String> {
+    let text = text.to_lowercase();
+    let mut tokens = Tokens::default();
+
+    for caps in URL_HOST.captures_iter(&text) {
+        tokens.push(format!("host:{}", &caps[1]));
+    }
+
+    let mut word = String::new();
+    let mut cjk: Vec<char> = Vec::new();
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if is_cjk(c) {
+            tokens.push_word(&mut word);
+            cjk.push(c);
+        } else if c.is_alphanumeric() {
+            tokens.push_cjk(&mut cjk);
+            word.push(c);
+        } else {
+            tokens.push_word(&mut word);
+            tokens.push_cjk(&mut cjk);
+        }
+    }
+
+    tokens.list
+}
+
+#[derive(Default)]
+struct Tokens {
+    seen: HashSet<String>,
+    list: Vec<String>,
+}
+
+impl Tokens {
+    fn push(&mut self, token: String) {
+        if self.list.len() < MAX_TOKENS && self.seen.insert(token.clone()) {
+            self.list.push(token);
+        }
+    }
+
+    fn push_word(&mut self, word: &mut String) {
+        let len = word.chars().count();
+        if (2..=MAX_WORD_LEN).contains(&len) {
+            self.push(word.clone());
+        }
+        word.clear();
+    }
+
+    fn push_cjk(&mut self, run: &mut Vec<char>) {
+        match run.len() {
+            0 => {}
+            1 => self.push(run[0].to_string()),
+            _ => {
+                for pair in run.windows(2) {
+                    self.push(pair.iter().collect());
+                }
+            }
+        }
+        run.clear();
+    }
+}
+
+/// 垃圾概率：先验比加上各词似然比（拉普拉斯平滑）的对数和，再映射到 0-1
+///
+/// `counts` 为评论中每个已知词的（垃圾样本数, 正常样本数），未出现过的词不参与计算
+pub fn spam_probability(spam_docs: i64, ham_docs: i64, counts: &[(i64, i64)]) -> f64 {
+    let (spam_docs, ham_docs) = (spam_docs as f64, ham_docs as f64);
+    let mut log_odds = ((spam_docs + 1.0) / (ham_docs + 1.0)).ln();
+    for &(spam, ham) in counts {
+        if spam + ham <= 0 {
+            continue;
+        }
+        let p_spam = (spam.max(0) as f64 + 1.0) / (spam_docs + 2.0);
+        let p_ham = (ham.max(0) as f64 + 1.0) / (ham_docs + 2.0);
+        log_odds += (p_spam / p_ham).ln();
+    }
+    1.0 / (1.0 + (-log_odds).exp())
+}
+
+/// 词的样本计数（`spam_classifier` 集合）
+#[derive(Debug, Deserialize)]
+struct TokenCounts {
+    #[serde(rename = "_id")]
+    token: String,
+    #[serde(default)]
+    spam: i64,
+    #[serde(default)]
+    ham: i64,
+}
+
+/// 贝叶斯分类器
+pub struct BayesClassifier {
+    tokens: Collection<TokenCounts>,
+    comments: Collection<Comment>,
+}
+
+impl BayesClassifier {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            tokens: db.collection::<TokenCounts>("spam_classifier"),
+            comments: db.collection::<Comment>("comments"),
+        }
+    }
+
+    /// 评论为垃圾的概率；样本不足时返回 None
+    pub async fn classify(&self, text: &str, author: &str) -> Result<Option<f64>, String> {
+        let totals = self
+            .tokens
+            .find_one(doc! { "_id": TOTALS_ID })
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(totals) = totals.filter(|t| t.spam >= MIN_TRAINING_DOCS && t.ham >= MIN_TRAINING_DOCS) else {
+            return Ok(None);
+        };
+
+        let tokens = tokenize(&format!("{} {}", author, text));
+        let known: Vec<TokenCounts> = self
+            .tokens
+            .find(doc! { "_id": { "$in": &tokens } })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+        let counts: Vec<(i64, i64)> = known
+            .iter()
+            .filter(|t| t.token != TOTALS_ID)
+            .map(|t| (t.spam, t.ham))
+            .collect();
+
+        Ok(Some(spam_probability(totals.spam, totals.ham, &counts)))
+    }
+
+    /// 以博主的审核结果训练，返回新训练（或改判）的评论数
+    ///
+    /// 已按同一标签训练过的评论跳过；改判的评论先撤销旧样本再计入新样本
+    pub async fn train(&self, ids: &[ObjectId], label: SpamLabel) -> Result<u64, String> {
+        let comments: Vec<Comment> = self
+            .comments
+            .find(doc! {
+                "_id": { "$in": ids },
+                "trainedAs": { "$ne": label.as_str() },
+                "text": { "$ne": "" },
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut deltas: HashMap<String, (i64, i64)> = HashMap::new();
+        let mut trained = 0;
+        for comment in comments {
+            let Some(id) = comment.id else { continue };
+            let previous = comment.trained_as;
+
+            // 以旧标签为条件改写，并发训练同一条评论时只计入一次
+            let previous_bson = previous.map_or(Bson::Null, |p| Bson::String(p.as_str().to_string()));
+            let claimed = self
+                .comments
+                .update_one(
+                    doc! { "_id": id, "trainedAs": previous_bson },
+                    doc! { "$set": { "trainedAs": label.as_str() } },
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            if claimed.modified_count == 0 {
+                continue;
+            }
+            trained += 1;
+
+            let tokens = tokenize(&format!("{} {}", comment.author, comment.text));
+            for token in tokens.into_iter().chain(std::iter::once(TOTALS_ID.to_string())) {
+                let entry = deltas.entry(token).or_default();
+                apply(entry, label, 1);
+                if let Some(previous) = previous {
+                    apply(entry, previous, -1);
+                }
+            }
+        }
+
+        for (token, (spam, ham)) in deltas {
+            if spam == 0 && ham == 0 {
+                continue;
+            }
+            self.tokens
+                .clone_with_type::<Document>()
+                .update_one(
+                    doc! { "_id": token },
+                    doc! { "$inc": { "spam": spam, "ham": ham } },
+                )
+                .upsert(true)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(trained)
+    }
+}
+
+fn apply(entry: &mut (i64, i64), label: SpamLabel, delta: i64) {
+    match label {
+        SpamLabel::Spam => entry.0 += delta,
+        SpamLabel::Ham => entry.1 += delta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_cjk_into_bigrams() {
+        let tokens = tokenize("加微信 BUY-cheap 好 https://www.Spam.example/x?a=1 a");
+        assert_eq!(
+            tokens,
+            vec![
+                "host:spam.example",
+                "加微",
+                "微信",
+                "buy",
+                "cheap",
+                "好",
+                "https",
+                "www",
+                "spam",
+                "example",
+            ]
+        );
+        assert_eq!(tokenize("哈哈哈哈"), vec!["哈哈"]);
+    }
+
+    #[test]
+    fn probability_follows_token_evidence() {
+        // 未训练过的词只剩先验
+        assert!((spam_probability(10, 10, &[]) - 0.5).abs() < 1e-9);
+        assert!((spam_probability(10, 10, &[(0, 0)]) - 0.5).abs() < 1e-9);
+
+        let spammy = spam_probability(20, 20, &[(18, 0), (15, 1)]);
+        let hammy = spam_probability(20, 20, &[(0, 17), (1, 12)]);
+        assert!(spammy > 0.99, "{}", spammy);
+        assert!(hammy < 0.01, "{}", hammy);
+    }
+}
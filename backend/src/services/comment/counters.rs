@@ -0,0 +1,386 @@
+//! 评论序号分配
+//!
+//! `key`（层级标识，如 `#3#2`）和 `commentsIndex` 从 `comment_counters` 集合中的计数器原子分配：
+//! - `index:<refType>:<ref>`: 文章下的评论序号
+//! - `root:<refType>:<ref>`: 文章下的根评论序号
+//! - `children:<parent>`: 父评论下的回复序号
+//!
+//! 计数器首次使用时以现有评论的最大序号初始化。`comments` 集合上的唯一索引保证同一文章下
+//! `key` 和 `commentsIndex` 不重复；历史数据中的重复序号导致索引创建失败时，先修复再创建。
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::options::{IndexOptions, ReturnDocument};
+use mongodb::{Collection, Database, IndexModel};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+
+use crate::services::db_service::is_duplicate_key;
+
+/// 评论的序号字段（修复重复序号时使用）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentSlot {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    #[serde(default)]
+    pub parent: Option<ObjectId>,
+    pub key: String,
+    pub comments_index: i32,
+}
+
+/// 评论序号计数器
+pub struct CommentCounters {
+    counters: Collection<Document>,
+    comments: Collection<Document>,
+}
+
+impl CommentCounters {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            counters: db.collection::<Document>("comment_counters"),
+            comments: db.collection::<Document>("comments"),
+        }
+    }
+
+    /// 创建 `key` 和 `commentsIndex` 的唯一索引，返回修复的评论数
+    ///
+    /// 索引已存在时不做任何事；只有重复序号导致创建失败时（升级后首次启动）才扫描并修复，再重新创建
+    pub async fn ensure_indexes(&self) -> Result<u64, String> {
+        match self.create_unique_indexes().await {
+            Ok(()) => return Ok(0),
+            Err(e) if is_duplicate_key(&e) => tracing::info!("评论存在重复序号，修复后创建唯一索引"),
+            Err(e) => return Err(e.to_string()),
+        }
+        let repaired = self.repair_duplicates().await?;
+        self.create_unique_indexes().await.map_err(|e| e.to_string())?;
+        Ok(repaired)
+    }
+
+    async fn create_unique_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let unique = || IndexOptions::builder().unique(true).build();
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "ref": 1, "refType": 1, "key": 1 })
+                .options(unique())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "ref": 1, "refType": 1, "commentsIndex": 1 })
+                .options(unique())
+                .build(),
+        ];
+        self.comments.create_indexes(indexes).await?;
+        Ok(())
+    }
+
+    /// 分配文章下的下一个评论序号
+    pub async fn next_index(&self, ref_oid: ObjectId, ref_type: &str) -> Result<i32, String> {
+        let seq = self
+            .next(
+                format!("index:{}:{}", ref_type, ref_oid),
+                self.max_index(ref_oid, ref_type),
+            )
+            .await?;
+        Ok(seq as i32)
+    }
+
+    /// 分配文章下的下一个根评论序号
+    pub async fn next_root(&self, ref_oid: ObjectId, ref_type: &str) -> Result<i64, String> {
+        self.next(
+            format!("root:{}:{}", ref_type, ref_oid),
+            self.max_key_number(doc! { "ref": ref_oid, "refType": ref_type, "parent": null }),
+        )
+        .await
+    }
+
+    /// 分配父评论下的下一个回复序号
+    pub async fn next_child(&self, parent: ObjectId) -> Result<i64, String> {
+        self.next(
+            format!("children:{}", parent),
+            self.max_key_number(doc! { "parent": parent }),
+        )
+        .await
+    }
+
+    /// 计数器加一并返回新值；计数器不存在时先以 `seed`（现有的最大序号）初始化
+    async fn next(&self, id: String, seed: impl Future<Output = Result<i64, String>>) -> Result<i64, String> {
+        let exists = self
+            .counters
+            .find_one(doc! { "_id": &id })
+            .await
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if !exists {
+            let seed = seed.await?;
+            // `$max` 可重复执行，并发初始化时的唯一索引冲突可以忽略
+            let result = self
+                .counters
+                .update_one(doc! { "_id": &id }, doc! { "$max": { "seq": seed } })
+                .upsert(true)
+                .await;
+            match result {
+                Ok(_) => {}
+                Err(e) if is_duplicate_key(&e) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        let counter = self
+            .counters
+            .find_one_and_update(doc! { "_id": &id }, doc! { "$inc": { "seq": 1_i64 } })
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("计数器 {} 不存在", id))?;
+        counter
+            .get_i64("seq")
+            .or_else(|_| counter.get_i32("seq").map(i64::from))
+            .map_err(|e| e.to_string())
+    }
+
+    async fn max_index(&self, ref_oid: ObjectId, ref_type: &str) -> Result<i64, String> {
+        let comment = self
+            .comments
+            .find_one(doc! { "ref": ref_oid, "refType": ref_type })
+            .sort(doc! { "commentsIndex": -1 })
+            .projection(doc! { "commentsIndex": 1 })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(comment
+            .and_then(|doc| doc.get_i32("commentsIndex").ok())
+            .map_or(0, i64::from))
+    }
+
+    /// 符合条件的评论 `key` 末段序号的最大值
+    async fn max_key_number(&self, filter: Document) -> Result<i64, String> {
+        let keys: Vec<Document> = self
+            .comments
+            .find(filter)
+            .projection(doc! { "key": 1 })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(keys
+            .iter()
+            .filter_map(|doc| doc.get_str("key").ok().and_then(last_number))
+            .max()
+            .unwrap_or(0))
+    }
+
+    /// 修复同一文章下重复的 `key` 和 `commentsIndex`，返回修改的评论数
+    ///
+    /// 先发表的评论保留原序号，之后的重复评论改用新序号（回复的 `key` 随父评论一起更新），
+    /// 并重置相关计数器
+    async fn repair_duplicates(&self) -> Result<u64, String> {
+        let mut refs: HashSet<(ObjectId, String)> = HashSet::new();
+        for field in ["$key", "$commentsIndex"] {
+            let pipeline = vec![
+                doc! { "$group": {
+                    "_id": { "ref": "$ref", "refType": "$refType", "value": field },
+                    "count": { "$sum": 1 },
+                } },
+                doc! { "$match": { "count": { "$gt": 1 } } },
+            ];
+            let groups: Vec<Document> = self
+                .comments
+                .aggregate(pipeline)
+                .await
+                .map_err(|e| e.to_string())?
+                .try_collect()
+                .await
+                .map_err(|e| e.to_string())?;
+            for group in groups {
+                let Ok(id) = group.get_document("_id") else { continue };
+                if let (Ok(ref_oid), Ok(ref_type)) = (id.get_object_id("ref"), id.get_str("refType")) {
+                    refs.insert((ref_oid, ref_type.to_string()));
+                }
+            }
+        }
+
+        let mut repaired = 0;
+        for (ref_oid, ref_type) in refs {
+            let slots: Vec<CommentSlot> = self
+                .comments
+                .clone_with_type::<CommentSlot>()
+                .find(doc! { "ref": ref_oid, "refType": &ref_type })
+                .sort(doc! { "created": 1, "_id": 1 })
+                .projection(doc! { "parent": 1, "key": 1, "commentsIndex": 1 })
+                .await
+                .map_err(|e| e.to_string())?
+                .try_collect()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let repairs = plan_repairs(&slots);
+            for slot in &slots {
+                let Some((key, index)) = repairs.get(&slot.id) else { continue };
+                self.comments
+                    .update_one(
+                        doc! { "_id": slot.id },
+                        doc! { "$set": { "key": key, "commentsIndex": index } },
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            tracing::info!("已修复 {} {} 下 {} 条评论的重复序号", ref_type, ref_oid, repairs.len());
+            repaired += repairs.len() as u64;
+
+            // 重置计数器，下次分配时按修复后的数据重新初始化
+            let mut ids = vec![
+                format!("index:{}:{}", ref_type, ref_oid),
+                format!("root:{}:{}", ref_type, ref_oid),
+            ];
+            ids.extend(slots.iter().map(|slot| format!("children:{}", slot.id)));
+            self.counters
+                .delete_many(doc! { "_id": { "$in": ids } })
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(repaired)
+    }
+}
+
+/// `key` 末段的序号（`#3#2` → 2）
+fn last_number(key: &str) -> Option<i64> {
+    key.rsplit('#').next()?.parse().ok().filter(|n| *n >= 1)
+}
+
+/// 计算一篇文章下需要修改的评论及其新的（`key`, `commentsIndex`）
+///
+/// `slots` 按发表顺序排列。先出现的评论保留原序号；重复的评论改用同级最大序号之后的新序号，
+/// 其回复的 `key` 前缀随之更新。父评论已不存在的回复在 `key` 冲突时改为根评论序号。
+pub fn plan_repairs(slots: &[CommentSlot]) -> HashMap<ObjectId, (String, i32)> {
+    // commentsIndex
+    let mut max_index = slots.iter().map(|slot| slot.comments_index).max().unwrap_or(0);
+    let mut seen_indexes = HashSet::new();
+    let mut indexes: HashMap<ObjectId, i32> = HashMap::new();
+    for slot in slots {
+        let index = if slot.comments_index >= 1 && seen_indexes.insert(slot.comments_index) {
+            slot.comments_index
+        } else {
+            max_index += 1;
+            seen_indexes.insert(max_index);
+            max_index
+        };
+        indexes.insert(slot.id, index);
+    }
+
+    // key：从根评论开始逐层分配，同级评论按发表顺序
+    let ids: HashSet<ObjectId> = slots.iter().map(|slot| slot.id).collect();
+    let mut children: HashMap<ObjectId, Vec<&CommentSlot>> = HashMap::new();
+    let mut roots = Vec::new();
+    for slot in slots {
+        match slot.parent.filter(|parent| ids.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(slot),
+            None => roots.push(slot),
+        }
+    }
+
+    let mut used: HashSet<String> = HashSet::new();
+    let mut keys: HashMap<ObjectId, String> = HashMap::new();
+    let mut max_root = roots
+        .iter()
+        .filter(|slot| slot.parent.is_none())
+        .filter_map(|slot| last_number(&slot.key))
+        .max()
+        .unwrap_or(0);
+    let mut queue = std::collections::VecDeque::new();
+    for slot in &roots {
+        let key = if slot.parent.is_none() {
+            allocate_key("", last_number(&slot.key), &mut max_root, &used)
+        } else if !used.contains(&slot.key) {
+            slot.key.clone()
+        } else {
+            allocate_key("", None, &mut max_root, &used)
+        };
+        used.insert(key.clone());
+        keys.insert(slot.id, key);
+        queue.push_back(slot.id);
+    }
+
+    while let Some(parent) = queue.pop_front() {
+        let Some(siblings) = children.get(&parent) else { continue };
+        let prefix = keys[&parent].clone();
+        let mut max_sibling = siblings
+            .iter()
+            .filter_map(|slot| last_number(&slot.key))
+            .max()
+            .unwrap_or(0);
+        for slot in siblings {
+            let key = allocate_key(&prefix, last_number(&slot.key), &mut max_sibling, &used);
+            used.insert(key.clone());
+            keys.insert(slot.id, key);
+            queue.push_back(slot.id);
+        }
+    }
+
+    slots
+        .iter()
+        .filter_map(|slot| {
+            let key = keys.get(&slot.id)?.clone();
+            let index = indexes[&slot.id];
+            (key != slot.key || index != slot.comments_index).then_some((slot.id, (key, index)))
+        })
+        .collect()
+}
+
+/// 在 `prefix` 下分配序号：优先使用原序号，已被占用时取 `max` 之后的第一个空闲序号
+fn allocate_key(prefix: &str, preferred: Option<i64>, max: &mut i64, used: &HashSet<String>) -> String {
+    if let Some(n) = preferred {
+        let key = format!("{}#{}", prefix, n);
+        if !used.contains(&key) {
+            return key;
+        }
+    }
+    loop {
+        *max += 1;
+        let key = format!("{}#{}", prefix, max);
+        if !used.contains(&key) {
+            return key;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(id: u8, parent: Option<u8>, key: &str, index: i32) -> CommentSlot {
+        let oid = |n: u8| ObjectId::from_bytes([n; 12]);
+        CommentSlot {
+            id: oid(id),
+            parent: parent.map(oid),
+            key: key.to_string(),
+            comments_index: index,
+        }
+    }
+
+    #[test]
+    fn repairs_duplicates_and_rekeys_replies() {
+        let slots = vec![
+            slot(1, None, "#1", 1),
+            slot(2, None, "#2", 2),
+            slot(3, Some(2), "#2#1", 3),
+            // 与 #2 同时发表的根评论及其回复
+            slot(4, None, "#2", 3),
+            slot(5, Some(4), "#2#1", 4),
+            // 同时发表的两条回复
+            slot(6, Some(1), "#1#1", 5),
+            slot(7, Some(1), "#1#1", 5),
+        ];
+        let repairs = plan_repairs(&slots);
+        let oid = |n: u8| ObjectId::from_bytes([n; 12]);
+
+        assert_eq!(repairs.len(), 3);
+        assert_eq!(repairs[&oid(4)], ("#3".to_string(), 6));
+        assert_eq!(repairs[&oid(5)], ("#3#1".to_string(), 4));
+        assert_eq!(repairs[&oid(7)], ("#1#2".to_string(), 7));
+        assert!(!repairs.contains_key(&oid(3)));
+        assert!(!repairs.contains_key(&oid(6)));
+    }
+}
@@ -1 +1,7 @@
-pub mod service;
\ No newline at end of file
+pub mod service;
+pub mod purge;
+pub mod reactions;
+pub mod rules;
+pub mod bayes;
+pub mod review;
+pub mod counters;
@@ -0,0 +1,24 @@
+//! 墓碑评论清理任务
+
+use mongodb::Database;
+use std::time::Duration;
+
+use super::service::CommentService;
+
+/// 清理间隔
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 定期清理没有回复的墓碑评论（在后台任务中运行，不会返回）
+pub async fn run_tombstone_purge(db: Database) {
+    let service = CommentService::new(&db);
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        match service.purge_tombstones().await {
+            Ok(0) => tracing::debug!("没有需要清理的墓碑评论"),
+            Ok(purged) => tracing::info!("已清理 {} 条没有回复的墓碑评论", purged),
+            Err(e) => tracing::error!("清理墓碑评论失败: {}", e),
+        }
+    }
+}
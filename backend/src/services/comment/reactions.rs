@@ -0,0 +1,251 @@
+//! 评论表情回应 - 每个访客对同一评论的同一表情只计一次
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::{CommentTree, ReactionCount};
+use crate::services::db_service::is_duplicate_key;
+
+/// 表情回应记录（`comment_reactions` 集合）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentReaction {
+    pub comment: ObjectId,
+    pub emoji: String,
+    /// 回应者：登录用户为 `reader:<id>`，匿名访客为 `ip:<地址>`
+    pub actor: String,
+    pub created: DateTime,
+}
+
+/// 表情回应服务
+pub struct ReactionService {
+    collection: Collection<CommentReaction>,
+}
+
+impl ReactionService {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            collection: db.collection::<CommentReaction>("comment_reactions"),
+        }
+    }
+
+    /// 回应者标识：登录用户按 Reader 去重，匿名访客按 IP 去重
+    pub fn actor(user_id: Option<ObjectId>, ip: &str) -> String {
+        match user_id {
+            Some(id) => format!("reader:{}", id.to_hex()),
+            None => format!("ip:{}", ip),
+        }
+    }
+
+    /// 创建 (comment, emoji, actor) 唯一索引
+    pub async fn ensure_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let index = IndexModel::builder()
+            .keys(doc! { "comment": 1, "emoji": 1, "actor": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection.create_index(index).await?;
+        Ok(())
+    }
+
+    /// 添加回应（重复回应视为成功）
+    pub async fn add(&self, comment: ObjectId, emoji: &str, actor: &str) -> Result<(), String> {
+        let result = self
+            .collection
+            .update_one(
+                doc! { "comment": comment, "emoji": emoji, "actor": actor },
+                doc! { "$setOnInsert": { "created": DateTime::now() } },
+            )
+            .upsert(true)
+            .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            // 并发的重复回应触发唯一索引冲突
+            Err(e) if is_duplicate_key(&e) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// 取消回应
+    pub async fn remove(&self, comment: ObjectId, emoji: &str, actor: &str) -> Result<(), String> {
+        self.collection
+            .delete_one(doc! { "comment": comment, "emoji": emoji, "actor": actor })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// 删除评论的全部回应
+    pub async fn remove_for_comments(&self, comments: &[ObjectId]) -> Result<(), String> {
+        self.collection
+            .delete_many(doc! { "comment": { "$in": comments } })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// 批量聚合评论的回应数，并标记当前访客回应过的表情
+    ///
+    /// 返回评论 ID（hex）到回应列表的映射，列表按数量降序
+    pub async fn summarize(
+        &self,
+        comments: &[ObjectId],
+        actor: &str,
+    ) -> Result<HashMap<String, Vec<ReactionCount>>, String> {
+        if comments.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let pipeline = vec![
+            doc! { "$match": { "comment": { "$in": comments } } },
+            doc! {
+                "$group": {
+                    "_id": { "comment": "$comment", "emoji": "$emoji" },
+                    "count": { "$sum": 1 },
+                    "reacted": { "$max": { "$eq": ["$actor", actor] } },
+                }
+            },
+        ];
+        let groups: Vec<Document> = self
+            .collection
+            .aggregate(pipeline)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Self::from_groups(groups))
+    }
+
+    /// 将 `$group` 结果整理为评论 ID 到回应列表的映射（数量降序，同数量按表情排序）
+    fn from_groups(groups: Vec<Document>) -> HashMap<String, Vec<ReactionCount>> {
+        let mut summary: HashMap<String, Vec<ReactionCount>> = HashMap::new();
+        for group in groups {
+            let Ok(key) = group.get_document("_id") else { continue };
+            let (Ok(comment), Ok(emoji)) = (key.get_object_id("comment"), key.get_str("emoji")) else {
+                continue;
+            };
+            let count = group
+                .get_i32("count")
+                .map(i64::from)
+                .or_else(|_| group.get_i64("count"))
+                .unwrap_or(0);
+            summary.entry(comment.to_hex()).or_default().push(ReactionCount {
+                emoji: emoji.to_string(),
+                count,
+                reacted: group.get_bool("reacted").unwrap_or(false),
+            });
+        }
+
+        for reactions in summary.values_mut() {
+            reactions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.emoji.cmp(&b.emoji)));
+        }
+        summary
+    }
+
+    /// 为评论树的每个节点填充回应结果
+    pub async fn attach(&self, nodes: &mut [CommentTree], actor: &str) -> Result<(), String> {
+        let mut ids = Vec::new();
+        collect_ids(nodes, &mut ids);
+        let summary = self.summarize(&ids, actor).await?;
+        Self::apply(nodes, &summary);
+        Ok(())
+    }
+
+    /// 将回应结果填充到评论树的每个节点（墓碑节点除外）
+    fn apply(nodes: &mut [CommentTree], summary: &HashMap<String, Vec<ReactionCount>>) {
+        for node in nodes {
+            if node.is_deleted.is_none() {
+                if let Some(reactions) = summary.get(&node.id) {
+                    node.reactions = reactions.clone();
+                }
+            }
+            Self::apply(&mut node.children, summary);
+        }
+    }
+}
+
+/// 收集评论树中所有节点的 ID
+fn collect_ids(nodes: &[CommentTree], ids: &mut Vec<ObjectId>) {
+    for node in nodes {
+        if let Ok(id) = ObjectId::parse_str(&node.id) {
+            ids.push(id);
+        }
+        collect_ids(&node.children, ids);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actor_prefers_reader_over_ip() {
+        let id = ObjectId::new();
+        assert_eq!(ReactionService::actor(Some(id), "1.2.3.4"), format!("reader:{}", id.to_hex()));
+        assert_eq!(ReactionService::actor(None, "1.2.3.4"), "ip:1.2.3.4");
+    }
+
+    fn counts(summary: &HashMap<String, Vec<ReactionCount>>, comment: ObjectId) -> Vec<(String, i64, bool)> {
+        summary
+            .get(&comment.to_hex())
+            .into_iter()
+            .flatten()
+            .map(|r| (r.emoji.clone(), r.count, r.reacted))
+            .collect()
+    }
+
+    #[test]
+    fn groups_are_summarized_per_comment() {
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        let group = |comment: ObjectId, emoji: &str, count: mongodb::bson::Bson, reacted: bool| {
+            doc! { "_id": { "comment": comment, "emoji": emoji }, "count": count, "reacted": reacted }
+        };
+        let summary = ReactionService::from_groups(vec![
+            group(a, "🎉", 1.into(), false),
+            group(a, "👍", 3_i64.into(), true),
+            group(b, "👍", 1.into(), false),
+            group(a, "👀", 1.into(), true),
+        ]);
+
+        assert_eq!(
+            counts(&summary, a),
+            [("👍".to_string(), 3, true), ("🎉".to_string(), 1, false), ("👀".to_string(), 1, true)]
+        );
+        assert_eq!(counts(&summary, b), [("👍".to_string(), 1, false)]);
+    }
+
+    #[tokio::test]
+    #[ignore = "需要本地 MongoDB（MONGODB_URI）"]
+    async fn add_is_idempotent_under_unique_index() {
+        let db = crate::services::db_service::test_database().await;
+        let reactions = ReactionService::new(&db);
+        reactions.ensure_indexes().await.unwrap();
+        let (comment, other) = (ObjectId::new(), ObjectId::new());
+        let visitor = "ip:203.0.113.7";
+
+        // 同一访客的并发重复回应只计一次
+        let adds = (0..8).map(|_| reactions.add(comment, "👍", visitor));
+        for result in futures::future::join_all(adds).await {
+            result.unwrap();
+        }
+        reactions.add(comment, "👍", "reader:other").await.unwrap();
+        reactions.add(comment, "🎉", visitor).await.unwrap();
+        reactions.add(other, "👍", "reader:other").await.unwrap();
+
+        let summary = reactions.summarize(&[comment, other], visitor).await.unwrap();
+        assert_eq!(counts(&summary, comment), [("👍".to_string(), 2, true), ("🎉".to_string(), 1, true)]);
+        assert_eq!(counts(&summary, other), [("👍".to_string(), 1, false)]);
+
+        // 取消回应同样可以重复执行
+        reactions.remove(comment, "👍", visitor).await.unwrap();
+        reactions.remove(comment, "👍", visitor).await.unwrap();
+        let summary = reactions.summarize(&[comment], visitor).await.unwrap();
+        assert_eq!(counts(&summary, comment), [("🎉".to_string(), 1, true), ("👍".to_string(), 1, false)]);
+
+        db.drop().await.unwrap();
+    }
+}
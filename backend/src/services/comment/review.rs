@@ -0,0 +1,338 @@
+//! 评论审核队列
+//!
+//! `comment_review_jobs` 集合即审核队列，每条评论最多一个任务（`_id` 为评论 ID）。
+//! 任务领取时把 `nextAttemptAt` 推后一个租期，进程在审核途中退出时，租期结束后会被重新领取；
+//! AI 调用失败按指数退避重试，超过次数上限后标记为失败，评论保持待审核，由博主处理。
+//! 启动时为仍处于待审核、但没有任务的评论补建任务。
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use mongodb::options::ReturnDocument;
+use mongodb::{Collection, Database, IndexModel};
+use serde::Deserialize;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::Instrument;
+
+use crate::models::{Comment, CommentState};
+use crate::services::mail::mailer::Mailer;
+use crate::services::options_service::OptionsService;
+use crate::services::spam_detector::SpamDetector;
+use crate::utils::request_id;
+
+/// 最大尝试次数
+pub const MAX_ATTEMPTS: i32 = 6;
+/// 领取任务后的租期
+const LEASE: Duration = Duration::from_secs(5 * 60);
+/// 单次审核的超时时间（需小于租期）
+const REVIEW_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+/// 没有新任务入队时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// 首次重试的等待时间，之后每次翻倍
+const BASE_BACKOFF: Duration = Duration::from_secs(60);
+/// 重试等待时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// 任务状态
+const STATUS_PENDING: &str = "pending";
+/// 重试次数用尽
+const STATUS_FAILED: &str = "failed";
+
+/// 新任务入队时唤醒审核任务
+static REVIEW_QUEUED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// 第 `attempts` 次尝试失败后的重试等待时间
+pub fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    BASE_BACKOFF.saturating_mul(1 << exponent).min(MAX_BACKOFF)
+}
+
+/// 审核任务（`comment_review_jobs` 集合）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewJob {
+    /// 评论 ID
+    #[serde(rename = "_id")]
+    comment: ObjectId,
+    attempts: i32,
+    /// 每次入队递增，用于识别审核期间被重新入队的任务
+    generation: i64,
+    /// 审核通过后是否发送评论通知（仅新评论）
+    #[serde(default)]
+    notify: bool,
+    /// 入队请求的 ID，审核日志沿用它以便追踪
+    #[serde(default)]
+    request_id: Option<String>,
+}
+
+/// 审核队列
+pub struct ReviewQueue {
+    jobs: Collection<ReviewJob>,
+    comments: Collection<Comment>,
+}
+
+impl ReviewQueue {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            jobs: db.collection::<ReviewJob>("comment_review_jobs"),
+            comments: db.collection::<Comment>("comments"),
+        }
+    }
+
+    pub async fn ensure_indexes(&self) -> Result<(), mongodb::error::Error> {
+        self.jobs
+            .create_index(IndexModel::builder().keys(doc! { "status": 1, "nextAttemptAt": 1 }).build())
+            .await?;
+        Ok(())
+    }
+
+    /// 为评论创建（或重置）审核任务
+    ///
+    /// `notify` 为 true 时审核通过后发送评论通知；为 false 时保留尚未完成的任务原有的通知设置。
+    /// 在请求中调用时记录当前请求 ID
+    pub async fn enqueue(&self, comment: ObjectId, notify: bool) -> Result<(), String> {
+        let now = DateTime::now();
+        let mut update = doc! {
+            "$set": {
+                "status": STATUS_PENDING,
+                "attempts": 0,
+                "nextAttemptAt": now,
+                "updatedAt": now,
+            },
+            "$inc": { "generation": 1_i64 },
+            "$unset": { "lastError": "" },
+            "$setOnInsert": { "created": now },
+        };
+        match request_id::current() {
+            Some(id) => update.get_document_mut("$set").unwrap().insert("requestId", id),
+            None => update.get_document_mut("$unset").unwrap().insert("requestId", ""),
+        };
+        if notify {
+            update.get_document_mut("$set").unwrap().insert("notify", true);
+        } else {
+            update.get_document_mut("$setOnInsert").unwrap().insert("notify", false);
+        }
+
+        self.jobs
+            .clone_with_type::<Document>()
+            .update_one(doc! { "_id": comment }, update)
+            .upsert(true)
+            .await
+            .map_err(|e| e.to_string())?;
+        REVIEW_QUEUED.notify_one();
+        Ok(())
+    }
+
+    /// 取消评论的审核任务（博主已改判，不再以 AI 结果覆盖）
+    pub async fn cancel(&self, comments: &[ObjectId]) -> Result<(), String> {
+        if comments.is_empty() {
+            return Ok(());
+        }
+        self.jobs
+            .delete_many(doc! { "_id": { "$in": comments } })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// 为待审核但没有任务的评论补建任务（不发送通知），返回补建数量
+    ///
+    /// 命中过滤规则而待审核的评论由博主处理，不在此列
+    pub async fn recover(&self) -> Result<u64, String> {
+        let ids: Vec<ObjectId> = self
+            .comments
+            .clone_with_type::<Document>()
+            .find(doc! {
+                "state": CommentState::PENDING,
+                "ruleHit": null,
+                "deletedAt": null,
+            })
+            .projection(doc! { "_id": 1 })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| e.to_string())?
+            .iter()
+            .filter_map(|doc| doc.get_object_id("_id").ok())
+            .collect();
+
+        let now = DateTime::now();
+        let mut recovered = 0;
+        for id in ids {
+            let result = self
+                .jobs
+                .clone_with_type::<Document>()
+                .update_one(
+                    doc! { "_id": id },
+                    doc! {
+                        "$setOnInsert": {
+                            "status": STATUS_PENDING,
+                            "attempts": 0,
+                            "generation": 1_i64,
+                            "notify": false,
+                            "nextAttemptAt": now,
+                            "created": now,
+                        },
+                    },
+                )
+                .upsert(true)
+                .await
+                .map_err(|e| e.to_string())?;
+            if result.upserted_id.is_some() {
+                recovered += 1;
+            }
+        }
+        if recovered > 0 {
+            REVIEW_QUEUED.notify_one();
+        }
+        Ok(recovered)
+    }
+
+    /// 领取一个到期的任务，并占用一个租期
+    async fn claim(&self) -> Result<Option<ReviewJob>, mongodb::error::Error> {
+        let now = DateTime::now();
+        let lease_until = DateTime::from_millis(now.timestamp_millis() + LEASE.as_millis() as i64);
+        self.jobs
+            .find_one_and_update(
+                doc! {
+                    "status": STATUS_PENDING,
+                    "nextAttemptAt": { "$lte": now },
+                },
+                doc! {
+                    "$set": { "nextAttemptAt": lease_until },
+                    "$inc": { "attempts": 1 },
+                },
+            )
+            .sort(doc! { "nextAttemptAt": 1 })
+            .return_document(ReturnDocument::After)
+            .await
+    }
+
+    /// 在带有入队请求 ID 的 span 中执行任务
+    async fn process(&self, db: &Database, options: &OptionsService, mailer: &Mailer, job: ReviewJob) {
+        let span = tracing::info_span!(
+            "review",
+            comment = %job.comment,
+            request_id = tracing::field::Empty,
+        );
+        if let Some(id) = &job.request_id {
+            span.record("request_id", id.as_str());
+        }
+        self.run(db, options, mailer, job).instrument(span).await
+    }
+
+    /// 执行任务并记录结果；审核期间被重新入队的任务以新一轮为准
+    async fn run(&self, db: &Database, options: &OptionsService, mailer: &Mailer, job: ReviewJob) {
+        let job_filter = doc! { "_id": job.comment, "generation": job.generation };
+        // 领取之后博主的改判优先于本次审核结果
+        let claimed_at = DateTime::now();
+
+        let comment = match self.comments.find_one(doc! { "_id": job.comment }).await {
+            Ok(comment) => comment,
+            Err(e) => {
+                // 数据库异常时保留任务，租期结束后重试
+                tracing::error!("读取评论 {} 失败: {}", job.comment, e);
+                return;
+            }
+        };
+
+        let result = match comment.filter(|comment| comment.deleted_at.is_none()) {
+            Some(comment) => {
+                let mailer = job.notify.then_some(mailer);
+                tokio::time::timeout(REVIEW_TIMEOUT, SpamDetector::review(db, options, &comment, claimed_at, mailer))
+                    .await
+                    .unwrap_or_else(|_| Err("审核超时".to_string()))
+            }
+            None => {
+                tracing::info!("评论 {} 已删除，取消审核任务", job.comment);
+                Ok(())
+            }
+        };
+
+        let outcome = match result {
+            Ok(()) => self.jobs.delete_one(job_filter).await.map(|_| ()),
+            Err(error) => {
+                let exhausted = job.attempts >= MAX_ATTEMPTS;
+                if exhausted {
+                    tracing::error!(
+                        "评论 {} 审核失败 {} 次，保持待审核: {}",
+                        job.comment,
+                        job.attempts,
+                        error
+                    );
+                } else {
+                    tracing::warn!(
+                        "评论 {} 审核失败 (第 {} 次尝试)，稍后重试: {}",
+                        job.comment,
+                        job.attempts,
+                        error
+                    );
+                }
+                let next_attempt_at = DateTime::from_millis(
+                    DateTime::now().timestamp_millis() + backoff(job.attempts).as_millis() as i64,
+                );
+                self.jobs
+                    .update_one(
+                        job_filter,
+                        doc! {
+                            "$set": {
+                                "status": if exhausted { STATUS_FAILED } else { STATUS_PENDING },
+                                "lastError": error,
+                                "nextAttemptAt": next_attempt_at,
+                                "updatedAt": DateTime::now(),
+                            },
+                        },
+                    )
+                    .await
+                    .map(|_| ())
+            }
+        };
+        if let Err(e) = outcome {
+            tracing::error!("更新评论 {} 的审核任务失败: {}", job.comment, e);
+        }
+    }
+}
+
+/// 持续处理到期的审核任务（在后台任务中运行，不会返回）
+pub async fn run_review_worker(db: Database, options: OptionsService, mailer: Mailer) {
+    let queue = ReviewQueue::new(&db);
+
+    match queue.recover().await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("已为 {} 条待审核评论补建审核任务", count),
+        Err(e) => tracing::error!("补建审核任务失败: {}", e),
+    }
+
+    loop {
+        loop {
+            match queue.claim().await {
+                Ok(Some(job)) => queue.process(&db, &options, &mailer, job).await,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("领取审核任务失败: {}", e);
+                    break;
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = REVIEW_QUEUED.notified() => {}
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        assert_eq!(backoff(1), Duration::from_secs(60));
+        assert_eq!(backoff(3), Duration::from_secs(240));
+        assert_eq!(backoff(MAX_ATTEMPTS), Duration::from_secs(60 * 32));
+        assert_eq!(backoff(20), MAX_BACKOFF);
+    }
+}
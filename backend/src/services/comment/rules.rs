@@ -0,0 +1,330 @@
+//! 评论规则过滤 - 在 AI 审核之前运行的确定性规则
+//!
+//! 规则读取自 `commentOptions`：
+//! - `disableNoChinese`: 内容必须包含中文
+//! - `spamKeywords`: 关键词黑名单（不区分大小写，匹配内容和昵称）
+//! - `blockIps`: 屏蔽的 IP 或 CIDR 网段
+//! - `rules`: 正则黑名单、屏蔽邮箱、链接上限、重复内容窗口，以及每条规则的处理方式
+//!
+//! 命中多条规则时取最严重的处理方式（拒绝 > 垃圾 > 待审核）。
+
+use mongodb::bson::{doc, DateTime};
+use mongodb::{Database, IndexModel};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer};
+use std::net::IpAddr;
+use std::sync::LazyLock;
+
+use crate::models::{Comment, RuleAction, RuleHit, RuleKind};
+use crate::services::spam_detector::CommentOptions;
+use crate::utils::ip_network::IpNetwork;
+
+/// 统计链接数量
+static LINK_PATTERN: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"(?i)https?://").unwrap());
+
+/// `commentOptions.rules` 配置（默认全部关闭，升级后行为不变）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommentRuleOptions {
+    /// 正则黑名单（不区分大小写，匹配内容和昵称）
+    pub regex_blocklist: RegexBlocklist,
+    /// 屏蔽的邮箱，`@example.com` 形式屏蔽整个域名
+    pub blocked_emails: Vec<String>,
+    /// 内容中允许的最大链接数，0 表示不限制
+    pub max_links: usize,
+    /// 同一 IP 重复发表相同内容的检测窗口（分钟），0 表示不检测
+    pub duplicate_window_minutes: i64,
+    /// 每条规则的处理方式
+    pub actions: RuleActions,
+}
+
+/// 正则黑名单，在加载配置快照时编译一次；无效的正则记录警告后忽略
+#[derive(Debug, Clone, Default)]
+pub struct RegexBlocklist(Vec<Regex>);
+
+impl RegexBlocklist {
+    fn compile(patterns: Vec<String>) -> Self {
+        let compiled = patterns
+            .iter()
+            .filter_map(|pattern| match RegexBuilder::new(pattern).case_insensitive(true).build() {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    tracing::warn!("忽略无效的评论过滤正则 {:?}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self(compiled)
+    }
+
+    /// 第一个匹配内容或昵称的正则
+    fn find(&self, text: &str, author: &str) -> Option<&Regex> {
+        self.0.iter().find(|re| re.is_match(text) || re.is_match(author))
+    }
+}
+
+impl<'de> Deserialize<'de> for RegexBlocklist {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<String>::deserialize(deserializer).map(Self::compile)
+    }
+}
+
+/// 每条规则命中后的处理方式
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleActions {
+    pub no_chinese: RuleAction,
+    pub keyword: RuleAction,
+    pub regex: RuleAction,
+    pub links: RuleAction,
+    pub duplicate: RuleAction,
+    pub blocked_email: RuleAction,
+    pub blocked_ip: RuleAction,
+}
+
+impl Default for RuleActions {
+    fn default() -> Self {
+        Self {
+            no_chinese: RuleAction::Reject,
+            keyword: RuleAction::Spam,
+            regex: RuleAction::Spam,
+            links: RuleAction::Pending,
+            duplicate: RuleAction::Reject,
+            blocked_email: RuleAction::Reject,
+            blocked_ip: RuleAction::Reject,
+        }
+    }
+}
+
+impl RuleActions {
+    fn get(&self, rule: RuleKind) -> RuleAction {
+        match rule {
+            RuleKind::NoChinese => self.no_chinese,
+            RuleKind::Keyword => self.keyword,
+            RuleKind::Regex => self.regex,
+            RuleKind::Links => self.links,
+            RuleKind::Duplicate => self.duplicate,
+            RuleKind::BlockedEmail => self.blocked_email,
+            RuleKind::BlockedIp => self.blocked_ip,
+        }
+    }
+}
+
+/// 待检查的评论内容
+pub struct RuleInput<'a> {
+    pub text: &'a str,
+    pub author: &'a str,
+    pub mail: &'a str,
+    pub ip: &'a str,
+}
+
+/// 评论规则过滤
+pub struct CommentRules;
+
+impl CommentRules {
+    /// 重复内容检测按 IP 和时间查询（不索引可能很长的评论内容）
+    pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error> {
+        db.collection::<Comment>("comments")
+            .create_index(IndexModel::builder().keys(doc! { "ip": 1, "created": -1 }).build())
+            .await?;
+        Ok(())
+    }
+
+    /// 运行全部规则（包括需要查询数据库的重复内容检测），返回最严重的命中
+    pub async fn check(
+        db: &Database,
+        options: &CommentOptions,
+        input: &RuleInput<'_>,
+    ) -> Result<Option<RuleHit>, String> {
+        let hit = Self::check_content(options, input);
+        if hit.as_ref().is_some_and(|hit| hit.action == RuleAction::Reject) {
+            return Ok(hit);
+        }
+
+        let duplicate = Self::check_duplicate(db, options, input).await?;
+        Ok(Self::most_severe(hit, duplicate))
+    }
+
+    /// 运行不依赖数据库的规则，返回最严重的命中
+    pub fn check_content(options: &CommentOptions, input: &RuleInput<'_>) -> Option<RuleHit> {
+        let rules = &options.rules;
+        let hit = |rule: RuleKind, detail: String| RuleHit {
+            rule,
+            action: rules.actions.get(rule),
+            detail,
+        };
+        let mut hits = Vec::new();
+
+        if let Some(pattern) = options.block_ips.iter().find(|p| ip_matches(p, input.ip)) {
+            hits.push(hit(RuleKind::BlockedIp, pattern.clone()));
+        }
+        if let Some(pattern) = rules.blocked_emails.iter().find(|p| email_matches(p, input.mail)) {
+            hits.push(hit(RuleKind::BlockedEmail, pattern.clone()));
+        }
+        if options.disable_no_chinese && !contains_chinese(input.text) {
+            hits.push(hit(RuleKind::NoChinese, "内容不含中文".to_string()));
+        }
+
+        let text = input.text.to_lowercase();
+        let author = input.author.to_lowercase();
+        if let Some(keyword) = options.spam_keywords.iter().find(|k| {
+            let k = k.trim().to_lowercase();
+            !k.is_empty() && (text.contains(&k) || author.contains(&k))
+        }) {
+            hits.push(hit(RuleKind::Keyword, keyword.clone()));
+        }
+        if let Some(re) = rules.regex_blocklist.find(input.text, input.author) {
+            hits.push(hit(RuleKind::Regex, re.as_str().to_string()));
+        }
+
+        let links = LINK_PATTERN.find_iter(input.text).count();
+        if rules.max_links > 0 && links > rules.max_links {
+            hits.push(hit(RuleKind::Links, format!("{} 个链接（上限 {}）", links, rules.max_links)));
+        }
+
+        hits.into_iter().reduce(|a, b| if b.action > a.action { b } else { a })
+    }
+
+    /// 同一 IP 在检测窗口内发表过相同内容
+    async fn check_duplicate(
+        db: &Database,
+        options: &CommentOptions,
+        input: &RuleInput<'_>,
+    ) -> Result<Option<RuleHit>, String> {
+        let window = options.rules.duplicate_window_minutes;
+        if window <= 0 || input.ip.is_empty() {
+            return Ok(None);
+        }
+
+        let since = DateTime::from_millis(DateTime::now().timestamp_millis() - window * 60 * 1000);
+        let duplicates = db
+            .collection::<Comment>("comments")
+            .count_documents(doc! {
+                "ip": input.ip,
+                "text": input.text,
+                "created": { "$gte": since },
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((duplicates > 0).then(|| RuleHit {
+            rule: RuleKind::Duplicate,
+            action: options.rules.actions.duplicate,
+            detail: format!("{} 分钟内重复", window),
+        }))
+    }
+
+    fn most_severe(a: Option<RuleHit>, b: Option<RuleHit>) -> Option<RuleHit> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(if b.action > a.action { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// 拒绝发表时返回给用户的提示
+    pub fn reject_message(rule: RuleKind) -> &'static str {
+        match rule {
+            RuleKind::NoChinese => "评论需要包含中文",
+            RuleKind::Keyword | RuleKind::Regex => "评论包含不允许的内容",
+            RuleKind::Links => "评论中的链接过多",
+            RuleKind::Duplicate => "请勿重复发表相同的评论",
+            RuleKind::BlockedEmail | RuleKind::BlockedIp => "你已被禁止发表评论",
+        }
+    }
+}
+
+/// 是否包含汉字
+fn contains_chinese(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c,
+            '\u{4E00}'..='\u{9FFF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}')
+    })
+}
+
+/// 邮箱匹配：完整邮箱或 `@域名`（不区分大小写）
+fn email_matches(pattern: &str, mail: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let mail = mail.trim().to_lowercase();
+    if pattern.is_empty() || mail.is_empty() {
+        return false;
+    }
+    if pattern.starts_with('@') {
+        mail.ends_with(&pattern)
+    } else {
+        mail == pattern
+    }
+}
+
+/// IP 匹配：单个地址或 CIDR 网段（如 `10.0.0.0/8`、`2001:db8::/32`）
+fn ip_matches(pattern: &str, ip: &str) -> bool {
+    match (IpNetwork::parse(pattern), ip.trim().parse::<IpAddr>()) {
+        (Ok(network), Ok(ip)) => network.contains(ip),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(value: mongodb::bson::Document) -> CommentOptions {
+        mongodb::bson::from_document(value).unwrap()
+    }
+
+    fn input(text: &str) -> RuleInput<'_> {
+        RuleInput {
+            text,
+            author: "访客",
+            mail: "guest@example.com",
+            ip: "203.0.113.7",
+        }
+    }
+
+    #[test]
+    fn ip_ranges_and_emails() {
+        assert!(ip_matches("203.0.113.7", "203.0.113.7"));
+        assert!(ip_matches("203.0.113.0/24", "203.0.113.200"));
+        assert!(!ip_matches("203.0.113.0/24", "203.0.114.1"));
+        assert!(ip_matches("0.0.0.0/0", "8.8.8.8"));
+        assert!(ip_matches("2001:db8::/32", "2001:db8:1::1"));
+        assert!(!ip_matches("2001:db8::/32", "203.0.113.7"));
+        assert!(!ip_matches("203.0.113.0/33", "203.0.113.7"));
+
+        assert!(email_matches("@Spam.example", "bot@spam.example"));
+        assert!(email_matches("bot@example.com", "BOT@example.com"));
+        assert!(!email_matches("bot@example.com", "other@example.com"));
+    }
+
+    #[test]
+    fn most_severe_hit_wins() {
+        let opts = options(doc! {
+            "disableNoChinese": true,
+            "spamKeywords": ["casino"],
+            "rules": { "maxLinks": 1, "actions": { "keyword": "pending" } },
+        });
+
+        assert_eq!(CommentRules::check_content(&opts, &input("写得很好")), None);
+
+        let hit = CommentRules::check_content(&opts, &input("好文 https://a.example https://b.example")).unwrap();
+        assert_eq!((hit.rule, hit.action), (RuleKind::Links, RuleAction::Pending));
+
+        let hit = CommentRules::check_content(&opts, &input("CASINO bonus")).unwrap();
+        assert_eq!((hit.rule, hit.action), (RuleKind::NoChinese, RuleAction::Reject));
+
+        let opts = options(doc! {
+            "blockIps": ["203.0.113.0/24"],
+            "rules": { "actions": { "blockedIp": "spam" }, "regexBlocklist": ["("] },
+        });
+        let hit = CommentRules::check_content(&opts, &input("你好")).unwrap();
+        assert_eq!((hit.rule, hit.action), (RuleKind::BlockedIp, RuleAction::Spam));
+
+        let opts = options(doc! { "rules": { "regexBlocklist": ["(", "b[o0]nus"] } });
+        assert_eq!(opts.rules.regex_blocklist.0.len(), 1);
+        let hit = CommentRules::check_content(&opts, &input("领取 B0NUS")).unwrap();
+        assert_eq!((hit.rule, hit.detail.as_str()), (RuleKind::Regex, "b[o0]nus"));
+    }
+}
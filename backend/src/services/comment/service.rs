@@ -1,26 +1,55 @@
 //! 评论服务 - 封装评论相关的业务逻辑
 
+use futures::stream::TryStreamExt;
 use md5;
-use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document, Regex};
 use mongodb::{Collection, Database};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::guards::OptionalAuthGuard;
-use crate::models::{Comment, CommentTree};
-use crate::services::ReaderRepository;
+use crate::models::{AdminComment, AdminModeration, Comment, CommentTree, RefType};
+use crate::services::comment::counters::CommentCounters;
+use crate::services::spam_detector::DEFAULT_EDIT_WINDOW_MINUTES;
+use crate::services::{OptionsService, ReaderRepository, ReactionService};
+
+/// 展开回复时的最大层数，防御 parent 成环的数据
+const MAX_THREAD_DEPTH: usize = 64;
+
+/// 博主在审核队列中删除的评论可恢复的天数
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// 评论目标校验失败的原因
+#[derive(Debug)]
+pub enum CommentTargetError {
+    /// 站点已关闭评论（`commentOptions.disableComment`）
+    CommentsDisabled,
+    /// 文章 / 手记 / 页面不存在或未发布
+    RefNotFound,
+    /// 目标不允许评论（`allowComment` 为 false）
+    CommentsClosed,
+    /// 回复的评论不存在
+    ParentNotFound,
+    /// 回复的评论不属于同一目标
+    ParentMismatch,
+    Database(String),
+}
 
 /// 评论服务
 pub struct CommentService {
+    db: Database,
     collection: Collection<Comment>,
     reader_repo: ReaderRepository,
+    reactions: ReactionService,
 }
 
 impl CommentService {
     /// 创建新的评论服务实例
     pub fn new(db: &Database) -> Self {
         Self {
+            db: db.clone(),
             collection: db.collection::<Comment>("comments"),
             reader_repo: ReaderRepository::new(db),
+            reactions: ReactionService::new(db),
         }
     }
 
@@ -38,7 +67,7 @@ impl CommentService {
         ref_type: &str,
         auth: &OptionalAuthGuard,
     ) -> Result<mongodb::bson::Document, String> {
-        log::debug!(
+        tracing::debug!(
             "构建评论过滤器: user_id={:?}, is_owner={}",
             auth.user_id,
             auth.is_owner
@@ -47,7 +76,7 @@ impl CommentService {
         // 根据用户身份构建过滤器
         if auth.is_owner {
             // 管理员：可以看到所有评论（包括待审核、垃圾评论等）
-            log::debug!("管理员模式：显示所有评论");
+            tracing::debug!("管理员模式：显示所有评论");
             return Ok(doc! {
                 "ref": ref_oid,
                 "refType": ref_type,
@@ -63,7 +92,7 @@ impl CommentService {
                 .map_err(|e| e.to_string())?;
 
             if let Some(reader) = user_reader {
-                log::debug!(
+                tracing::debug!(
                     "普通用户模式：显示正常评论 + 用户 {} 的所有评论（包括待审核）",
                     reader.email
                 );
@@ -96,7 +125,7 @@ impl CommentService {
                 return Ok(filter);
             } else {
                 // 用户不存在，只显示正常状态的公开评论
-                log::debug!("用户不存在，只显示正常状态的公开评论");
+                tracing::debug!("用户不存在，只显示正常状态的公开评论");
                 return Ok(doc! {
                     "ref": ref_oid,
                     "refType": ref_type,
@@ -107,7 +136,7 @@ impl CommentService {
         }
 
         // 匿名用户：只能看到正常状态的公开评论
-        log::debug!("匿名用户模式：只显示正常状态的公开评论");
+        tracing::debug!("匿名用户模式：只显示正常状态的公开评论");
         Ok(doc! {
             "ref": ref_oid,
             "refType": ref_type,
@@ -116,151 +145,435 @@ impl CommentService {
         })
     }
 
+    /// 查找当前用户可见的评论（可见性规则与评论列表相同），返回评论及其所在文章的可见性过滤器
+    pub async fn find_visible(
+        &self,
+        id: ObjectId,
+        auth: &OptionalAuthGuard,
+    ) -> Result<Option<(Comment, Document)>, String> {
+        let Some(comment) = self
+            .collection
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+
+        let filter = self
+            .build_visibility_filter(comment.r#ref, &comment.ref_type, auth)
+            .await?;
+        let mut own_filter = filter.clone();
+        own_filter.insert("_id", id);
+        let visible = self
+            .collection
+            .count_documents(own_filter)
+            .await
+            .map_err(|e| e.to_string())?
+            > 0;
+
+        Ok(visible.then_some((comment, filter)))
+    }
+
     /// 批量查询 Reader 信息，构建邮箱到头像和站长身份的映射
     pub async fn build_reader_mappings(
         &self,
         emails: Vec<String>,
     ) -> Result<(HashMap<String, String>, HashMap<String, bool>), String> {
+        let readers = self
+            .reader_repo
+            .find_by_emails(&emails)
+            .await
+            .map_err(|e| e.to_string())?;
+
         let mut email_to_avatar = HashMap::new();
         let mut email_to_is_owner = HashMap::new();
+        for reader in readers {
+            // 同一邮箱存在多个 Reader 时取最新创建的一个
+            if email_to_is_owner.contains_key(&reader.email) {
+                continue;
+            }
+            if !reader.image.is_empty() {
+                email_to_avatar.insert(reader.email.clone(), reader.image);
+            }
+            email_to_is_owner.insert(reader.email, reader.is_owner);
+        }
+
+        Ok((email_to_avatar, email_to_is_owner))
+    }
+
+    /// 广度优先收集若干根评论下的全部可见回复，按所属根评论分组，组内按时间升序
+    ///
+    /// 只沿可见的父评论向下展开（与此前整棵树的构建方式一致），
+    /// 并通过已访问集合和层数上限防御 parent 成环的脏数据。
+    pub async fn find_thread_replies(
+        &self,
+        visibility_filter: &Document,
+        root_ids: &[ObjectId],
+    ) -> Result<HashMap<ObjectId, Vec<Comment>>, String> {
+        let mut root_of: HashMap<ObjectId, ObjectId> =
+            root_ids.iter().map(|id| (*id, *id)).collect();
+        let mut grouped: HashMap<ObjectId, Vec<Comment>> = HashMap::new();
+        let mut frontier = root_ids.to_vec();
+
+        for _ in 0..MAX_THREAD_DEPTH {
+            if frontier.is_empty() {
+                break;
+            }
+
+            let mut filter = visibility_filter.clone();
+            filter.insert("parent", doc! { "$in": frontier });
+            let mut cursor = self.collection.find(filter).await.map_err(|e| e.to_string())?;
 
-        for email in emails {
-            // 通过邮箱查找 Reader（假设邮箱是唯一的）
-            if let Ok(readers) = self.reader_repo.get_all().await {
-                for reader in readers {
-                    if reader.email == email {
-                        if !reader.image.is_empty() {
-                            email_to_avatar.insert(email.clone(), reader.image.clone());
-                        }
-                        email_to_is_owner.insert(email.clone(), reader.is_owner);
-                        break;
-                    }
+            let mut next = Vec::new();
+            while let Some(comment) = cursor.try_next().await.map_err(|e| e.to_string())? {
+                let (Some(id), Some(parent)) = (comment.id, comment.parent) else {
+                    continue;
+                };
+                if root_of.contains_key(&id) {
+                    continue;
                 }
+                let Some(root) = root_of.get(&parent).copied() else {
+                    continue;
+                };
+                root_of.insert(id, root);
+                next.push(id);
+                grouped.entry(root).or_default().push(comment);
             }
+            frontier = next;
         }
 
-        Ok((email_to_avatar, email_to_is_owner))
+        for replies in grouped.values_mut() {
+            replies.sort_by_key(|c| (c.created, c.id));
+        }
+        Ok(grouped)
+    }
+
+    /// 生成回复分页游标：`<创建时间毫秒>-<评论 ID>`
+    pub fn encode_reply_cursor(comment: &Comment) -> Option<String> {
+        comment
+            .id
+            .map(|id| format!("{}-{}", comment.created.timestamp_millis(), id.to_hex()))
     }
 
-    /// 构建评论树形结构
-    pub fn build_comment_tree(
+    /// 解析回复分页游标，格式错误时返回 None
+    pub fn decode_reply_cursor(cursor: &str) -> Option<(DateTime, ObjectId)> {
+        let (millis, id) = cursor.rsplit_once('-')?;
+        let millis = millis.parse::<i64>().ok()?;
+        let id = ObjectId::parse_str(id).ok()?;
+        Some((DateTime::from_millis(millis), id))
+    }
+
+    /// 若干评论下可见回复（含多层嵌套）的查询条件，按评论 ID 分组
+    ///
+    /// 回复的 `key` 以上级评论的 `key` 加 `#` 开头（如 `#3` 下的 `#3#1`、`#3#1#2`）。
+    /// 不可见的回复（垃圾、待审核、他人的私密评论）连同其下的回复一并排除，
+    /// 与沿可见父评论逐层展开的结果一致。
+    pub async fn thread_filters(
+        &self,
+        visibility_filter: &Document,
+        parents: &[Comment],
+    ) -> Result<HashMap<ObjectId, Document>, String> {
+        let prefixes: Vec<(ObjectId, String)> = parents
+            .iter()
+            .filter_map(|parent| Some((parent.id?, format!("{}#", parent.key))))
+            .collect();
+        let Some(first) = parents.first() else {
+            return Ok(HashMap::new());
+        };
+
+        // 讨论串中不可见的回复（只取 key）
+        let patterns: Vec<Regex> = prefixes.iter().map(|(_, prefix)| key_prefix_regex(prefix)).collect();
+        let hidden: Vec<String> = self
+            .collection
+            .clone_with_type::<Document>()
+            .find(doc! {
+                "ref": first.r#ref,
+                "refType": &first.ref_type,
+                "key": { "$in": patterns },
+                "$nor": [visibility_filter.clone()],
+            })
+            .projection(doc! { "key": 1 })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| e.to_string())?
+            .iter()
+            .filter_map(|doc| doc.get_str("key").ok().map(str::to_string))
+            .collect();
+
+        Ok(prefixes
+            .into_iter()
+            .map(|(id, prefix)| {
+                let mut filter = visibility_filter.clone();
+                filter.insert("key", key_prefix_regex(&prefix));
+                let excluded: Vec<Document> = hidden
+                    .iter()
+                    .filter(|key| key.starts_with(&prefix))
+                    .map(|key| doc! { "key": key_prefix_regex(&format!("{}#", key)) })
+                    .collect();
+                if !excluded.is_empty() {
+                    filter.insert("$nor", excluded);
+                }
+                (id, filter)
+            })
+            .collect())
+    }
+
+    /// 按时间顺序取游标之后的一页回复，返回（回复总数, 本页回复, 下一页游标）
+    pub async fn find_replies_page(
+        &self,
+        thread_filter: &Document,
+        after: Option<(DateTime, ObjectId)>,
+        size: usize,
+    ) -> Result<(usize, Vec<Comment>, Option<String>), String> {
+        let page_filter = match after {
+            Some((created, id)) => doc! {
+                "$and": [
+                    thread_filter.clone(),
+                    { "$or": [
+                        { "created": { "$gt": created } },
+                        { "created": created, "_id": { "$gt": id } },
+                    ] },
+                ],
+            },
+            None => thread_filter.clone(),
+        };
+
+        let count = self.collection.count_documents(thread_filter.clone());
+        let page = async {
+            self.collection
+                .find(page_filter)
+                .sort(doc! { "created": 1, "_id": 1 })
+                .limit(size as i64 + 1)
+                .await?
+                .try_collect::<Vec<Comment>>()
+                .await
+        };
+        let (count, replies) = tokio::try_join!(count, page).map_err(|e| e.to_string())?;
+
+        let (replies, next_cursor) = Self::split_page(replies, size);
+        Ok((count as usize, replies, next_cursor))
+    }
+
+    /// 查询时多取一条用于判断是否还有下一页：超出 `size` 时截断并返回下一页游标
+    pub fn split_page(mut replies: Vec<Comment>, size: usize) -> (Vec<Comment>, Option<String>) {
+        if replies.len() <= size {
+            return (replies, None);
+        }
+        replies.truncate(size);
+        let next_cursor = replies.last().and_then(Self::encode_reply_cursor);
+        (replies, next_cursor)
+    }
+
+    /// 将评论转换为树节点（不含子评论）；已删除的评论转换为只保留结构信息的墓碑
+    pub fn to_tree_node(
+        comment: &Comment,
+        email_to_avatar: &HashMap<String, String>,
+        email_to_is_owner: &HashMap<String, bool>,
+    ) -> CommentTree {
+        let mut node = CommentTree {
+            id: comment.id.map(|id| id.to_hex()).unwrap_or_default(),
+            r#ref: comment.r#ref.to_hex(),
+            ref_type: comment.ref_type.clone(),
+            author: String::new(),
+            text: String::new(),
+            state: comment.state,
+            children: vec![],
+            comments_index: comment.comments_index,
+            key: comment.key.clone(),
+            pin: comment.pin,
+            is_whispers: comment.is_whispers,
+            is_admin: None,
+            source: None,
+            avatar: None,
+            created: comment.created.to_chrono().to_rfc3339(),
+            location: None,
+            url: None,
+            parent: comment.parent.as_ref().map(|p| p.to_hex()),
+            ua: None,
+            edited_at: None,
+            is_deleted: None,
+            reactions: vec![],
+            reply_count: None,
+            replies_cursor: None,
+        };
+
+        if comment.deleted_at.is_some() {
+            node.is_deleted = Some(true);
+            return node;
+        }
+
+        // 优先使用 Reader 的最新头像，否则使用评论保存的头像，最后根据邮箱生成
+        let avatar_url = email_to_avatar
+            .get(&comment.mail)
+            .cloned()
+            .or_else(|| comment.avatar.clone())
+            .unwrap_or_else(|| Self::generate_avatar_url(&comment.mail));
+
+        // 判断是否为站长
+        let is_admin = email_to_is_owner
+            .get(&comment.mail)
+            .copied()
+            .filter(|&is_owner| is_owner);
+
+        node.author = comment.author.clone();
+        node.text = comment.text.clone();
+        node.is_admin = is_admin;
+        node.source = comment.source.clone();
+        node.avatar = Some(avatar_url);
+        node.location = comment.location.clone();
+        node.url = comment.url.clone();
+        node.ua = comment.ua.clone();
+        node.edited_at = comment.edited_at.map(|t| t.to_chrono().to_rfc3339());
+        node
+    }
+
+    /// 返回给评论者的单条评论（与评论列表相同的公开字段）
+    ///
+    /// 不含邮箱、IP 以及规则命中、审核结果等审核信息，这些只在博主的审核队列中返回
+    pub async fn to_public_comment(&self, comment: &Comment) -> Result<CommentTree, String> {
+        let (email_to_avatar, email_to_is_owner) = self
+            .build_reader_mappings(vec![comment.mail.clone()])
+            .await?;
+        Ok(Self::to_tree_node(comment, &email_to_avatar, &email_to_is_owner))
+    }
+
+    /// 将一组评论组装为森林：父评论不在本组中的评论作为顶层节点，同层按创建时间升序
+    pub fn build_comment_forest(
         comments: &[Comment],
         email_to_avatar: &HashMap<String, String>,
         email_to_is_owner: &HashMap<String, bool>,
     ) -> Vec<CommentTree> {
-        // 创建 ID 到评论的映射
-        let mut comment_map: HashMap<String, CommentTree> = HashMap::new();
-        let mut root_ids: Vec<String> = Vec::new();
-
-        // 第一遍：创建所有评论节点，并记录根评论 ID
+        let mut sorted: Vec<&Comment> = Vec::with_capacity(comments.len());
         for comment in comments {
-            let id_str = match comment.id.as_ref() {
-                Some(id) => id.to_hex(),
-                None => {
-                    log::error!("comment missing id: {:?}", comment);
-                    continue;
-                } // 跳过没有 ID 的评论
-            };
+            if comment.id.is_none() {
+                tracing::error!("comment missing id: {:?}", comment);
+                continue;
+            }
+            sorted.push(comment);
+        }
+        sorted.sort_by_key(|c| (c.created, c.id));
 
-            // 优先使用 Reader 的最新头像，否则使用评论保存的头像，最后根据邮箱生成
-            let avatar_url = email_to_avatar
-                .get(&comment.mail)
-                .cloned()
-                .or_else(|| comment.avatar.clone())
-                .unwrap_or_else(|| Self::generate_avatar_url(&comment.mail));
-
-            // 判断是否为站长
-            let is_admin = email_to_is_owner
-                .get(&comment.mail)
-                .copied()
-                .filter(|&is_owner| is_owner);
-
-            let tree_node = CommentTree {
-                id: id_str.clone(),
-                r#ref: comment.r#ref.to_hex(),
-                ref_type: comment.ref_type.clone(),
-                author: comment.author.clone(),
-                text: comment.text.clone(),
-                state: comment.state,
-                children: vec![],
-                comments_index: comment.comments_index,
-                key: comment.key.clone(),
-                pin: comment.pin,
-                is_whispers: comment.is_whispers,
-                is_admin,
-                source: comment.source.clone(),
-                avatar: Some(avatar_url),
-                created: comment.created.to_chrono().to_rfc3339(),
-                location: comment.location.clone(),
-                url: comment.url.clone(),
-                parent: comment.parent.as_ref().map(|p| p.to_hex()),
-                ua: comment.ua.clone(),
-            };
+        let ids: HashSet<ObjectId> = sorted.iter().filter_map(|c| c.id).collect();
+        let mut children_of: HashMap<ObjectId, Vec<&Comment>> = HashMap::new();
+        let mut top_level = Vec::new();
+        for comment in sorted {
+            match comment.parent.filter(|p| ids.contains(p)) {
+                Some(parent) => children_of.entry(parent).or_default().push(comment),
+                None => top_level.push(comment),
+            }
+        }
 
-            // 记录根评论 ID
-            if comment.parent.is_none() {
-                root_ids.push(id_str.clone());
+        // 从顶层节点出发逐层挂载子评论；成环的评论不可能从顶层到达，因此会被丢弃
+        fn attach(
+            comment: &Comment,
+            children_of: &HashMap<ObjectId, Vec<&Comment>>,
+            email_to_avatar: &HashMap<String, String>,
+            email_to_is_owner: &HashMap<String, bool>,
+        ) -> CommentTree {
+            let mut node = CommentService::to_tree_node(comment, email_to_avatar, email_to_is_owner);
+            if let Some(children) = comment.id.and_then(|id| children_of.get(&id)) {
+                node.children = children
+                    .iter()
+                    .map(|child| attach(child, children_of, email_to_avatar, email_to_is_owner))
+                    .collect();
             }
+            node
+        }
+
+        top_level
+            .into_iter()
+            .map(|comment| attach(comment, &children_of, email_to_avatar, email_to_is_owner))
+            .collect()
+    }
 
-            comment_map.insert(id_str, tree_node);
+    /// 判断用户是否为评论作者：评论记录的 Reader 一致，或 Reader 邮箱与评论邮箱一致（兼容旧评论）
+    pub async fn is_author(&self, comment: &Comment, user_id: ObjectId) -> Result<bool, String> {
+        if comment.reader_id == Some(user_id) {
+            return Ok(true);
         }
 
-        // 递归构建子树
-        fn build_children(
-            parent_id: &str,
-            comment_map: &HashMap<String, CommentTree>,
-            comments: &[Comment],
-        ) -> Vec<CommentTree> {
-            let mut children = Vec::new();
-
-            for comment in comments {
-                if let Some(parent_oid) = &comment.parent {
-                    if parent_oid.to_hex() == parent_id {
-                        let child_id = match comment.id.as_ref() {
-                            Some(id) => id.to_hex(),
-                            None => {
-                                log::error!("comment missing id: {:?}", comment);
-                                continue;
-                            }
-                        };
-
-                        if let Some(mut child_node) = comment_map.get(&child_id).cloned() {
-                            // 递归构建子评论的子评论
-                            child_node.children = build_children(&child_id, comment_map, comments);
-                            children.push(child_node);
-                        }
-                    }
-                }
-            }
+        let reader = self
+            .reader_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(reader.is_some_and(|r| !r.email.is_empty() && r.email.eq_ignore_ascii_case(&comment.mail)))
+    }
 
-            // 按创建时间排序
-            children.sort_by(|a, b| a.created.cmp(&b.created));
-            children
+    /// 作者可编辑评论的时间窗口（毫秒），未配置评论选项时使用默认值
+    pub fn edit_window_millis(options: &OptionsService) -> i64 {
+        let minutes = options
+            .comment_options()
+            .map(|opts| opts.edit_window_minutes)
+            .unwrap_or(DEFAULT_EDIT_WINDOW_MINUTES);
+        minutes.max(0) * 60_000
+    }
+
+    /// 校验评论目标：目标存在且已发布、允许评论，站点未关闭评论，
+    /// 回复的评论未删除、对当前用户可见且属于同一目标
+    ///
+    /// 博主不受发布状态和评论开关的限制
+    pub async fn validate_target(
+        &self,
+        options: &OptionsService,
+        ref_oid: ObjectId,
+        ref_type: RefType,
+        parent_oid: Option<ObjectId>,
+        auth: &OptionalAuthGuard,
+    ) -> Result<(), CommentTargetError> {
+        let is_owner = auth.is_owner;
+        let db_error = |e: mongodb::error::Error| CommentTargetError::Database(e.to_string());
+
+        if !is_owner && options.site_config().comment.disable_comment {
+            return Err(CommentTargetError::CommentsDisabled);
         }
 
-        // 构建根评论及其子树
-        let mut root_comments = Vec::new();
-        for root_id in root_ids {
-            if let Some(mut root_node) = comment_map.get(&root_id).cloned() {
-                root_node.children = build_children(&root_id, &comment_map, comments);
-                root_comments.push(root_node);
+        let target = self
+            .db
+            .collection::<Document>(ref_type.as_str())
+            .find_one(doc! { "_id": ref_oid })
+            .projection(doc! { "allowComment": 1, "isPublished": 1, "publicAt": 1 })
+            .await
+            .map_err(db_error)?
+            .ok_or(CommentTargetError::RefNotFound)?;
+        if !is_owner {
+            if !is_published(&target) {
+                return Err(CommentTargetError::RefNotFound);
+            }
+            // 缺少 allowComment 字段的旧数据视为允许评论
+            if !target.get_bool("allowComment").unwrap_or(true) {
+                return Err(CommentTargetError::CommentsClosed);
             }
         }
 
-        // 按创建时间排序
-        root_comments.sort_by(|a, b| a.created.cmp(&b.created));
+        // 墓碑和不可见的评论（垃圾、待审核、悄悄话）视为不存在，不能通过猜测 ID 回复
+        if let Some(parent_oid) = parent_oid {
+            let (parent, _) = self
+                .find_visible(parent_oid, auth)
+                .await
+                .map_err(CommentTargetError::Database)?
+                .filter(|(parent, _)| parent.deleted_at.is_none())
+                .ok_or(CommentTargetError::ParentNotFound)?;
+            if parent.r#ref != ref_oid || parent.ref_type != ref_type.as_str() {
+                return Err(CommentTargetError::ParentMismatch);
+            }
+        }
 
-        root_comments
+        Ok(())
     }
 
-    /// 生成评论的 key（层级标识）
+    /// 生成评论的 key（层级标识），序号由计数器原子分配
     pub async fn generate_comment_key(
         &self,
         ref_oid: ObjectId,
         ref_type: &str,
         parent_oid: Option<ObjectId>,
     ) -> Result<String, String> {
-        let key = if let Some(parent_id) = parent_oid {
+        let counters = CommentCounters::new(&self.db);
+        if let Some(parent_id) = parent_oid {
             // 回复评论：获取父评论的 key，然后追加子评论序号
             let parent_comment = self
                 .collection
@@ -269,53 +582,371 @@ impl CommentService {
                 .map_err(|e| e.to_string())?;
 
             if let Some(parent) = parent_comment {
-                // 统计该父评论下已有的直接子评论数量
-                let sibling_count = self
-                    .collection
-                    .count_documents(doc! { "parent": parent_id })
-                    .await
-                    .map_err(|e| e.to_string())?;
                 // 父评论 key 如 "#1" 或 "#1#2"，子评论 key 为 "#1#1" 或 "#1#2#1"
-                format!("{}#{}", parent.key, sibling_count + 1)
-            } else {
-                // 父评论不存在，降级为根评论处理
-                let root_count = self
-                    .collection
-                    .count_documents(doc! { "ref": ref_oid, "refType": ref_type, "parent": null })
-                    .await
-                    .map_err(|e| e.to_string())?;
-                format!("#{}", root_count + 1)
+                let seq = counters.next_child(parent_id).await?;
+                return Ok(format!("{}#{}", parent.key, seq));
             }
-        } else {
-            // 根评论：统计该文章下的根评论数量
-            let root_count = self
-                .collection
-                .count_documents(doc! { "ref": ref_oid, "refType": ref_type, "parent": null })
-                .await
-                .map_err(|e| e.to_string())?;
-            format!("#{}", root_count + 1)
-        };
+            // 父评论不存在，降级为根评论处理
+        }
 
-        Ok(key)
+        let seq = counters.next_root(ref_oid, ref_type).await?;
+        Ok(format!("#{}", seq))
     }
 
-    /// 获取评论索引（所有评论的总数）
+    /// 分配评论索引（文章下的评论序号）
     pub async fn get_comment_index(
         &self,
         ref_oid: ObjectId,
         ref_type: &str,
     ) -> Result<i32, String> {
-        let count = self
+        CommentCounters::new(&self.db).next_index(ref_oid, ref_type).await
+    }
+
+    /// 更新父评论的 children 字段
+    pub async fn update_parent_children(
+        &self,
+        parent_id: ObjectId,
+        child_id: ObjectId,
+    ) -> Result<(), String> {
+        self.collection
+            .update_one(
+                doc! { "_id": parent_id },
+                doc! { "$push": { "children": child_id } },
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// 软删除评论：保留为墓碑节点，清空作者、内容和其他个人信息
+    ///
+    /// 返回 `false` 表示评论不存在或已被删除
+    pub async fn soft_delete(&self, id: ObjectId) -> Result<bool, String> {
+        let result = self
             .collection
-            .count_documents(doc! { "ref": ref_oid, "refType": ref_type })
+            .update_one(
+                doc! { "_id": id, "deletedAt": null },
+                doc! {
+                    "$set": {
+                        "author": "",
+                        "mail": "",
+                        "text": "",
+                        "deletedAt": DateTime::now(),
+                    },
+                    "$unset": {
+                        "avatar": "",
+                        "url": "",
+                        "ip": "",
+                        "agent": "",
+                        "location": "",
+                        "ua": "",
+                        "source": "",
+                        "readerId": "",
+                        "editedAt": "",
+                    },
+                },
+            )
             .await
             .map_err(|e| e.to_string())?;
 
-        Ok((count + 1) as i32)
+        Ok(result.matched_count > 0)
     }
 
-    /// 更新父评论的 children 字段
-    pub async fn update_parent_children(
+    /// 彻底删除评论及其全部回复（不区分状态），并从父评论的 children 中移除
+    ///
+    /// 返回删除的评论数量，评论不存在时返回 None
+    pub async fn hard_delete_cascade(&self, id: ObjectId) -> Result<Option<u64>, String> {
+        let Some(comment) = self
+            .collection
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+
+        let mut ids = vec![id];
+        if let Some(replies) = self.find_thread_replies(&doc! {}, &[id]).await?.remove(&id) {
+            ids.extend(replies.into_iter().filter_map(|c| c.id));
+        }
+
+        let result = self
+            .collection
+            .delete_many(doc! { "_id": { "$in": &ids } })
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.reactions.remove_for_comments(&ids).await?;
+        if let Some(parent_id) = comment.parent {
+            self.remove_parent_child(parent_id, id).await?;
+        }
+
+        Ok(Some(result.deleted_count))
+    }
+
+    /// 清理没有回复的墓碑评论，逐层向上直到不再有可清理的墓碑
+    ///
+    /// 返回清理的评论数量
+    pub async fn purge_tombstones(&self) -> Result<u64, String> {
+        let mut purged = 0;
+
+        for _ in 0..MAX_THREAD_DEPTH {
+            let tombstones: Vec<ObjectId> = self
+                .collection
+                .distinct(
+                    "_id",
+                    doc! {
+                        "deletedAt": { "$ne": null },
+                        // 审核队列中删除的评论在可恢复期内保留
+                        "$or": [
+                            { "purgeAfter": null },
+                            { "purgeAfter": { "$lte": DateTime::now() } },
+                        ],
+                    },
+                )
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter_map(|id| id.as_object_id())
+                .collect();
+            if tombstones.is_empty() {
+                break;
+            }
+
+            let with_replies: HashSet<ObjectId> = self
+                .collection
+                .distinct("parent", doc! { "parent": { "$in": &tombstones } })
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter_map(|id| id.as_object_id())
+                .collect();
+            let leaves: Vec<ObjectId> = tombstones
+                .into_iter()
+                .filter(|id| !with_replies.contains(id))
+                .collect();
+            if leaves.is_empty() {
+                break;
+            }
+
+            let result = self
+                .collection
+                .delete_many(doc! { "_id": { "$in": &leaves } })
+                .await
+                .map_err(|e| e.to_string())?;
+            self.collection
+                .update_many(
+                    doc! { "children": { "$in": &leaves } },
+                    doc! { "$pull": { "children": { "$in": &leaves } } },
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            self.reactions.remove_for_comments(&leaves).await?;
+            purged += result.deleted_count;
+        }
+
+        Ok(purged)
+    }
+
+    /// 构建审核队列过滤器
+    ///
+    /// - `state`: 按评论状态筛选
+    /// - `ref_type`: 按评论目标类型筛选（posts / notes / pages）
+    /// - `q`: 在内容、作者和邮箱中搜索（不区分大小写）
+    /// - `deleted`: 为 true 时只看已删除的评论，否则排除已删除的评论
+    pub fn moderation_filter(
+        state: Option<i32>,
+        ref_type: Option<RefType>,
+        q: Option<&str>,
+        deleted: bool,
+    ) -> Document {
+        let mut filter = if deleted {
+            doc! { "deletedAt": { "$ne": null } }
+        } else {
+            doc! { "deletedAt": null }
+        };
+        if let Some(state) = state {
+            filter.insert("state", state);
+        }
+        if let Some(ref_type) = ref_type {
+            filter.insert("refType", ref_type.as_str());
+        }
+        if let Some(q) = q.map(str::trim).filter(|q| !q.is_empty()) {
+            let pattern = regex::escape(q);
+            filter.insert(
+                "$or",
+                vec![
+                    doc! { "text": { "$regex": &pattern, "$options": "i" } },
+                    doc! { "author": { "$regex": &pattern, "$options": "i" } },
+                    doc! { "mail": { "$regex": &pattern, "$options": "i" } },
+                ],
+            );
+        }
+        filter
+    }
+
+    /// 批量查询评论所属文章 / 页面 / 手记的标题
+    pub async fn find_ref_titles(
+        &self,
+        comments: &[Comment],
+    ) -> Result<HashMap<ObjectId, String>, String> {
+        let mut titles = HashMap::new();
+        for ref_type in RefType::ALL {
+            let refs: HashSet<ObjectId> = comments
+                .iter()
+                .filter(|c| c.ref_type == ref_type.as_str())
+                .map(|c| c.r#ref)
+                .collect();
+            if refs.is_empty() {
+                continue;
+            }
+
+            let refs: Vec<ObjectId> = refs.into_iter().collect();
+            let mut cursor = self
+                .db
+                .collection::<Document>(ref_type.as_str())
+                .find(doc! { "_id": { "$in": refs } })
+                .projection(doc! { "title": 1 })
+                .await
+                .map_err(|e| e.to_string())?;
+            while let Some(doc) = cursor.try_next().await.map_err(|e| e.to_string())? {
+                if let (Ok(id), Ok(title)) = (doc.get_object_id("_id"), doc.get_str("title")) {
+                    titles.insert(id, title.to_string());
+                }
+            }
+        }
+        Ok(titles)
+    }
+
+    /// 转换为审核队列中的评论
+    pub fn to_admin_comment(comment: Comment, titles: &HashMap<ObjectId, String>) -> AdminComment {
+        let rfc3339 = |dt: DateTime| dt.to_chrono().to_rfc3339();
+        AdminComment {
+            id: comment.id.map(|id| id.to_hex()).unwrap_or_default(),
+            r#ref: comment.r#ref.to_hex(),
+            ref_title: titles.get(&comment.r#ref).cloned(),
+            ref_type: comment.ref_type,
+            author: comment.author,
+            mail: comment.mail,
+            text: comment.text,
+            state: comment.state,
+            is_whispers: comment.is_whispers,
+            pin: comment.pin,
+            parent: comment.parent.map(|id| id.to_hex()),
+            ip: comment.ip,
+            location: comment.location,
+            avatar: comment.avatar,
+            url: comment.url,
+            created: rfc3339(comment.created),
+            edited_at: comment.edited_at.map(rfc3339),
+            deleted_at: comment.deleted_at.map(rfc3339),
+            purge_after: comment.purge_after.map(rfc3339),
+            rule_hit: comment.rule_hit,
+            moderation: comment.moderation.map(AdminModeration::from),
+        }
+    }
+
+    /// 批量修改评论状态（只修改当前状态在 `from` 中且未删除的评论），返回被修改的评论 ID
+    pub async fn set_state(
+        &self,
+        ids: &[ObjectId],
+        from: &[i32],
+        to: i32,
+    ) -> Result<Vec<ObjectId>, String> {
+        let filter = doc! {
+            "_id": { "$in": ids },
+            "state": { "$in": from },
+            "deletedAt": null,
+        };
+        let matched: Vec<ObjectId> = self
+            .collection
+            .distinct("_id", filter.clone())
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter_map(|id| id.as_object_id())
+            .collect();
+        if matched.is_empty() {
+            return Ok(matched);
+        }
+
+        let mut filter = filter;
+        filter.insert("_id", doc! { "$in": &matched });
+        self.collection
+            .update_many(filter, doc! { "$set": { "state": to } })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(matched)
+    }
+
+    /// 博主删除评论：与软删除一样显示为墓碑，但保留原内容，
+    /// [`TRASH_RETENTION_DAYS`] 天内可以恢复，之后由墓碑清理任务删除
+    pub async fn trash(&self, ids: &[ObjectId]) -> Result<u64, String> {
+        let now = DateTime::now();
+        let purge_after = DateTime::from_millis(
+            now.timestamp_millis() + TRASH_RETENTION_DAYS * 24 * 60 * 60 * 1000,
+        );
+        let result = self
+            .collection
+            .update_many(
+                doc! { "_id": { "$in": ids }, "deletedAt": null },
+                doc! { "$set": { "deletedAt": now, "purgeAfter": purge_after } },
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.modified_count)
+    }
+
+    /// 恢复博主删除的评论（作者自己删除的评论内容已清空，无法恢复）
+    pub async fn restore(&self, ids: &[ObjectId]) -> Result<u64, String> {
+        let result = self
+            .collection
+            .update_many(
+                doc! {
+                    "_id": { "$in": ids },
+                    "deletedAt": { "$ne": null },
+                    "purgeAfter": { "$ne": null },
+                },
+                doc! { "$unset": { "deletedAt": "", "purgeAfter": "" } },
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.modified_count)
+    }
+
+    /// 在审核记录上记下博主的改判（没有审核记录的评论不记录）
+    pub async fn record_override(&self, ids: &[ObjectId], state: i32) -> Result<(), String> {
+        self.collection
+            .update_many(
+                doc! { "_id": { "$in": ids }, "moderation": { "$ne": null } },
+                doc! {
+                    "$set": {
+                        "moderation.overrideState": state,
+                        "moderation.overriddenAt": DateTime::now(),
+                    },
+                },
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// 可以重新审核的评论（存在且未删除）
+    pub async fn reviewable(&self, ids: &[ObjectId]) -> Result<Vec<ObjectId>, String> {
+        let comments: Vec<Comment> = self
+            .collection
+            .find(doc! { "_id": { "$in": ids }, "deletedAt": null })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(comments.into_iter().filter_map(|comment| comment.id).collect())
+    }
+
+    /// 从父评论的 children 字段中移除子评论
+    pub async fn remove_parent_child(
         &self,
         parent_id: ObjectId,
         child_id: ObjectId,
@@ -323,7 +954,7 @@ impl CommentService {
         self.collection
             .update_one(
                 doc! { "_id": parent_id },
-                doc! { "$push": { "children": child_id } },
+                doc! { "$pull": { "children": child_id } },
             )
             .await
             .map_err(|e| e.to_string())?;
@@ -331,3 +962,281 @@ impl CommentService {
         Ok(())
     }
 }
+
+/// 匹配以 `prefix` 开头的 `key`（前缀锚定，可使用 `{ ref, refType, key }` 索引）
+fn key_prefix_regex(prefix: &str) -> Regex {
+    Regex {
+        pattern: format!("^{}", regex::escape(prefix)),
+        options: String::new(),
+    }
+}
+
+/// 目标是否已发布：`isPublished` 缺省视为已发布（页面没有该字段），手记的 `publicAt` 未到时视为未发布
+fn is_published(target: &Document) -> bool {
+    let published = target.get_bool("isPublished").unwrap_or(true);
+    let public_at_passed = target
+        .get_datetime("publicAt")
+        .map_or(true, |public_at| *public_at <= DateTime::now());
+    published && public_at_passed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CommentState;
+
+    #[test]
+    fn unpublished_and_scheduled_targets_are_hidden() {
+        let hour = 60 * 60 * 1000;
+        let now = DateTime::now().timestamp_millis();
+        assert!(is_published(&doc! {}));
+        assert!(is_published(&doc! { "isPublished": true }));
+        assert!(!is_published(&doc! { "isPublished": false }));
+        assert!(is_published(&doc! { "publicAt": DateTime::from_millis(now - hour) }));
+        assert!(!is_published(&doc! { "isPublished": true, "publicAt": DateTime::from_millis(now + hour) }));
+    }
+
+    async fn insert_parent(service: &CommentService, post: ObjectId, state: i32, whisper: bool, deleted: bool) -> ObjectId {
+        let comment = Comment {
+            r#ref: post,
+            ref_type: "posts".to_string(),
+            state,
+            is_whispers: whisper,
+            deleted_at: deleted.then(DateTime::now),
+            ..Default::default()
+        };
+        service.collection.insert_one(comment).await.unwrap().inserted_id.as_object_id().unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "需要本地 MongoDB（MONGODB_URI）"]
+    async fn replies_need_a_live_visible_parent() {
+        let db = crate::services::db_service::test_database().await;
+        let options = OptionsService::init(&db).await.unwrap();
+        let service = CommentService::new(&db);
+        let post = db
+            .collection::<Document>("posts")
+            .insert_one(doc! { "title": "测试" })
+            .await
+            .unwrap()
+            .inserted_id
+            .as_object_id()
+            .unwrap();
+        let guest = OptionalAuthGuard { user_id: None, is_owner: false };
+        let owner = OptionalAuthGuard { user_id: Some(ObjectId::new()), is_owner: true };
+        let reply_to = |parent: ObjectId, auth: &OptionalAuthGuard| {
+            let (service, options, auth) = (&service, &options, auth.clone());
+            async move { service.validate_target(options, post, RefType::Posts, Some(parent), &auth).await }
+        };
+
+        let public = insert_parent(&service, post, CommentState::READ, false, false).await;
+        assert!(reply_to(public, &guest).await.is_ok());
+
+        let spam = insert_parent(&service, post, CommentState::SPAM, false, false).await;
+        let pending = insert_parent(&service, post, CommentState::PENDING, false, false).await;
+        let whisper = insert_parent(&service, post, CommentState::READ, true, false).await;
+        let tombstone = insert_parent(&service, post, CommentState::READ, false, true).await;
+        for parent in [spam, pending, whisper, tombstone] {
+            assert!(matches!(reply_to(parent, &guest).await, Err(CommentTargetError::ParentNotFound)));
+        }
+
+        // 博主可以回复隐藏的评论，但不能回复墓碑
+        assert!(reply_to(spam, &owner).await.is_ok());
+        assert!(matches!(reply_to(tombstone, &owner).await, Err(CommentTargetError::ParentNotFound)));
+
+        db.drop().await.unwrap();
+    }
+
+    #[test]
+    fn moderation_filter_combines_conditions() {
+        let filter = CommentService::moderation_filter(
+            CommentState::parse("SPAM"),
+            Some(RefType::Posts),
+            Some(" a.b "),
+            false,
+        );
+        assert_eq!(filter.get("deletedAt"), Some(&mongodb::bson::Bson::Null));
+        assert_eq!(filter.get_i32("state"), Ok(CommentState::SPAM));
+        assert_eq!(filter.get_str("refType"), Ok("posts"));
+        let or = filter.get_array("$or").unwrap();
+        assert_eq!(or.len(), 3);
+        assert_eq!(
+            or[0].as_document().unwrap().get_document("text").unwrap().get_str("$regex"),
+            Ok(r"a\.b")
+        );
+
+        let trash = CommentService::moderation_filter(None, None, None, true);
+        assert_eq!(trash, doc! { "deletedAt": { "$ne": null } });
+        assert_eq!(CommentState::parse("archived"), None);
+    }
+
+    /// 旧版 `build_comment_tree` 的实现（每个节点都重新扫描全部评论），用作对照
+    fn legacy_tree(
+        comments: &[Comment],
+        email_to_avatar: &HashMap<String, String>,
+        email_to_is_owner: &HashMap<String, bool>,
+    ) -> Vec<CommentTree> {
+        fn build_children(
+            parent_id: &str,
+            comments: &[Comment],
+            email_to_avatar: &HashMap<String, String>,
+            email_to_is_owner: &HashMap<String, bool>,
+        ) -> Vec<CommentTree> {
+            let mut children: Vec<CommentTree> = comments
+                .iter()
+                .filter(|c| c.parent.is_some_and(|p| p.to_hex() == parent_id))
+                .map(|c| {
+                    let mut node = CommentService::to_tree_node(c, email_to_avatar, email_to_is_owner);
+                    node.children = build_children(&node.id, comments, email_to_avatar, email_to_is_owner);
+                    node
+                })
+                .collect();
+            children.sort_by(|a, b| a.created.cmp(&b.created));
+            children
+        }
+
+        let mut roots: Vec<CommentTree> = comments
+            .iter()
+            .filter(|c| c.parent.is_none())
+            .map(|c| {
+                let mut node = CommentService::to_tree_node(c, email_to_avatar, email_to_is_owner);
+                node.children = build_children(&node.id, comments, email_to_avatar, email_to_is_owner);
+                node
+            })
+            .collect();
+        roots.sort_by(|a, b| a.created.cmp(&b.created));
+        roots
+    }
+
+    /// 生成 n 条评论：约四分之一为根评论，其余随机回复更早的评论，输入顺序打乱
+    fn generate_thread(n: usize) -> (Vec<Comment>, HashMap<String, String>, HashMap<String, bool>) {
+        let mut seed: u64 = 0x5eed;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+
+        let mut comments: Vec<Comment> = Vec::with_capacity(n);
+        for i in 0..n {
+            let parent = if i == 0 || next() % 4 == 0 {
+                None
+            } else {
+                comments[next() % i].id
+            };
+            let mut c = comment(1_700_000_000_000 + i as i64 * 1_000, parent);
+            c.mail = format!("reader{}@example.com", next() % 50);
+            comments.push(c);
+        }
+        for i in (1..n).rev() {
+            comments.swap(i, next() % (i + 1));
+        }
+
+        let avatars = (0..10)
+            .map(|i| (format!("reader{}@example.com", i), format!("https://example.com/{}.png", i)))
+            .collect();
+        let owners = HashMap::from([("reader0@example.com".to_string(), true)]);
+        (comments, avatars, owners)
+    }
+
+    #[test]
+    fn forest_matches_legacy_tree() {
+        for n in [0, 1, 10, 500] {
+            let (comments, avatars, owners) = generate_thread(n);
+            let forest = CommentService::build_comment_forest(&comments, &avatars, &owners);
+            let legacy = legacy_tree(&comments, &avatars, &owners);
+            assert_eq!(
+                serde_json::to_value(&forest).unwrap(),
+                serde_json::to_value(&legacy).unwrap(),
+                "tree mismatch for {} comments",
+                n
+            );
+        }
+    }
+
+    /// 10k 条评论的建树时间上限（调试构建约 0.1s，旧的逐层扫描实现约 40s）
+    #[test]
+    fn forest_builds_10k_comments_within_bound() {
+        fn count(nodes: &[CommentTree]) -> usize {
+            nodes.iter().map(|node| 1 + count(&node.children)).sum()
+        }
+
+        let (comments, avatars, owners) = generate_thread(10_000);
+        let start = std::time::Instant::now();
+        let forest = CommentService::build_comment_forest(&comments, &avatars, &owners);
+        let elapsed = start.elapsed();
+
+        assert_eq!(count(&forest), 10_000);
+        assert!(elapsed < std::time::Duration::from_secs(2), "building 10k comments took {:?}", elapsed);
+    }
+
+    fn comment(created: i64, parent: Option<ObjectId>) -> Comment {
+        Comment {
+            id: Some(ObjectId::new()),
+            created: DateTime::from_millis(created),
+            parent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn forest_nests_replies_and_lifts_orphans() {
+        let root = ObjectId::new();
+        let a = comment(1_000, Some(root));
+        let b = comment(3_000, a.id);
+        let c = comment(2_000, Some(root));
+        let (avatars, owners) = (HashMap::new(), HashMap::new());
+
+        let forest = CommentService::build_comment_forest(
+            &[b.clone(), c.clone(), a.clone()],
+            &avatars,
+            &owners,
+        );
+        let ids: Vec<_> = forest.iter().map(|n| n.id.clone()).collect();
+        assert_eq!(ids, vec![a.id.unwrap().to_hex(), c.id.unwrap().to_hex()]);
+        assert_eq!(forest[0].children[0].id, b.id.unwrap().to_hex());
+
+        // 父评论不在本批中时作为顶层节点返回
+        let forest = CommentService::build_comment_forest(std::slice::from_ref(&b), &avatars, &owners);
+        assert_eq!(forest.len(), 1);
+        assert_eq!(forest[0].parent, a.id.map(|id| id.to_hex()));
+    }
+
+    #[test]
+    fn tombstone_keeps_replies_attached() {
+        let mut root = comment(1_000, None);
+        root.deleted_at = Some(DateTime::from_millis(5_000));
+        root.author = "ghost".to_string();
+        root.text = "left over".to_string();
+        let reply = comment(2_000, root.id);
+
+        let forest = CommentService::build_comment_forest(
+            &[root.clone(), reply.clone()],
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(forest.len(), 1);
+        assert_eq!(forest[0].is_deleted, Some(true));
+        assert!(forest[0].author.is_empty() && forest[0].text.is_empty() && forest[0].avatar.is_none());
+        assert_eq!(forest[0].children[0].id, reply.id.unwrap().to_hex());
+        assert_eq!(forest[0].children[0].is_deleted, None);
+    }
+
+    #[test]
+    fn reply_pages_follow_cursor() {
+        let root = ObjectId::new();
+        let replies: Vec<Comment> = (0..3).map(|i| comment(1_000 * i, Some(root))).collect();
+
+        let (page, cursor) = CommentService::split_page(replies.clone(), 2);
+        assert_eq!(page.len(), 2);
+        let cursor = cursor.expect("more replies remain");
+        let after = CommentService::decode_reply_cursor(&cursor);
+        assert_eq!(after, Some((page[1].created, page[1].id.unwrap())));
+
+        let (page, cursor) = CommentService::split_page(replies, 3);
+        assert_eq!(page.len(), 3);
+        assert!(cursor.is_none());
+
+        assert!(CommentService::decode_reply_cursor("not-a-cursor").is_none());
+        assert_eq!(key_prefix_regex("#3#").pattern, r"^\#3\#");
+    }
+}
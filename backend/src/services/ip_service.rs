@@ -70,7 +70,7 @@ impl IpService {
                 }
             }
             Err(e) => {
-                log::warn!("Failed to search IP {}: {}", ip, e);
+                tracing::warn!("Failed to search IP {}: {}", ip, e);
                 None
             }
         }
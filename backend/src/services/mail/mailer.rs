@@ -0,0 +1,195 @@
+//! SMTP 发信服务 - 每封邮件都带有签名的一键退订链接
+
+use hmac::{Hmac, Mac};
+use lettre::message::header::{ContentType, HeaderName, HeaderValue};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{ConfigError, MailConfig, SmtpTls};
+use crate::models::MailKind;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 发信服务（未配置 SMTP 时为禁用状态，发送请求会被忽略）
+#[derive(Clone)]
+pub struct Mailer {
+    inner: Option<Arc<MailerInner>>,
+    /// 退订链接签名密钥（`mail.unsubscribe_secret`）；停用 SMTP 后已发出的退订链接仍然有效
+    unsubscribe_secret: Option<Arc<str>>,
+}
+
+struct MailerInner {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    owner_email: Option<String>,
+    /// 退订链接指向的后端地址
+    backend_url: String,
+    /// 文章链接使用的前端地址
+    frontend_url: String,
+}
+
+impl Mailer {
+    /// 根据配置创建发信服务
+    ///
+    /// # 参数
+    /// - `backend_url` / `frontend_url`: 生成退订链接和文章链接
+    pub fn from_config(config: &MailConfig, backend_url: &str, frontend_url: &str) -> Result<Self, ConfigError> {
+        let unsubscribe_secret = config.unsubscribe_secret.as_deref().map(Arc::from);
+        let Some(host) = config.smtp_host.as_deref().filter(|_| config.enabled()) else {
+            return Ok(Self { inner: None, unsubscribe_secret });
+        };
+        if unsubscribe_secret.is_none() {
+            return Err(ConfigError::MissingSetting("mail.unsubscribe_secret"));
+        }
+
+        let builder = match config.smtp_tls {
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| ConfigError::invalid("mail.smtp_host", e))?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| ConfigError::invalid("mail.smtp_host", e))?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        };
+        let mut builder = builder.port(config.port()).timeout(Some(Duration::from_secs(15)));
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let from = config
+            .from
+            .as_deref()
+            .unwrap_or_default()
+            .parse::<Mailbox>()
+            .map_err(|e| ConfigError::invalid("mail.from", e))?;
+
+        Ok(Self {
+            inner: Some(Arc::new(MailerInner {
+                transport: builder.build(),
+                from,
+                owner_email: config.owner_email.clone().filter(|email| !email.is_empty()),
+                backend_url: backend_url.trim_end_matches('/').to_string(),
+                frontend_url: frontend_url.trim_end_matches('/').to_string(),
+            })),
+            unsubscribe_secret,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// 配置中指定的站长邮箱
+    pub fn owner_email(&self) -> Option<&str> {
+        self.inner.as_ref()?.owner_email.as_deref()
+    }
+
+    /// 前端地址（用于生成文章链接）
+    pub fn frontend_url(&self) -> &str {
+        self.inner.as_ref().map_or("", |inner| inner.frontend_url.as_str())
+    }
+
+    /// 一键退订链接
+    pub fn unsubscribe_url(&self, email: &str, kind: MailKind) -> Option<String> {
+        let inner = self.inner.as_ref()?;
+        Some(format!(
+            "{}/api/mail/unsubscribe?email={}&kind={}&token={}",
+            inner.backend_url,
+            urlencoding::encode(&email.to_lowercase()),
+            kind.as_str(),
+            sign_unsubscribe(self.unsubscribe_secret.as_deref()?, email, kind),
+        ))
+    }
+
+    /// 校验退订链接的签名（未配置签名密钥时一律无效）
+    pub fn verify_unsubscribe(&self, email: &str, kind: MailKind, token: &str) -> bool {
+        self.unsubscribe_secret
+            .as_deref()
+            .is_some_and(|key| verify_unsubscribe(key, email, kind, token))
+    }
+
+    /// 发送纯文本邮件，正文末尾附带退订链接，并设置 `List-Unsubscribe` 头（RFC 8058）
+    pub async fn send(&self, to: &str, kind: MailKind, subject: &str, body: &str) -> Result<(), String> {
+        let Some(inner) = self.inner.as_ref() else {
+            return Ok(());
+        };
+        let unsubscribe_url = self.unsubscribe_url(to, kind).unwrap_or_default();
+
+        let message = Message::builder()
+            .from(inner.from.clone())
+            .to(to.parse::<Mailbox>().map_err(|e| format!("收件人地址无效: {}", e))?)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .raw_header(HeaderValue::new(
+                HeaderName::new_from_ascii_str("List-Unsubscribe"),
+                format!("<{}>", unsubscribe_url),
+            ))
+            .raw_header(HeaderValue::new(
+                HeaderName::new_from_ascii_str("List-Unsubscribe-Post"),
+                "List-Unsubscribe=One-Click".to_string(),
+            ))
+            .body(format!(
+                "{}\n\n--\n不想再收到此类邮件？点击退订：{}\n",
+                body, unsubscribe_url
+            ))
+            .map_err(|e| e.to_string())?;
+
+        inner.transport.send(message).await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// 退订签名：HMAC-SHA256(key, "unsubscribe:<kind>:<小写邮箱>")
+pub fn sign_unsubscribe(key: &str, email: &str, kind: MailKind) -> String {
+    hex::encode(unsubscribe_mac(key, email, kind).finalize().into_bytes())
+}
+
+/// 校验退订签名（常数时间比较）
+pub fn verify_unsubscribe(key: &str, email: &str, kind: MailKind, token: &str) -> bool {
+    let Ok(token) = hex::decode(token) else {
+        return false;
+    };
+    unsubscribe_mac(key, email, kind).verify_slice(&token).is_ok()
+}
+
+fn unsubscribe_mac(key: &str, email: &str, kind: MailKind) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("unsubscribe:{}:{}", kind.as_str(), email.to_lowercase()).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsubscribe_token_is_bound_to_email_and_kind() {
+        let token = sign_unsubscribe("key", "Reader@Example.com", MailKind::Replies);
+        assert!(verify_unsubscribe("key", "reader@example.com", MailKind::Replies, &token));
+        assert!(!verify_unsubscribe("key", "other@example.com", MailKind::Replies, &token));
+        assert!(!verify_unsubscribe("key", "reader@example.com", MailKind::NewComments, &token));
+        assert!(!verify_unsubscribe("other-key", "reader@example.com", MailKind::Replies, &token));
+        assert!(!verify_unsubscribe("key", "reader@example.com", MailKind::Replies, "zz"));
+    }
+
+    /// 需要本地 SMTP 捕获服务，例如 `docker run -p 1025:1025 -p 8025:8025 axllent/mailpit`
+    #[tokio::test]
+    #[ignore = "需要本地 SMTP 捕获服务（localhost:1025）"]
+    async fn test_send_to_local_smtp_catcher() {
+        let config = MailConfig {
+            smtp_host: Some("localhost".to_string()),
+            smtp_port: Some(1025),
+            smtp_tls: SmtpTls::None,
+            from: Some("Blog <noreply@example.com>".to_string()),
+            unsubscribe_secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+            ..Default::default()
+        };
+        let mailer = Mailer::from_config(&config, "http://localhost:8000", "http://localhost:3000").unwrap();
+        mailer
+            .send("reader@example.com", MailKind::Replies, "测试邮件", "你好")
+            .await
+            .unwrap();
+    }
+}
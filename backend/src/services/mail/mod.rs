@@ -0,0 +1,5 @@
+//! 邮件通知：SMTP 发信、订阅偏好与评论通知
+
+pub mod mailer;
+pub mod preferences;
+pub mod notifier;
@@ -0,0 +1,163 @@
+//! 评论邮件通知
+//!
+//! 评论公开后（无需审核时立即，否则在 AI 审核通过后）调用 [`CommentNotifier::comment_published`]：
+//! - 通知站长有新评论（站长自己的评论除外）
+//! - 通知被回复的评论作者（悄悄话、垃圾评论和已删除的评论不通知，悄悄话回复也不通知）
+
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Database;
+
+use super::mailer::Mailer;
+use super::preferences::MailPreferenceRepository;
+use crate::models::{Comment, CommentState, MailKind, RefType};
+use crate::services::ReaderRepository;
+
+/// 评论所属文章的标题和前端路径
+struct Article {
+    title: String,
+    path: String,
+}
+
+/// 评论邮件通知
+pub struct CommentNotifier;
+
+impl CommentNotifier {
+    /// 评论公开后发送通知；失败只记录日志，不影响评论本身
+    pub async fn comment_published(db: &Database, mailer: &Mailer, comment_id: ObjectId) {
+        if !mailer.is_enabled() {
+            return;
+        }
+        if let Err(e) = Self::notify(db, mailer, comment_id).await {
+            tracing::error!("发送评论 {} 的通知邮件失败: {}", comment_id, e);
+        }
+    }
+
+    async fn notify(db: &Database, mailer: &Mailer, comment_id: ObjectId) -> Result<(), String> {
+        let comments = db.collection::<Comment>("comments");
+        let Some(comment) = comments
+            .find_one(doc! { "_id": comment_id })
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(());
+        };
+        if !is_public(&comment) {
+            tracing::debug!("评论 {} 未公开，不发送通知", comment_id);
+            return Ok(());
+        }
+
+        let preferences = MailPreferenceRepository::new(db);
+        let article = Self::find_article(db, &comment).await;
+        let title = article.as_ref().map_or("文章", |a| a.title.as_str());
+        let link = article
+            .as_ref()
+            .map(|a| format!("{}{}#comment-{}", mailer.frontend_url(), a.path, comment_id.to_hex()))
+            .unwrap_or_else(|| mailer.frontend_url().to_string());
+
+        // 站长：新评论通知
+        let owner_email = match mailer.owner_email() {
+            Some(email) => Some(email.to_string()),
+            None => ReaderRepository::new(db)
+                .find_owner()
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|reader| reader.email)
+                .filter(|email| !email.is_empty()),
+        };
+        if let Some(owner_email) = owner_email.as_deref() {
+            if !owner_email.eq_ignore_ascii_case(&comment.mail)
+                && Self::allows(&preferences, owner_email, MailKind::NewComments).await?
+            {
+                let body = format!(
+                    "{} 在《{}》发表了评论：\n\n{}\n\n查看：{}",
+                    comment.author, title, comment.text, link
+                );
+                mailer
+                    .send(owner_email, MailKind::NewComments, &format!("《{}》有新评论", title), &body)
+                    .await?;
+                tracing::info!("已通知站长评论 {}", comment_id);
+            }
+        }
+
+        // 被回复者：回复通知
+        let Some(parent_id) = comment.parent else {
+            return Ok(());
+        };
+        if comment.is_whispers {
+            return Ok(());
+        }
+        let Some(parent) = comments
+            .find_one(doc! { "_id": parent_id })
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(());
+        };
+        if !is_public(&parent) || parent.is_whispers || parent.mail.is_empty() {
+            return Ok(());
+        }
+        // 回复自己，或站长已收到新评论通知
+        if parent.mail.eq_ignore_ascii_case(&comment.mail)
+            || owner_email.is_some_and(|owner| owner.eq_ignore_ascii_case(&parent.mail))
+        {
+            return Ok(());
+        }
+        if !Self::allows(&preferences, &parent.mail, MailKind::Replies).await? {
+            return Ok(());
+        }
+
+        let body = format!(
+            "{}，你好：\n\n{} 回复了你在《{}》的评论：\n\n> {}\n\n{}\n\n查看：{}",
+            parent.author, comment.author, title, parent.text, comment.text, link
+        );
+        mailer
+            .send(&parent.mail, MailKind::Replies, &format!("你在《{}》的评论有了新回复", title), &body)
+            .await?;
+        tracing::info!("已通知评论 {} 的作者有新回复 {}", parent_id, comment_id);
+        Ok(())
+    }
+
+    async fn allows(
+        preferences: &MailPreferenceRepository,
+        email: &str,
+        kind: MailKind,
+    ) -> Result<bool, String> {
+        Ok(preferences
+            .get(email)
+            .await
+            .map_err(|e| e.to_string())?
+            .allows(kind))
+    }
+
+    /// 查找评论所属文章的标题和前端路径（posts: /posts/<分类>/<slug>，notes: /notes/<nid>，pages: /<slug>）
+    async fn find_article(db: &Database, comment: &Comment) -> Option<Article> {
+        let ref_type = RefType::parse(&comment.ref_type)?;
+        let doc = db
+            .collection::<Document>(ref_type.as_str())
+            .find_one(doc! { "_id": comment.r#ref })
+            .await
+            .ok()??;
+        let title = doc.get_str("title").unwrap_or_default().to_string();
+
+        let path = match ref_type {
+            RefType::Posts => {
+                let category = db
+                    .collection::<Document>("categories")
+                    .find_one(doc! { "_id": doc.get_object_id("categoryId").ok()? })
+                    .await
+                    .ok()??;
+                format!("/posts/{}/{}", category.get_str("slug").ok()?, doc.get_str("slug").ok()?)
+            }
+            RefType::Notes => format!("/notes/{}", doc.get_i32("nid").ok()?),
+            RefType::Pages => format!("/{}", doc.get_str("slug").ok()?),
+        };
+
+        Some(Article { title, path })
+    }
+}
+
+/// 评论已公开：正常状态且未删除
+fn is_public(comment: &Comment) -> bool {
+    comment.deleted_at.is_none()
+        && (comment.state == CommentState::UNREAD || comment.state == CommentState::READ)
+}
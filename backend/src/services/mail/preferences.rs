@@ -0,0 +1,70 @@
+//! 邮件订阅偏好仓库（`mail_preferences` 集合，按小写邮箱保存）
+
+use mongodb::bson::{doc, DateTime, Document};
+use mongodb::{Collection, Database};
+
+use crate::models::{MailKind, MailPreferences};
+
+/// 邮件订阅偏好仓库
+pub struct MailPreferenceRepository {
+    collection: Collection<Document>,
+}
+
+impl MailPreferenceRepository {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            collection: db.collection::<Document>("mail_preferences"),
+        }
+    }
+
+    /// 获取邮箱的订阅偏好，未保存时返回默认值（全部开启）
+    pub async fn get(&self, email: &str) -> Result<MailPreferences, mongodb::error::Error> {
+        let Some(doc) = self
+            .collection
+            .find_one(doc! { "email": email.to_lowercase() })
+            .await?
+        else {
+            return Ok(MailPreferences::default());
+        };
+
+        let defaults = MailPreferences::default();
+        Ok(MailPreferences {
+            replies: doc.get_bool("replies").unwrap_or(defaults.replies),
+            new_comments: doc.get_bool("newComments").unwrap_or(defaults.new_comments),
+        })
+    }
+
+    /// 保存邮箱的订阅偏好
+    pub async fn set(&self, email: &str, preferences: MailPreferences) -> Result<(), mongodb::error::Error> {
+        self.collection
+            .update_one(
+                doc! { "email": email.to_lowercase() },
+                doc! {
+                    "$set": {
+                        "replies": preferences.replies,
+                        "newComments": preferences.new_comments,
+                        "updatedAt": DateTime::now(),
+                    }
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// 退订某类邮件
+    pub async fn unsubscribe(&self, email: &str, kind: MailKind) -> Result<(), mongodb::error::Error> {
+        let field = match kind {
+            MailKind::Replies => "replies",
+            MailKind::NewComments => "newComments",
+        };
+        self.collection
+            .update_one(
+                doc! { "email": email.to_lowercase() },
+                doc! { "$set": { field: false, "updatedAt": DateTime::now() } },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
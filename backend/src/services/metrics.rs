@@ -0,0 +1,221 @@
+//! Metrics service - Prometheus metrics registry
+//!
+//! 所有指标注册在同一个进程级 Registry 中，由 `fairings::metrics` 暴露为 `/metrics`。
+//! 缓存命中率可通过 `neo_cache_requests_total{result="hit"} / sum(neo_cache_requests_total)` 计算。
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// 应用指标集合
+pub struct Metrics {
+    registry: Registry,
+    /// HTTP 请求数（method, route, status）
+    pub http_requests_total: IntCounterVec,
+    /// HTTP 请求耗时（method, route）
+    pub http_request_duration_seconds: HistogramVec,
+    /// 缓存查询数（result = hit | miss）
+    pub cache_requests_total: IntCounterVec,
+    /// MongoDB 命令耗时（command）
+    pub db_command_duration_seconds: HistogramVec,
+    /// MongoDB 命令失败数（command）
+    pub db_command_failures_total: IntCounterVec,
+    /// Change Stream 断开（需重连）次数
+    pub change_stream_reconnects_total: IntCounter,
+    /// Change Stream 已处理事件数（collection, operation）
+    pub change_stream_events_total: IntCounterVec,
+    /// Next.js Revalidation 结果（result = success | failure）
+    pub revalidations_total: IntCounterVec,
+    /// AI 调用耗时
+    pub ai_request_duration_seconds: Histogram,
+    /// AI 调用失败数
+    pub ai_request_failures_total: IntCounter,
+    /// 被限流的请求数（group）
+    pub rate_limited_total: IntCounterVec,
+    /// Webhook 投递结果（event, result = success | failure）
+    pub webhook_deliveries_total: IntCounterVec,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// 获取全局指标实例
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("neo".to_string()), None)
+            .expect("Failed to create metrics registry");
+
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP 请求总数"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP 请求耗时（秒）"),
+            &["method", "route"],
+        )
+        .unwrap();
+        let cache_requests_total = IntCounterVec::new(
+            Opts::new("cache_requests_total", "缓存查询次数"),
+            &["result"],
+        )
+        .unwrap();
+        let db_command_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("db_command_duration_seconds", "MongoDB 命令耗时（秒）")
+                .buckets(vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+            &["command"],
+        )
+        .unwrap();
+        let db_command_failures_total = IntCounterVec::new(
+            Opts::new("db_command_failures_total", "MongoDB 命令失败次数"),
+            &["command"],
+        )
+        .unwrap();
+        let change_stream_reconnects_total = IntCounter::new(
+            "change_stream_reconnects_total",
+            "Change Stream 断开（需重连）次数",
+        )
+        .unwrap();
+        let change_stream_events_total = IntCounterVec::new(
+            Opts::new("change_stream_events_total", "Change Stream 已处理事件数"),
+            &["collection", "operation"],
+        )
+        .unwrap();
+        let revalidations_total = IntCounterVec::new(
+            Opts::new("revalidations_total", "Next.js Revalidation 请求次数"),
+            &["result"],
+        )
+        .unwrap();
+        let ai_request_duration_seconds = Histogram::with_opts(
+            HistogramOpts::new("ai_request_duration_seconds", "AI 调用耗时（秒）")
+                .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0]),
+        )
+        .unwrap();
+        let ai_request_failures_total = IntCounter::new(
+            "ai_request_failures_total",
+            "AI 调用失败次数",
+        )
+        .unwrap();
+
+        let rate_limited_total = IntCounterVec::new(
+            Opts::new("rate_limited_total", "被限流的请求数"),
+            &["group"],
+        )
+        .unwrap();
+
+        let webhook_deliveries_total = IntCounterVec::new(
+            Opts::new("webhook_deliveries_total", "Webhook 投递次数"),
+            &["event", "result"],
+        )
+        .unwrap();
+
+        let metrics = Self {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            cache_requests_total,
+            db_command_duration_seconds,
+            db_command_failures_total,
+            change_stream_reconnects_total,
+            change_stream_events_total,
+            revalidations_total,
+            ai_request_duration_seconds,
+            ai_request_failures_total,
+            rate_limited_total,
+            webhook_deliveries_total,
+        };
+        metrics.register_all();
+        metrics
+    }
+
+    fn register_all(&self) {
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(self.http_requests_total.clone()),
+            Box::new(self.http_request_duration_seconds.clone()),
+            Box::new(self.cache_requests_total.clone()),
+            Box::new(self.db_command_duration_seconds.clone()),
+            Box::new(self.db_command_failures_total.clone()),
+            Box::new(self.change_stream_reconnects_total.clone()),
+            Box::new(self.change_stream_events_total.clone()),
+            Box::new(self.revalidations_total.clone()),
+            Box::new(self.ai_request_duration_seconds.clone()),
+            Box::new(self.ai_request_failures_total.clone()),
+            Box::new(self.rate_limited_total.clone()),
+            Box::new(self.webhook_deliveries_total.clone()),
+        ];
+        for collector in collectors {
+            self.registry
+                .register(collector)
+                .expect("Failed to register metric");
+        }
+    }
+
+    /// 记录一次 HTTP 请求
+    pub fn observe_http(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        self.http_requests_total
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration_seconds
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// 记录一次缓存查询
+    pub fn observe_cache(&self, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.cache_requests_total.with_label_values(&[result]).inc();
+    }
+
+    /// 记录一次 Revalidation 结果
+    pub fn observe_revalidation(&self, success: bool) {
+        let result = if success { "success" } else { "failure" };
+        self.revalidations_total.with_label_values(&[result]).inc();
+    }
+
+    /// 记录一次 Webhook 投递结果
+    pub fn observe_webhook_delivery(&self, event: &str, success: bool) {
+        let result = if success { "success" } else { "failure" };
+        self.webhook_deliveries_total.with_label_values(&[event, result]).inc();
+    }
+
+    /// 记录一次被限流的请求
+    pub fn observe_rate_limited(&self, group: &str) {
+        self.rate_limited_total.with_label_values(&[group]).inc();
+    }
+
+    /// 以 Prometheus 文本格式导出所有指标
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!("导出 Prometheus 指标失败: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_contains_recorded_metrics() {
+        let metrics = metrics();
+        metrics.observe_http("GET", "/api/posts", 200, Duration::from_millis(12));
+        metrics.observe_cache(true);
+        metrics.observe_cache(false);
+        metrics.observe_revalidation(false);
+
+        let output = metrics.render();
+        assert!(output.contains(r#"neo_http_requests_total{method="GET",route="/api/posts",status="200"}"#));
+        assert!(output.contains("neo_http_request_duration_seconds_bucket"));
+        assert!(output.contains(r#"neo_cache_requests_total{result="hit"}"#));
+        assert!(output.contains(r#"neo_cache_requests_total{result="miss"}"#));
+        assert!(output.contains(r#"neo_revalidations_total{result="failure"}"#));
+    }
+}
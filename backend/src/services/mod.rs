@@ -14,8 +14,13 @@ pub mod turnstile;
 pub mod spam_detector;
 pub mod ip_service;
 pub mod cache_service;
+pub mod metrics;
+pub mod redis_cache;
+pub mod rate_limiter;
 pub mod revalidation_service;
 pub mod change_stream_service;
+pub mod mail;
+pub mod webhook;
 
 pub use db_service::*;
 pub use options_service::*;
@@ -24,11 +29,17 @@ pub use reader_repository::ReaderRepository;
 pub use account_repository::AccountRepository;
 pub use github_oauth::GitHubOAuthService;
 pub use qq_oauth::QQOAuthService;
-pub use options_repository::OptionsRepository;
 pub use comment::service::CommentService;
+pub use comment::reactions::ReactionService;
+pub use comment::review::ReviewQueue;
 pub use turnstile::verify_turnstile;
 pub use spam_detector::SpamDetector;
 pub use ip_service::IpService;
 pub use cache_service::CacheService;
+pub use rate_limiter::{RateLimitGroup, RateLimiter};
 pub use revalidation_service::RevalidationService;
-pub use change_stream_service::ChangeStreamService;
\ No newline at end of file
+pub use change_stream_service::{ChangeStreamHealth, ChangeStreamService};
+pub use mail::mailer::Mailer;
+pub use mail::notifier::CommentNotifier;
+pub use mail::preferences::MailPreferenceRepository;
+pub use webhook::service::WebhookService;
@@ -1,7 +1,11 @@
 //! Options Repository - 从数据库读取配置选项
 
-use mongodb::{Database, bson::doc};
+use futures::stream::TryStreamExt;
+use mongodb::{Database, bson::{doc, Bson}};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::RawOption;
 
 /// OAuth 配置选项（存储在数据库中）
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -10,14 +14,7 @@ pub struct OAuthOptions {
     pub github_client_secret: Option<String>,
 }
 
-/// 数据库中 OAuth 配置的结构
-#[derive(Debug, Deserialize)]
-struct OAuthDocument {
-    #[allow(unused)]
-    name: String,
-    value: OAuthValue,
-}
-
+/// 数据库中 `oauth` 配置项的结构
 #[derive(Debug, Deserialize)]
 struct OAuthValue {
     #[serde(default)]
@@ -59,6 +56,34 @@ struct GitHubPublic {
     client_id: Option<String>,
 }
 
+impl OAuthOptions {
+    /// 从 `oauth` 配置项解析（仅在 GitHub 登录启用时返回 GitHub 凭据）
+    pub fn from_option(value: Option<&Bson>) -> Self {
+        let mut options = Self::default();
+
+        let Some(value) = value.and_then(|v| bson::from_bson::<OAuthValue>(v.clone()).ok()) else {
+            return options;
+        };
+
+        // 检查 GitHub 是否启用
+        let github_enabled = value.providers
+            .iter()
+            .any(|p| p.provider_type == "github" && p.enabled);
+
+        if github_enabled {
+            // 提取 GitHub 配置
+            if let Some(github_public) = &value.public.github {
+                options.github_client_id = github_public.client_id.clone();
+            }
+            if let Some(github_secrets) = &value.secrets.github {
+                options.github_client_secret = github_secrets.client_secret.clone();
+            }
+        }
+
+        options
+    }
+}
+
 /// Options Repository
 pub struct OptionsRepository {
     db: Database,
@@ -70,41 +95,16 @@ impl OptionsRepository {
         Self { db: db.clone() }
     }
 
-    /// 获取 OAuth 配置
-    /// 
+    /// 读取全部配置项
+    ///
     /// # 返回
-    /// * `Ok(OAuthOptions)` - OAuth 配置
+    /// * `Ok(HashMap)` - 配置名 -> 配置值
     /// * `Err(mongodb::error::Error)` - 数据库错误
-    pub async fn get_oauth_config(&self) -> Result<OAuthOptions, mongodb::error::Error> {
-        let collection = self.db.collection::<OAuthDocument>("options");
-
-        // 查询 name 为 "oauth" 的文档
-        let oauth_doc = collection
-            .find_one(doc! { "name": "oauth" })
-            .await?;
-
-        let mut options = OAuthOptions::default();
-
-        if let Some(doc) = oauth_doc {
-            // 检查 GitHub 是否启用
-            let github_enabled = doc.value.providers
-                .iter()
-                .any(|p| p.provider_type == "github" && p.enabled);
-
-            if github_enabled {
-                // 提取 GitHub 配置
-                if let Some(github_public) = &doc.value.public.github {
-                    options.github_client_id = github_public.client_id.clone();
-                }
-                if let Some(github_secrets) = &doc.value.secrets.github {
-                    options.github_client_secret = github_secrets.client_secret.clone();
-                }
-            }
-        }
+    pub async fn load_all(&self) -> Result<HashMap<String, Bson>, mongodb::error::Error> {
+        let collection = self.db.collection::<RawOption>("options");
 
-        log::debug!("从数据库读取 OAuth 配置: github_client_id={:?}", 
-            options.github_client_id.as_ref().map(|s| format!("{}...", &s[..8.min(s.len())])));
+        let options: Vec<RawOption> = collection.find(doc! {}).await?.try_collect().await?;
 
-        Ok(options)
+        Ok(options.into_iter().map(|opt| (opt.name, opt.value)).collect())
     }
 }
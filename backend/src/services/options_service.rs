@@ -1,81 +1,173 @@
-//! Options service - Site configuration queries
+//! Options service - In-memory snapshot of the `options` collection
 //!
 //! IMPORTANT: This service handles the `options` collection from MongoDB.
 //! Only specific, safe fields are extracted and exposed via API endpoints.
 //! Sensitive data (API keys, passwords, secrets) are NEVER exposed.
+//!
+//! 启动时一次性加载全部配置项并解析为各个类型化视图，请求只读取内存中的快照。
+//! Change Stream 监听到 `options` 集合变更（或重新连接）时调用 [`OptionsService::reload`] 刷新。
+
+use chrono::{DateTime, Utc};
+use mongodb::{bson::Bson, Database};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::watch;
 
-use futures::stream::TryStreamExt;
-use mongodb::{bson::doc, Collection, Database};
 use crate::models::{
-    RawOption, SiteConfig, SeoOptions, UrlOptions, FeatureListOptions,
+    SiteConfig, SeoOptions, UrlOptions, FeatureListOptions,
     FriendLinkOptions, CommentOptionsPublic, OAuthPublicOptions, OAuthProvider,
     AlgoliaPublicOptions, AdminExtraPublic,
 };
+use super::ai_service::AiConfig;
+use super::options_repository::{OAuthOptions, OptionsRepository};
+use super::spam_detector::CommentOptions;
+use crate::models::default_reactions;
 
-/// Get a single option by name (internal use only)
-#[allow(unused)]
-pub async fn get_option_by_name(
-    database: &Database,
-    name: &str,
-) -> Result<Option<bson::Bson>, mongodb::error::Error> {
-    let collection: Collection<RawOption> = database.collection("options");
-
-    match collection.find_one(doc! { "name": name }).await? {
-        Some(opt) => Ok(Some(opt.value)),
-        None => Ok(None),
+/// 配置快照（加载后只读）
+#[derive(Debug)]
+pub struct OptionsSnapshot {
+    /// 原始配置项（配置名 -> 值）
+    raw: HashMap<String, Bson>,
+    /// 前端可见的站点配置
+    site: SiteConfig,
+    /// 评论与反垃圾配置
+    comment: Result<CommentOptions, String>,
+    /// AI 配置
+    ai: Result<AiConfig, String>,
+    /// OAuth 凭据（数据库优先于环境变量）
+    oauth: OAuthOptions,
+    /// 加载时间
+    loaded_at: DateTime<Utc>,
+}
+
+impl OptionsSnapshot {
+    /// 前端可见的站点配置
+    pub fn site(&self) -> &SiteConfig {
+        &self.site
+    }
+
+    fn from_raw(raw: HashMap<String, Bson>) -> Self {
+        Self {
+            site: parse_site_config(&raw),
+            comment: CommentOptions::from_option(raw.get("commentOptions")),
+            ai: AiConfig::from_option(raw.get("ai")),
+            oauth: OAuthOptions::from_option(raw.get("oauth")),
+            loaded_at: Utc::now(),
+            raw,
+        }
     }
 }
 
-/// Get aggregated site config (safe for frontend)
-pub async fn get_site_config(database: &Database) -> Result<SiteConfig, mongodb::error::Error> {
-    let collection: Collection<RawOption> = database.collection("options");
+/// 站点配置服务
+///
+/// 可廉价克隆，所有克隆共享同一份快照。
+#[derive(Clone)]
+pub struct OptionsService {
+    repository: Arc<OptionsRepository>,
+    snapshot: Arc<watch::Sender<Arc<OptionsSnapshot>>>,
+}
 
-    let mut config = SiteConfig::default();
+impl OptionsService {
+    /// 加载全部配置项并创建服务
+    pub async fn init(db: &Database) -> Result<Self, mongodb::error::Error> {
+        let repository = OptionsRepository::new(db);
+        let snapshot = OptionsSnapshot::from_raw(repository.load_all().await?);
+        tracing::info!("已加载 {} 个站点配置项", snapshot.raw.len());
+
+        Ok(Self {
+            repository: Arc::new(repository),
+            snapshot: Arc::new(watch::channel(Arc::new(snapshot)).0),
+        })
+    }
+
+    /// 重新从数据库加载配置，失败时保留旧快照
+    pub async fn reload(&self) -> Result<(), mongodb::error::Error> {
+        let snapshot = OptionsSnapshot::from_raw(self.repository.load_all().await?);
+        tracing::info!("站点配置已刷新 ({} 个配置项)", snapshot.raw.len());
+        self.snapshot.send_replace(Arc::new(snapshot));
+        Ok(())
+    }
+
+    /// 当前快照（每次刷新都会替换为新的 Arc，可用 `Arc::ptr_eq` 判断是否变化）
+    pub fn current(&self) -> Arc<OptionsSnapshot> {
+        self.snapshot.borrow().clone()
+    }
+
+    /// 按名称获取原始配置值（仅内部使用，可能包含敏感信息）
+    #[allow(unused)]
+    pub fn get(&self, name: &str) -> Option<Bson> {
+        self.current().raw.get(name).cloned()
+    }
+
+    /// 前端可见的站点配置
+    pub fn site_config(&self) -> SiteConfig {
+        self.current().site.clone()
+    }
+
+    /// 评论与反垃圾配置
+    pub fn comment_options(&self) -> Result<CommentOptions, String> {
+        self.current().comment.clone()
+    }
+
+    /// AI 配置
+    pub fn ai_config(&self) -> Result<AiConfig, String> {
+        self.current().ai.clone()
+    }
 
-    // Fetch all options we need
-    let names = vec![
-        "seo", "url", "featureList", "friendLinkOptions",
-        "commentOptions", "oauth", "algoliaSearchOptions", "adminExtra"
-    ];
+    /// 数据库中的 OAuth 凭据
+    pub fn oauth_options(&self) -> OAuthOptions {
+        self.current().oauth.clone()
+    }
+
+    /// 当前快照的加载时间
+    pub fn loaded_at(&self) -> DateTime<Utc> {
+        self.current().loaded_at
+    }
+}
 
-    let mut cursor = collection
-        .find(doc! { "name": { "$in": &names } })
-        .await?;
+/// Build the aggregated site config (safe for frontend) from raw options
+fn parse_site_config(options: &HashMap<String, Bson>) -> SiteConfig {
+    let mut config = SiteConfig::default();
 
-    while let Some(opt) = cursor.try_next().await? {
-        match opt.name.as_str() {
+    for (name, value) in options {
+        let value = value.clone();
+        match name.as_str() {
             "seo" => {
-                if let Ok(seo) = bson::from_bson::<SeoOptions>(opt.value) {
+                if let Ok(seo) = bson::from_bson::<SeoOptions>(value) {
                     config.seo = seo;
                 }
             }
             "url" => {
-                if let Ok(url) = bson::from_bson::<UrlOptions>(opt.value) {
+                if let Ok(url) = bson::from_bson::<UrlOptions>(value) {
                     config.url = url;
                 }
             }
             "featureList" => {
-                if let Ok(features) = bson::from_bson::<FeatureListOptions>(opt.value) {
+                if let Ok(features) = bson::from_bson::<FeatureListOptions>(value) {
                     config.features = features;
                 }
             }
             "friendLinkOptions" => {
-                if let Ok(friend) = bson::from_bson::<FriendLinkOptions>(opt.value) {
+                if let Ok(friend) = bson::from_bson::<FriendLinkOptions>(value) {
                     config.friend_link = friend;
                 }
             }
             "commentOptions" => {
                 // Only extract safe fields
-                if let bson::Bson::Document(doc) = opt.value {
+                if let bson::Bson::Document(doc) = value {
                     config.comment = CommentOptionsPublic {
                         disable_comment: doc.get_bool("disableComment").unwrap_or(false),
                         disable_no_chinese: doc.get_bool("disableNoChinese").unwrap_or(false),
+                        reactions: doc
+                            .get_array("reactions")
+                            .map(|list| list.iter().filter_map(|r| r.as_str().map(String::from)).collect())
+                            .unwrap_or_else(|_| default_reactions()),
                     };
                 }
             }
             "oauth" => {
                 // Only extract public fields
-                if let bson::Bson::Document(doc) = opt.value {
+                if let bson::Bson::Document(doc) = value {
                     let mut oauth = OAuthPublicOptions::default();
 
                     // Get providers
@@ -98,7 +190,7 @@ pub async fn get_site_config(database: &Database) -> Result<SiteConfig, mongodb:
             }
             "algoliaSearchOptions" => {
                 // Only extract public fields (no apiKey)
-                if let bson::Bson::Document(doc) = opt.value {
+                if let bson::Bson::Document(doc) = value {
                     config.algolia = AlgoliaPublicOptions {
                         enable: doc.get_bool("enable").unwrap_or(false),
                         app_id: doc.get_str("appId").ok().map(String::from),
@@ -108,7 +200,7 @@ pub async fn get_site_config(database: &Database) -> Result<SiteConfig, mongodb:
             }
             "adminExtra" => {
                 // Only extract safe fields
-                if let bson::Bson::Document(doc) = opt.value {
+                if let bson::Bson::Document(doc) = value {
                     config.admin_extra = AdminExtraPublic {
                         title: doc.get_str("title").ok().map(String::from),
                         background: doc.get_str("background").ok().map(String::from),
@@ -119,5 +211,51 @@ pub async fn get_site_config(database: &Database) -> Result<SiteConfig, mongodb:
         }
     }
 
-    Ok(config)
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{bson, doc};
+
+    #[test]
+    fn test_snapshot_builds_typed_views() {
+        let raw = HashMap::from([
+            ("seo".to_string(), bson!({ "title": "TNXG", "description": "blog", "keywords": [] })),
+            (
+                "commentOptions".to_string(),
+                bson!({ "disableComment": true, "antiSpam": true, "aiReview": true, "aiReviewType": "score" }),
+            ),
+            (
+                "oauth".to_string(),
+                bson!({
+                    "providers": [{ "type": "github", "enabled": true }],
+                    "public": { "github": { "clientId": "client-id" } },
+                    "secrets": { "github": { "clientSecret": "client-secret" } },
+                }),
+            ),
+        ]);
+
+        let snapshot = OptionsSnapshot::from_raw(raw);
+        assert_eq!(snapshot.site.seo.title, "TNXG");
+        assert!(snapshot.site.comment.disable_comment);
+
+        let comment = snapshot.comment.unwrap();
+        assert!(comment.anti_spam && comment.ai_review);
+        assert_eq!(comment.ai_review_type, "score");
+        assert_eq!(comment.ai_review_threshold, 5);
+        assert_eq!(comment.edit_window_minutes, 15);
+        assert_eq!(comment.reactions, snapshot.site.comment.reactions);
+
+        assert_eq!(snapshot.oauth.github_client_secret.as_deref(), Some("client-secret"));
+        assert_eq!(snapshot.site.oauth.github_client_id.as_deref(), Some("client-id"));
+        assert!(snapshot.ai.is_err());
+
+        let disabled = OptionsSnapshot::from_raw(HashMap::from([(
+            "oauth".to_string(),
+            Bson::Document(doc! { "providers": [], "secrets": { "github": { "clientSecret": "s" } } }),
+        )]));
+        assert!(disabled.oauth.github_client_secret.is_none());
+    }
 }
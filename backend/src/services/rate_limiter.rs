@@ -0,0 +1,185 @@
+//! Rate limiter - token buckets per route group, stored in the cache backend
+//!
+//! 令牌桶状态保存在缓存后端中：内存后端只在本实例内生效，Redis 后端在所有实例间共享。
+
+use std::time::{Duration, Instant};
+
+use crate::config::{RateLimitConfig, RateLimitKey, RateLimitRule};
+use super::cache_service::CacheService;
+use super::metrics::metrics;
+
+/// 限流分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitGroup {
+    /// 创建评论
+    CommentCreate,
+    /// 评论表情回应
+    CommentReaction,
+    /// AI 时效性分析
+    AiTimeCapsule,
+    /// 缩写释义代理
+    Nbnhhsh,
+    /// OAuth 回调
+    OAuthCallback,
+}
+
+impl RateLimitGroup {
+    /// 分组名称（用于桶键、日志和指标）
+    pub fn name(&self) -> &'static str {
+        match self {
+            RateLimitGroup::CommentCreate => "comment_create",
+            RateLimitGroup::CommentReaction => "comment_reaction",
+            RateLimitGroup::AiTimeCapsule => "ai_time_capsule",
+            RateLimitGroup::Nbnhhsh => "nbnhhsh",
+            RateLimitGroup::OAuthCallback => "oauth_callback",
+        }
+    }
+}
+
+/// 令牌桶参数
+#[derive(Debug, Clone, Copy)]
+pub struct BucketSpec {
+    /// 桶容量（允许的突发请求数）
+    pub capacity: u32,
+    /// 每秒补充的令牌数
+    pub refill_per_second: f64,
+}
+
+impl From<&RateLimitRule> for BucketSpec {
+    fn from(rule: &RateLimitRule) -> Self {
+        Self {
+            capacity: rule.capacity,
+            refill_per_second: rule.refill_per_second(),
+        }
+    }
+}
+
+/// 取令牌的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitDecision {
+    /// 放行
+    Allowed,
+    /// 超出限制，需等待指定时间后重试
+    Limited { retry_after: Duration },
+}
+
+/// 进程内令牌桶状态
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    /// 创建满桶
+    pub fn full(spec: &BucketSpec, now: Instant) -> Self {
+        Self {
+            tokens: spec.capacity as f64,
+            updated_at: now,
+        }
+    }
+
+    /// 按经过的时间补充令牌后尝试取出一个
+    pub fn take(&mut self, spec: &BucketSpec, now: Instant) -> RateLimitDecision {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * spec.refill_per_second).min(spec.capacity as f64);
+        self.updated_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            RateLimitDecision::Allowed
+        } else {
+            let wait = (1.0 - self.tokens) / spec.refill_per_second;
+            RateLimitDecision::Limited {
+                retry_after: Duration::from_secs_f64(wait),
+            }
+        }
+    }
+}
+
+/// 限流服务
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    cache: CacheService,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, cache: CacheService) -> Self {
+        Self { config, cache }
+    }
+
+    fn rule(&self, group: RateLimitGroup) -> &RateLimitRule {
+        match group {
+            RateLimitGroup::CommentCreate => &self.config.comment_create,
+            RateLimitGroup::CommentReaction => &self.config.comment_reaction,
+            RateLimitGroup::AiTimeCapsule => &self.config.ai_time_capsule,
+            RateLimitGroup::Nbnhhsh => &self.config.nbnhhsh,
+            RateLimitGroup::OAuthCallback => &self.config.oauth_callback,
+        }
+    }
+
+    /// 检查一次请求是否放行
+    ///
+    /// # 参数
+    /// - `ip`: 客户端 IP
+    /// - `user_id`: 已登录用户 ID（匿名请求为 None）
+    pub async fn check(
+        &self,
+        group: RateLimitGroup,
+        ip: &str,
+        user_id: Option<&str>,
+    ) -> RateLimitDecision {
+        if !self.config.enabled {
+            return RateLimitDecision::Allowed;
+        }
+
+        let rule = self.rule(group);
+        let spec = BucketSpec::from(rule);
+        let ip_key = format!("{}:ip:{}", group.name(), ip);
+        let keys = match (rule.key, user_id) {
+            (RateLimitKey::Ip, _) | (RateLimitKey::User, None) | (RateLimitKey::Both, None) => {
+                vec![ip_key]
+            }
+            (RateLimitKey::User, Some(user)) => vec![format!("{}:user:{}", group.name(), user)],
+            (RateLimitKey::Both, Some(user)) => {
+                vec![ip_key, format!("{}:user:{}", group.name(), user)]
+            }
+        };
+
+        for key in keys {
+            if let RateLimitDecision::Limited { retry_after } = self.cache.take_token(&key, &spec).await {
+                tracing::warn!("请求被限流: {} (retry after {:.1}s)", key, retry_after.as_secs_f64());
+                metrics().observe_rate_limited(group.name());
+                return RateLimitDecision::Limited { retry_after };
+            }
+        }
+
+        RateLimitDecision::Allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refills_over_time() {
+        let spec = BucketSpec { capacity: 2, refill_per_second: 1.0 };
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(&spec, start);
+
+        assert_eq!(bucket.take(&spec, start), RateLimitDecision::Allowed);
+        assert_eq!(bucket.take(&spec, start), RateLimitDecision::Allowed);
+        match bucket.take(&spec, start) {
+            RateLimitDecision::Limited { retry_after } => {
+                assert!((retry_after.as_secs_f64() - 1.0).abs() < 1e-6)
+            }
+            RateLimitDecision::Allowed => panic!("bucket should be empty"),
+        }
+
+        let later = start + Duration::from_millis(1500);
+        assert_eq!(bucket.take(&spec, later), RateLimitDecision::Allowed);
+        assert!(matches!(bucket.take(&spec, later), RateLimitDecision::Limited { .. }));
+    }
+}
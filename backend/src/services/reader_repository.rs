@@ -65,6 +65,39 @@ impl ReaderRepository {
             .await
     }
 
+    /// 查找站长
+    /// 
+    /// # 返回
+    /// * `Ok(Some(Reader))` - 找到站长
+    /// * `Ok(None)` - 尚未绑定站长
+    /// * `Err(mongodb::error::Error)` - 查询失败时
+    pub async fn find_owner(&self) -> Result<Option<Reader>, mongodb::error::Error> {
+        self.collection
+            .find_one(doc! { "isOwner": true })
+            .await
+    }
+
+    /// 通过邮箱批量查找 readers（单次 `$in` 查询）
+    /// 
+    /// # 参数
+    /// * `emails` - 邮箱地址列表
+    /// 
+    /// # 返回
+    /// * `Ok(Vec<Reader>)` - 匹配到的 readers，按创建时间倒序
+    /// * `Err(mongodb::error::Error)` - 查询失败时
+    pub async fn find_by_emails(&self, emails: &[String]) -> Result<Vec<Reader>, mongodb::error::Error> {
+        if emails.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.collection
+            .find(doc! { "email": { "$in": emails } })
+            .sort(doc! { "createdAt": -1 })
+            .await?
+            .try_collect()
+            .await
+    }
+
     /// 通过昵称和邮箱查找 reader
     /// 
     /// # 参数
@@ -0,0 +1,500 @@
+//! Redis cache backend - shared cache for multi-instance deployments
+//!
+//! 两级缓存：本地 Moka（L1）+ Redis（L2）。
+//! 写入和失效都作用于 Redis，同时通过 pub/sub 广播失效消息，
+//! 所有实例收到后清除各自的本地缓存，保证多副本之间的一致性。
+
+use futures::stream::StreamExt;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::Instrument;
+
+use super::cache_service::{CacheBackend, CacheKey, CacheStats, MokaCacheBackend};
+use super::rate_limiter::{BucketSpec, RateLimitDecision};
+use crate::config::CacheConfig;
+
+/// 令牌桶脚本：以 Redis 服务器时间补充令牌并原子地取出一个
+///
+/// KEYS[1] 桶键；ARGV[1] 容量；ARGV[2] 每毫秒补充的令牌数。
+/// 返回 {是否放行, 需等待的毫秒数}。
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local refill_per_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(state[1]) or capacity
+local ts = tonumber(state[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - ts) * refill_per_ms)
+local allowed = 0
+local wait_ms = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+else
+  wait_ms = math.ceil((1 - tokens) / refill_per_ms)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / refill_per_ms))
+return {allowed, wait_ms}
+"#;
+
+/// 跨实例广播的失效消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "target", rename_all = "lowercase")]
+enum InvalidationMessage {
+    Key(String),
+    Prefix(String),
+    Tag(String),
+    All,
+}
+
+/// Redis 缓存后端
+pub struct RedisCacheBackend {
+    local: MokaCacheBackend,
+    conn: ConnectionManager,
+    key_prefix: String,
+    channel: String,
+    ttl_seconds: u64,
+}
+
+impl RedisCacheBackend {
+    /// 连接 Redis 并启动失效消息订阅（后台任务）
+    pub async fn connect(config: &CacheConfig) -> Result<Self, String> {
+        let redis_url = config
+            .redis_url
+            .as_deref()
+            .ok_or_else(|| "REDIS_URL 未配置".to_string())?;
+
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| format!("无效的 Redis 地址: {}", e))?;
+        let conn = client
+            .get_connection_manager()
+            .await
+            .map_err(|e| format!("连接 Redis 失败: {}", e))?;
+
+        let local = MokaCacheBackend::new(config.max_capacity, config.ttl_seconds);
+
+        tokio::spawn(
+            Self::run_subscriber(client, config.redis_channel.clone(), local.clone())
+                .instrument(tracing::info_span!("cache_invalidation_subscriber")),
+        );
+
+        tracing::info!(
+            "Redis 缓存后端已连接 - 键前缀: {}, 失效频道: {}",
+            config.redis_key_prefix,
+            config.redis_channel
+        );
+
+        Ok(Self {
+            local,
+            conn,
+            key_prefix: config.redis_key_prefix.clone(),
+            channel: config.redis_channel.clone(),
+            ttl_seconds: config.ttl_seconds,
+        })
+    }
+
+    /// 数据键: {prefix}{cache_key}
+    fn data_key(&self, key: &CacheKey) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+
+    /// 限流令牌桶键: {prefix}__ratelimit:{key}
+    fn bucket_key(&self, key: &str) -> String {
+        format!("{}__ratelimit:{}", self.key_prefix, key)
+    }
+
+    /// 前缀索引键: {prefix}__index:prefix:{prefix}
+    fn prefix_index_key(&self, prefix: &str) -> String {
+        format!("{}__index:prefix:{}", self.key_prefix, prefix)
+    }
+
+    /// 标签索引键: {prefix}__index:tag:{tag}
+    fn tag_index_key(&self, tag: &str) -> String {
+        format!("{}__index:tag:{}", self.key_prefix, tag)
+    }
+
+    /// 删除索引集合中登记的所有数据键，以及索引本身
+    async fn invalidate_index(&self, index_key: &str) -> redis::RedisResult<()> {
+        let mut conn = self.conn.clone();
+        let members: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(index_key)
+            .query_async(&mut conn)
+            .await?;
+
+        if !members.is_empty() {
+            redis::cmd("DEL")
+                .arg(&members)
+                .query_async::<()>(&mut conn)
+                .await?;
+        }
+        redis::cmd("DEL")
+            .arg(index_key)
+            .query_async::<()>(&mut conn)
+            .await
+    }
+
+    /// 删除所有带本服务前缀的键（限流令牌桶除外，清空缓存不应重置限额）
+    async fn delete_all(&self) -> redis::RedisResult<()> {
+        let mut conn = self.conn.clone();
+        let pattern = format!("{}*", self.key_prefix);
+        let bucket_prefix = self.bucket_key("");
+        let mut cursor: u64 = 0;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut conn)
+                .await?;
+
+            let keys: Vec<String> = keys.into_iter().filter(|key| !key.starts_with(&bucket_prefix)).collect();
+            if !keys.is_empty() {
+                redis::cmd("DEL")
+                    .arg(&keys)
+                    .query_async::<()>(&mut conn)
+                    .await?;
+            }
+
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
+        }
+    }
+
+    /// 广播失效消息
+    async fn publish(&self, message: InvalidationMessage) {
+        let payload = match serde_json::to_string(&message) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("序列化缓存失效消息失败: {}", e);
+                return;
+            }
+        };
+
+        let mut conn = self.conn.clone();
+        if let Err(e) = redis::cmd("PUBLISH")
+            .arg(&self.channel)
+            .arg(&payload)
+            .query_async::<()>(&mut conn)
+            .await
+        {
+            tracing::error!("广播缓存失效消息失败: {} - {}", payload, e);
+        }
+    }
+
+    /// 订阅失效频道（带自动重连）
+    async fn run_subscriber(client: redis::Client, channel: String, local: MokaCacheBackend) {
+        loop {
+            match Self::subscribe_once(&client, &channel, &local).await {
+                Ok(()) => tracing::warn!("Redis 失效订阅连接已关闭，准备重新连接..."),
+                Err(e) => tracing::error!("Redis 失效订阅错误: {}", e),
+            }
+
+            // 断线期间可能错过失效消息，清空本地缓存以保证一致性
+            local.clear().await;
+
+            sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn subscribe_once(
+        client: &redis::Client,
+        channel: &str,
+        local: &MokaCacheBackend,
+    ) -> redis::RedisResult<()> {
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.subscribe(channel).await?;
+        tracing::info!("✓ 已订阅 Redis 缓存失效频道: {}", channel);
+
+        let mut messages = pubsub.on_message();
+        while let Some(msg) = messages.next().await {
+            let payload: String = match msg.get_payload() {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::warn!("无法读取缓存失效消息: {}", e);
+                    continue;
+                }
+            };
+
+            match serde_json::from_str::<InvalidationMessage>(&payload) {
+                Ok(message) => Self::apply_locally(local, message).await,
+                Err(e) => tracing::warn!("无法解析缓存失效消息: {} - {}", payload, e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 在本地缓存上执行失效
+    async fn apply_locally(local: &MokaCacheBackend, message: InvalidationMessage) {
+        tracing::debug!("[Cache] 收到失效广播: {:?}", message);
+        match message {
+            InvalidationMessage::Key(key) => match key.parse::<CacheKey>() {
+                Ok(key) => local.invalidate(&key).await,
+                Err(e) => tracing::warn!("{}", e),
+            },
+            InvalidationMessage::Prefix(prefix) => local.invalidate_by_prefix(&prefix).await,
+            InvalidationMessage::Tag(tag) => local.invalidate_by_tag(&tag).await,
+            InvalidationMessage::All => local.clear().await,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl CacheBackend for RedisCacheBackend {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        if let Some(value) = self.local.get(key).await {
+            return Some(value);
+        }
+
+        let mut conn = self.conn.clone();
+        let value: Option<Vec<u8>> = match redis::cmd("GET")
+            .arg(self.data_key(key))
+            .query_async(&mut conn)
+            .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("[Cache] 读取 Redis 失败: {} - {}", key, e);
+                return None;
+            }
+        };
+
+        if let Some(ref value) = value {
+            self.local.set(key, value.clone()).await;
+        }
+        value
+    }
+
+    async fn set(&self, key: &CacheKey, value: Vec<u8>) {
+        self.local.set(key, value.clone()).await;
+
+        let data_key = self.data_key(key);
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("SET").arg(&data_key).arg(value).arg("EX").arg(self.ttl_seconds).ignore();
+
+        let prefix_index = self.prefix_index_key(key.prefix());
+        pipe.cmd("SADD").arg(&prefix_index).arg(&data_key).ignore()
+            .cmd("EXPIRE").arg(&prefix_index).arg(self.ttl_seconds).ignore();
+
+        for tag in key.tags() {
+            let tag_index = self.tag_index_key(tag);
+            pipe.cmd("SADD").arg(&tag_index).arg(&data_key).ignore()
+                .cmd("EXPIRE").arg(&tag_index).arg(self.ttl_seconds).ignore();
+        }
+
+        let mut conn = self.conn.clone();
+        if let Err(e) = pipe.query_async::<()>(&mut conn).await {
+            tracing::warn!("[Cache] 写入 Redis 失败: {} - {}", key, e);
+        }
+    }
+
+    async fn invalidate(&self, key: &CacheKey) {
+        let mut conn = self.conn.clone();
+        if let Err(e) = redis::cmd("DEL")
+            .arg(self.data_key(key))
+            .query_async::<()>(&mut conn)
+            .await
+        {
+            tracing::error!("清除 Redis 缓存失败: {} - {}", key, e);
+        }
+
+        self.local.invalidate(key).await;
+        self.publish(InvalidationMessage::Key(key.to_string())).await;
+    }
+
+    async fn invalidate_by_prefix(&self, prefix: &str) {
+        if let Err(e) = self.invalidate_index(&self.prefix_index_key(prefix)).await {
+            tracing::error!("批量清除 Redis 缓存失败 (前缀: {}): {}", prefix, e);
+        }
+
+        self.local.invalidate_by_prefix(prefix).await;
+        self.publish(InvalidationMessage::Prefix(prefix.to_string())).await;
+    }
+
+    async fn invalidate_by_tag(&self, tag: &str) {
+        if let Err(e) = self.invalidate_index(&self.tag_index_key(tag)).await {
+            tracing::error!("批量清除 Redis 缓存失败 (标签: {}): {}", tag, e);
+        }
+
+        self.local.invalidate_by_tag(tag).await;
+        self.publish(InvalidationMessage::Tag(tag.to_string())).await;
+    }
+
+    async fn clear(&self) {
+        if let Err(e) = self.delete_all().await {
+            tracing::error!("清除 Redis 全部缓存失败: {}", e);
+        }
+
+        self.local.clear().await;
+        self.publish(InvalidationMessage::All).await;
+    }
+
+    async fn stats(&self) -> CacheStats {
+        self.local.stats().await
+    }
+
+    /// 令牌桶只存在 Redis 中，不经过本地缓存，保证所有实例共享同一个限额
+    async fn take_token(&self, key: &str, spec: &BucketSpec) -> RateLimitDecision {
+        let mut conn = self.conn.clone();
+        let result: redis::RedisResult<(i64, i64)> = redis::cmd("EVAL")
+            .arg(TOKEN_BUCKET_SCRIPT)
+            .arg(1)
+            .arg(self.bucket_key(key))
+            .arg(spec.capacity)
+            .arg(spec.refill_per_second / 1000.0)
+            .query_async(&mut conn)
+            .await;
+
+        match result {
+            Ok((1, _)) => RateLimitDecision::Allowed,
+            Ok((_, wait_ms)) => RateLimitDecision::Limited {
+                retry_after: Duration::from_millis(wait_ms.max(0) as u64),
+            },
+            Err(e) => {
+                // Redis 不可用时放行，避免限流拖垮正常请求
+                tracing::warn!("[RateLimit] 读取 Redis 令牌桶失败，本次放行: {} - {}", key, e);
+                RateLimitDecision::Allowed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheBackendKind;
+
+    #[test]
+    fn test_invalidation_message_format() {
+        let cases = [
+            (InvalidationMessage::Key("post:p1".to_string()), r#"{"op":"key","target":"post:p1"}"#),
+            (InvalidationMessage::Prefix("posts".to_string()), r#"{"op":"prefix","target":"posts"}"#),
+            (InvalidationMessage::Tag("categories".to_string()), r#"{"op":"tag","target":"categories"}"#),
+            (InvalidationMessage::All, r#"{"op":"all"}"#),
+        ];
+
+        for (message, json) in cases {
+            assert_eq!(serde_json::to_string(&message).unwrap(), json);
+            assert_eq!(serde_json::from_str::<InvalidationMessage>(json).unwrap(), message);
+        }
+    }
+
+    // 以下测试需要本地 Redis，例如：
+    //   docker compose -f docker-compose.backend.yml --profile redis up -d redis
+    //   REDIS_URL=redis://127.0.0.1:6379 cargo test -- --ignored redis_cache
+
+    fn test_config() -> CacheConfig {
+        CacheConfig {
+            backend: CacheBackendKind::Redis,
+            max_capacity: 100,
+            ttl_seconds: 60,
+            redis_url: Some(
+                std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
+            ),
+            // 每次运行使用独立前缀和频道，避免与其他测试互相干扰
+            redis_key_prefix: format!("neo-space-test:{}:", bson::oid::ObjectId::new()),
+            redis_channel: format!("neo-space-test:{}", bson::oid::ObjectId::new()),
+        }
+    }
+
+    /// 等待失效广播到达另一个实例
+    async fn wait_until_missing(backend: &RedisCacheBackend, key: &CacheKey) -> bool {
+        for _ in 0..50 {
+            if backend.local.get(key).await.is_none() {
+                return true;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    #[ignore = "需要本地 Redis"]
+    async fn test_shared_values_and_broadcast_invalidation() {
+        let config = test_config();
+        let a = RedisCacheBackend::connect(&config).await.unwrap();
+        let b = RedisCacheBackend::connect(&config).await.unwrap();
+        // 等待两个实例完成订阅
+        sleep(Duration::from_millis(200)).await;
+
+        let key = CacheKey::Post("p1".to_string());
+        a.set(&key, b"hello".to_vec()).await;
+
+        // B 从 Redis 读到 A 写入的值，并写入自己的本地缓存
+        assert_eq!(b.get(&key).await, Some(b"hello".to_vec()));
+        assert!(b.local.get(&key).await.is_some());
+
+        // A 失效后，B 的本地缓存通过广播被清除
+        a.invalidate(&key).await;
+        assert!(wait_until_missing(&b, &key).await);
+        assert_eq!(b.get(&key).await, None);
+
+        a.clear().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "需要本地 Redis"]
+    async fn test_tag_and_prefix_invalidation_across_instances() {
+        let config = test_config();
+        let a = RedisCacheBackend::connect(&config).await.unwrap();
+        let b = RedisCacheBackend::connect(&config).await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        let post = CacheKey::Post("p1".to_string());
+        let list = CacheKey::PostList { page: 1, size: 10 };
+        let note = CacheKey::Note("n1".to_string());
+        for key in [&post, &list, &note] {
+            a.set(key, vec![1]).await;
+            assert!(b.get(key).await.is_some());
+        }
+
+        b.invalidate_by_prefix("posts").await;
+        assert!(wait_until_missing(&a, &list).await);
+        assert_eq!(a.get(&list).await, None);
+        assert!(a.get(&post).await.is_some());
+
+        b.invalidate_by_tag("categories").await;
+        assert!(wait_until_missing(&a, &post).await);
+        assert_eq!(a.get(&post).await, None);
+        assert!(a.get(&note).await.is_some());
+
+        b.clear().await;
+        assert!(wait_until_missing(&a, &note).await);
+        assert_eq!(a.get(&note).await, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "需要本地 Redis"]
+    async fn test_token_bucket_shared_across_instances() {
+        let config = test_config();
+        let a = RedisCacheBackend::connect(&config).await.unwrap();
+        let b = RedisCacheBackend::connect(&config).await.unwrap();
+        let spec = BucketSpec { capacity: 2, refill_per_second: 0.1 };
+
+        assert_eq!(a.take_token("test:ip:1", &spec).await, RateLimitDecision::Allowed);
+        assert_eq!(b.take_token("test:ip:1", &spec).await, RateLimitDecision::Allowed);
+        match a.take_token("test:ip:1", &spec).await {
+            RateLimitDecision::Limited { retry_after } => {
+                assert!(retry_after > Duration::from_secs(5))
+            }
+            RateLimitDecision::Allowed => panic!("bucket should be empty"),
+        }
+        assert_eq!(b.take_token("test:ip:2", &spec).await, RateLimitDecision::Allowed);
+
+        a.clear().await;
+    }
+}
@@ -1,10 +1,9 @@
 //! Revalidation service - Notify Next.js to revalidate ISR cache with HMAC signature
 
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-type HmacSha256 = Hmac<Sha256>;
+use super::metrics::metrics;
+use crate::utils::hmac::hmac_sha256_hex;
 
 /// Revalidation 服务 - 通知 Next.js 重新验证 ISR 缓存
 #[derive(Clone)]
@@ -22,7 +21,7 @@ impl RevalidationService {
     /// - `secret`: HMAC 签名密钥
     /// - `salt`: 额外的盐值
     pub fn new(nextjs_url: String, secret: String, salt: String) -> Self {
-        log::info!("Revalidation 服务初始化 - Next.js URL: {}", nextjs_url);
+        tracing::info!("Revalidation 服务初始化 - Next.js URL: {}", nextjs_url);
         Self {
             nextjs_url,
             secret,
@@ -55,7 +54,7 @@ impl RevalidationService {
             "signature": signature,
         });
 
-        log::debug!(
+        tracing::debug!(
             "发送 Revalidation 请求 - Tag: {}, Timestamp: {}, Signature: {}",
             tag,
             timestamp,
@@ -69,15 +68,19 @@ impl RevalidationService {
             .json(&body)
             .timeout(std::time::Duration::from_secs(10))
             .send()
-            .await?;
+            .await
+            .inspect_err(|_| metrics().observe_revalidation(false))?;
 
-        if response.status().is_success() {
-            log::info!("✓ Revalidation 成功 - Tag: {}", tag);
+        let success = response.status().is_success();
+        metrics().observe_revalidation(success);
+
+        if success {
+            tracing::info!("✓ Revalidation 成功 - Tag: {}", tag);
             Ok(())
         } else {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            log::error!(
+            tracing::error!(
                 "Revalidation 失败 - Status: {}, Error: {}",
                 status,
                 error_text
@@ -112,7 +115,7 @@ impl RevalidationService {
             "signature": signature,
         });
 
-        log::debug!(
+        tracing::debug!(
             "发送 Revalidation 请求 - Path: {}, Timestamp: {}, Signature: {}",
             path,
             timestamp,
@@ -126,15 +129,19 @@ impl RevalidationService {
             .json(&body)
             .timeout(std::time::Duration::from_secs(10))
             .send()
-            .await?;
+            .await
+            .inspect_err(|_| metrics().observe_revalidation(false))?;
+
+        let success = response.status().is_success();
+        metrics().observe_revalidation(success);
 
-        if response.status().is_success() {
-            log::info!("✓ Revalidation 成功 - Path: {}", path);
+        if success {
+            tracing::info!("✓ Revalidation 成功 - Path: {}", path);
             Ok(())
         } else {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            log::error!(
+            tracing::error!(
                 "Revalidation 失败 - Status: {}, Error: {}",
                 status,
                 error_text
@@ -145,11 +152,7 @@ impl RevalidationService {
 
     /// 生成 HMAC-SHA256 签名
     fn generate_hmac(&self, message: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let mut mac = HmacSha256::new_from_slice(self.secret.as_bytes())?;
-        mac.update(message.as_bytes());
-        let result = mac.finalize();
-        let code_bytes = result.into_bytes();
-        Ok(hex::encode(code_bytes))
+        Ok(hmac_sha256_hex(&self.secret, message))
     }
 }
 
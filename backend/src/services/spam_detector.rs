@@ -1,34 +1,76 @@
 //! 垃圾评论检测服务
 //!
-//! 支持两种检测模式：
+//! 支持三种检测模式：
 //! 1. binary - 二分法：AI 直接判断是/否垃圾评论
 //! 2. score - 评分法：AI 给出 0-10 分，根据阈值判断
+//! 3. bayes - 本地贝叶斯分类器先判断，把握不足时再交给 AI 二分法；AI 不可用时以分类器结果为准
 //!
-//! 支持异步审核：先存入数据库，后台异步调用 AI 审核
+//! 支持异步审核：先存入数据库，由审核队列在后台调用 AI 审核（失败时重试）
 
-use mongodb::{bson::doc, Database};
+use mongodb::{bson::{doc, Bson, DateTime}, Database};
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
-use crate::models::{RawOption, CommentState};
+use crate::models::{Comment, CommentState, Moderation, ModerationEngine, WebhookEvent};
 use crate::services::ai_service::{AiService, ChatMessage, ChatRole};
+use crate::services::comment::bayes::BayesClassifier;
+use crate::services::comment::rules::CommentRuleOptions;
+use crate::services::mail::{mailer::Mailer, notifier::CommentNotifier};
+use crate::services::webhook::service::{comment_payload, WebhookService};
+use crate::services::options_service::OptionsService;
 
 /// 评论配置选项
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct CommentOptions {
+pub struct CommentOptions {
     /// 是否启用反垃圾检测
     #[serde(default)]
-    anti_spam: bool,
+    pub anti_spam: bool,
     /// 是否启用 AI 审核
     #[serde(default)]
-    ai_review: bool,
-    /// AI 审核类型：binary（二分法）或 score（评分法）
+    pub ai_review: bool,
+    /// AI 审核类型：binary（二分法）、score（评分法）或 bayes（贝叶斯分类器优先）
     #[serde(default = "default_ai_review_type")]
-    ai_review_type: String,
+    pub ai_review_type: String,
     /// AI 审核阈值（仅评分法使用，0-10）
     #[serde(default = "default_ai_review_threshold")]
-    ai_review_threshold: u8,
+    pub ai_review_threshold: u8,
+    /// 贝叶斯分类器直接判为垃圾的概率下限（仅 bayes 使用）
+    #[serde(default = "default_bayes_spam_threshold")]
+    pub bayes_spam_threshold: f64,
+    /// 贝叶斯分类器直接判为正常的概率上限（仅 bayes 使用）
+    #[serde(default = "default_bayes_ham_threshold")]
+    pub bayes_ham_threshold: f64,
+    /// 作者可编辑评论的时间窗口（分钟），0 表示作者不可编辑；站长不受限制
+    #[serde(default = "default_edit_window_minutes")]
+    pub edit_window_minutes: i64,
+    /// 允许的表情回应
+    #[serde(default = "crate::models::default_reactions")]
+    pub reactions: Vec<String>,
+    /// 内容必须包含中文
+    #[serde(default)]
+    pub disable_no_chinese: bool,
+    /// 关键词黑名单
+    #[serde(default)]
+    pub spam_keywords: Vec<String>,
+    /// 屏蔽的 IP 或 CIDR 网段
+    #[serde(default)]
+    pub block_ips: Vec<String>,
+    /// 规则过滤配置（见 [`crate::services::comment::rules`]）
+    #[serde(default)]
+    pub rules: CommentRuleOptions,
+}
+
+impl CommentOptions {
+    /// 从 `commentOptions` 配置项解析
+    pub fn from_option(value: Option<&Bson>) -> Result<Self, String> {
+        let doc = value
+            .ok_or_else(|| "评论配置不存在".to_string())?
+            .as_document()
+            .ok_or_else(|| "评论配置不是文档类型".to_string())?;
+
+        bson::from_document(doc.clone()).map_err(|e| format!("解析评论配置失败: {}", e))
+    }
 }
 
 fn default_ai_review_type() -> String {
@@ -39,8 +81,23 @@ fn default_ai_review_threshold() -> u8 {
     5
 }
 
+fn default_bayes_spam_threshold() -> f64 {
+    0.9
+}
+
+fn default_bayes_ham_threshold() -> f64 {
+    0.1
+}
+
+/// 未配置评论选项时的默认编辑窗口（分钟）
+pub const DEFAULT_EDIT_WINDOW_MINUTES: i64 = 15;
+
+fn default_edit_window_minutes() -> i64 {
+    DEFAULT_EDIT_WINDOW_MINUTES
+}
+
 /// 垃圾检测结果
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SpamCheckResult {
     /// 是否为垃圾评论
     pub is_spam: bool,
@@ -48,6 +105,28 @@ pub struct SpamCheckResult {
     pub confidence: f32,
     /// 拒绝原因
     pub reason: Option<String>,
+    /// 给出结果的审核引擎，未进行审核（未启用或未配置）时为空
+    pub engine: Option<ModerationEngine>,
+    /// AI 模型名（仅 llm）
+    pub model: Option<String>,
+    /// 评分：评分法为 0-10 分，贝叶斯分类器为垃圾概率（0-1）
+    pub score: Option<f64>,
+}
+
+impl SpamCheckResult {
+    /// 保存在评论上的审核记录，未进行审核时为空
+    pub fn moderation(&self) -> Option<Moderation> {
+        Some(Moderation {
+            engine: self.engine?,
+            model: self.model.clone(),
+            score: self.score,
+            is_spam: self.is_spam,
+            reason: self.reason.clone(),
+            reviewed_at: mongodb::bson::DateTime::now(),
+            override_state: None,
+            overridden_at: None,
+        })
+    }
 }
 
 /// AI 二分法响应
@@ -72,129 +151,242 @@ impl SpamDetector {
     /// 
     /// # Returns
     /// * `bool` - 是否启用 AI 审核
-    pub async fn is_ai_review_enabled(db: &Database) -> bool {
-        match Self::get_comment_options(db).await {
+    pub fn is_ai_review_enabled(options: &OptionsService) -> bool {
+        match options.comment_options() {
             Ok(opts) => opts.anti_spam && opts.ai_review,
             Err(_) => false,
         }
     }
 
-    /// 异步审核评论（在后台任务中调用）
-    /// 
-    /// 根据 AI 检测结果更新评论状态：
+    /// 审核评论（由审核队列调用，见 [`crate::services::comment::review`]）
+    ///
+    /// 根据检测结果更新评论状态：
     /// - 垃圾评论：state = 2 (SPAM)
-    /// - 正常评论：state = 0 (UNREAD)
+    /// - 正常评论：state = 0 (UNREAD)，已读的评论保持已读；在传入 `mailer` 时发送评论通知
+    ///
+    /// 状态有变化时以 `comment.spam` / `comment.approved` Webhook 事件推送。
+    /// AI 调用失败时返回错误，评论状态保持不变，由队列稍后重试。
     ///
     /// # Arguments
     /// * `db` - 数据库连接
-    /// * `comment_id` - 评论 ID
-    /// * `text` - 评论内容
-    /// * `author` - 作者昵称
-    /// * `email` - 作者邮箱
-    pub async fn review_async(
+    /// * `options` - 站点配置
+    /// * `comment` - 待审核的评论
+    /// * `claimed_at` - 任务领取时间；此后博主改判过的评论不再写回
+    /// * `mailer` - 审核通过后用于发送通知的邮件服务（编辑后的复审不再通知）
+    pub async fn review(
         db: &Database,
-        comment_id: ObjectId,
-        text: &str,
-        author: &str,
-        email: &str,
-    ) {
-        log::info!("开始异步审核评论: {}", comment_id);
-        
+        options: &OptionsService,
+        comment: &Comment,
+        claimed_at: DateTime,
+        mailer: Option<&Mailer>,
+    ) -> Result<(), String> {
+        let comment_id = comment.id.ok_or_else(|| "评论缺少 ID".to_string())?;
+        tracing::info!("开始审核评论: {}", comment_id);
+
         // 执行垃圾检测
-        let result = Self::check(db, text, author, email).await;
-        
+        let result = Self::check(db, options, &comment.text, &comment.author, &comment.mail).await?;
+
         // 根据结果更新评论状态
         let new_state = if result.is_spam {
-            log::warn!(
-                "异步审核: 评论 {} 被识别为垃圾 (置信度: {:.2}) - 原因: {:?}",
+            tracing::warn!(
+                "审核: 评论 {} 被识别为垃圾 (置信度: {:.2}) - 原因: {:?}",
                 comment_id,
                 result.confidence,
                 result.reason
             );
             CommentState::SPAM
         } else {
-            log::info!("异步审核: 评论 {} 审核通过", comment_id);
-            CommentState::UNREAD
+            tracing::info!("审核: 评论 {} 审核通过", comment_id);
+            if comment.state == CommentState::READ {
+                CommentState::READ
+            } else {
+                CommentState::UNREAD
+            }
         };
-        
-        // 更新数据库
+
+        // 审核记录（未进行审核时清空旧记录）
+        let moderation = match result.moderation() {
+            Some(moderation) => mongodb::bson::to_bson(&moderation).map_err(|e| e.to_string())?,
+            None => Bson::Null,
+        };
+
+        // 更新数据库：内容在审核期间被再次编辑时以新一轮审核为准；
+        // 状态已被博主修改（含领取任务后的改判）时以博主的处理为准
         let collection = db.collection::<mongodb::bson::Document>("comments");
-        if let Err(e) = collection
+        let updated = collection
             .update_one(
-                doc! { "_id": comment_id },
-                doc! { "$set": { "state": new_state } },
+                doc! {
+                    "_id": comment_id,
+                    "text": &comment.text,
+                    "state": comment.state,
+                    "deletedAt": null,
+                    "$or": [
+                        { "moderation.overriddenAt": null },
+                        { "moderation.overriddenAt": { "$lt": claimed_at } },
+                    ],
+                },
+                doc! { "$set": { "state": new_state, "moderation": moderation } },
             )
             .await
-        {
-            log::error!("更新评论状态失败: {} - {}", comment_id, e);
+            .map_err(|e| format!("更新评论状态失败: {}", e))?;
+        if updated.matched_count == 0 {
+            tracing::info!("评论 {} 已被编辑、删除或由博主改判，丢弃本次审核结果", comment_id);
+            return Ok(());
         }
+        if new_state == comment.state {
+            return Ok(());
+        }
+
+        let event = if result.is_spam {
+            WebhookEvent::CommentSpam
+        } else {
+            WebhookEvent::CommentApproved
+        };
+        Self::emit_webhook(db, event, comment_id).await;
+
+        if let (Some(mailer), false) = (mailer, result.is_spam) {
+            CommentNotifier::comment_published(db, mailer, comment_id).await;
+        }
+        Ok(())
     }
 
-    /// 检测评论是否为垃圾内容（同步检测，用于异步任务内部）
+    /// 推送审核结果的 Webhook 事件
+    async fn emit_webhook(db: &Database, event: WebhookEvent, comment_id: ObjectId) {
+        match db.collection::<Comment>("comments").find_one(doc! { "_id": comment_id }).await {
+            Ok(Some(comment)) => WebhookService::new(db).emit(event, comment_payload(&comment)).await,
+            Ok(None) => {}
+            Err(e) => tracing::error!("读取评论 {} 失败: {}", comment_id, e),
+        }
+    }
+
+    /// 检测评论是否为垃圾内容（用于审核队列内部）
     ///
     /// # Arguments
-    /// * `db` - 数据库连接
+    /// * `db` - 数据库连接（bayes 模式读取分类器模型）
+    /// * `options` - 站点配置
     /// * `text` - 评论内容
     /// * `author` - 作者昵称
     /// * `email` - 作者邮箱
     ///
     /// # Returns
-    /// * `SpamCheckResult` - 检测结果
+    /// * `SpamCheckResult` - 检测结果；未启用或未配置 AI 审核时放行，AI 调用失败时返回错误
     pub async fn check(
         db: &Database,
+        options: &OptionsService,
         text: &str,
         author: &str,
         email: &str,
-    ) -> SpamCheckResult {
+    ) -> Result<SpamCheckResult, String> {
         // 1. 获取评论配置
-        let comment_options = match Self::get_comment_options(db).await {
+        let comment_options = match options.comment_options() {
             Ok(opts) => opts,
             Err(e) => {
-                log::error!("获取评论配置失败: {}", e);
-                return Self::pass_result();
+                tracing::error!("获取评论配置失败: {}", e);
+                return Ok(Self::pass_result());
             }
         };
 
         // 2. 检查是否启用反垃圾和 AI 审核
         if !comment_options.anti_spam || !comment_options.ai_review {
-            log::debug!("反垃圾或 AI 审核未启用");
-            return Self::pass_result();
+            tracing::debug!("反垃圾或 AI 审核未启用");
+            return Ok(Self::pass_result());
         }
 
-        // 3. 创建 AI 服务
-        let ai_service = match AiService::from_database(db).await {
-            Ok(service) => service,
-            Err(e) => {
-                log::error!("创建 AI 服务失败: {}", e);
-                return Self::pass_result();
-            }
-        };
-
-        if !ai_service.is_enabled() {
-            log::debug!("AI 服务未启用");
-            return Self::pass_result();
+        // 3. bayes 模式先由本地分类器判断，不依赖 AI 服务
+        if comment_options.ai_review_type == "bayes" {
+            return Self::check_bayes(db, options, &comment_options, text, author, email).await;
         }
 
-        // 4. 根据审核类型调用不同的检测方法
+        // 4. 创建 AI 服务
+        let Some(ai_service) = Self::ai_service(options) else {
+            return Ok(Self::pass_result());
+        };
+
+        // 5. 根据审核类型调用不同的检测方法
         match comment_options.ai_review_type.as_str() {
             "binary" => Self::check_binary(&ai_service, text, author, email).await,
             "score" => {
                 Self::check_score(&ai_service, text, author, email, comment_options.ai_review_threshold).await
             }
             _ => {
-                log::warn!("未知的 AI 审核类型: {}", comment_options.ai_review_type);
-                Self::pass_result()
+                tracing::warn!("未知的 AI 审核类型: {}", comment_options.ai_review_type);
+                Ok(Self::pass_result())
+            }
+        }
+    }
+
+    /// 已启用的 AI 服务
+    fn ai_service(options: &OptionsService) -> Option<AiService> {
+        match AiService::from_options(options) {
+            Ok(service) if service.is_enabled() => Some(service),
+            Ok(_) => {
+                tracing::debug!("AI 服务未启用");
+                None
+            }
+            Err(e) => {
+                tracing::error!("创建 AI 服务失败: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 贝叶斯分类器优先：概率落在阈值之外时直接判断，否则交给 AI 二分法；
+    /// AI 未启用或调用失败时按概率 0.5 判断。分类器样本不足时，AI 未启用则放行，AI 调用失败则返回错误
+    async fn check_bayes(
+        db: &Database,
+        options: &OptionsService,
+        comment_options: &CommentOptions,
+        text: &str,
+        author: &str,
+        email: &str,
+    ) -> Result<SpamCheckResult, String> {
+        let probability = match BayesClassifier::new(db).classify(text, author).await {
+            Ok(probability) => probability,
+            Err(e) => {
+                tracing::error!("贝叶斯分类失败: {}", e);
+                None
+            }
+        };
+
+        if let Some(p) = probability {
+            tracing::debug!("贝叶斯垃圾概率: {:.3}", p);
+            if p >= comment_options.bayes_spam_threshold || p <= comment_options.bayes_ham_threshold {
+                return Ok(Self::bayes_result(p));
+            }
+        }
+
+        let Some(ai_service) = Self::ai_service(options) else {
+            return Ok(probability.map_or_else(Self::pass_result, Self::bayes_result));
+        };
+        match (Self::check_binary(&ai_service, text, author, email).await, probability) {
+            (Ok(result), _) => Ok(result),
+            (Err(e), Some(p)) => {
+                tracing::error!("AI 检测失败，使用贝叶斯分类结果: {}", e);
+                Ok(Self::bayes_result(p))
             }
+            (Err(e), None) => Err(e),
         }
     }
 
-    /// 二分法检测：AI 直接判断是/否
+    fn bayes_result(probability: f64) -> SpamCheckResult {
+        let is_spam = probability >= 0.5;
+        SpamCheckResult {
+            is_spam,
+            confidence: if is_spam { probability } else { 1.0 - probability } as f32,
+            reason: Some(format!("贝叶斯分类器: 垃圾概率 {:.2}", probability)),
+            engine: Some(ModerationEngine::Bayes),
+            model: None,
+            score: Some(probability),
+        }
+    }
+
+    /// 二分法检测：AI 直接判断是/否（AI 调用失败时返回错误）
     async fn check_binary(
         ai_service: &AiService,
         text: &str,
         author: &str,
         email: &str,
-    ) -> SpamCheckResult {
+    ) -> Result<SpamCheckResult, String> {
         let system_prompt = r#"你是一个专业的垃圾评论检测助手。你的任务是判断用户提交的评论是否为垃圾内容。
 
 垃圾评论的特征包括但不限于：
@@ -231,24 +423,21 @@ impl SpamDetector {
 
         match ai_service.chat(messages, Some(0.3), None).await {
             Ok(response) => {
-                log::debug!("AI 二分法响应: {}", response);
-                Self::parse_binary_response(&response)
-            }
-            Err(e) => {
-                log::error!("AI 检测失败: {}", e);
-                Self::pass_result()
+                tracing::debug!("AI 二分法响应: {}", response);
+                Ok(Self::llm_result(Self::parse_binary_response(&response), ai_service))
             }
+            Err(e) => Err(e),
         }
     }
 
-    /// 评分法检测：AI 给出 0-10 分，根据阈值判断
+    /// 评分法检测：AI 给出 0-10 分，根据阈值判断（AI 调用失败时返回错误）
     async fn check_score(
         ai_service: &AiService,
         text: &str,
         author: &str,
         email: &str,
         threshold: u8,
-    ) -> SpamCheckResult {
+    ) -> Result<SpamCheckResult, String> {
         let system_prompt = format!(
             r#"你是一个专业的垃圾评论检测助手。你的任务是对用户提交的评论进行评分，判断其垃圾程度。
 
@@ -295,13 +484,10 @@ impl SpamDetector {
 
         match ai_service.chat(messages, Some(0.3), None).await {
             Ok(response) => {
-                log::debug!("AI 评分法响应: {}", response);
-                Self::parse_score_response(&response, threshold)
-            }
-            Err(e) => {
-                log::error!("AI 检测失败: {}", e);
-                Self::pass_result()
+                tracing::debug!("AI 评分法响应: {}", response);
+                Ok(Self::llm_result(Self::parse_score_response(&response, threshold), ai_service))
             }
+            Err(e) => Err(e),
         }
     }
 
@@ -310,16 +496,17 @@ impl SpamDetector {
         // 尝试提取 JSON
         let json_str = Self::extract_json(response);
         
-        log::debug!("提取的 JSON: {}", json_str);
+        tracing::debug!("提取的 JSON: {}", json_str);
         
         match serde_json::from_str::<BinaryResponse>(&json_str) {
             Ok(result) => SpamCheckResult {
                 is_spam: result.is_spam,
                 confidence: if result.is_spam { 1.0 } else { 0.0 },
                 reason: Some(result.reason),
+                ..Default::default()
             },
             Err(e) => {
-                log::error!("解析 AI 响应失败: {}，原始响应: {}，提取的 JSON: {}", e, response, json_str);
+                tracing::error!("解析 AI 响应失败: {}，原始响应: {}，提取的 JSON: {}", e, response, json_str);
                 Self::pass_result()
             }
         }
@@ -340,10 +527,12 @@ impl SpamDetector {
                     is_spam,
                     confidence,
                     reason: Some(format!("评分: {}/10 - {}", score, result.reason)),
+                    score: Some(score as f64),
+                    ..Default::default()
                 }
             }
             Err(e) => {
-                log::error!("解析 AI 响应失败: {}，原始响应: {}", e, response);
+                tracing::error!("解析 AI 响应失败: {}，原始响应: {}", e, response);
                 Self::pass_result()
             }
         }
@@ -450,32 +639,45 @@ impl SpamDetector {
         cleaned
     }
 
-    /// 获取评论配置
-    async fn get_comment_options(db: &Database) -> Result<CommentOptions, String> {
-        let collection = db.collection::<RawOption>("options");
-
-        let option = collection
-            .find_one(doc! { "name": "commentOptions" })
-            .await
-            .map_err(|e| format!("数据库错误: {}", e))?
-            .ok_or_else(|| "评论配置不存在".to_string())?;
-
-        let doc = option
-            .value
-            .as_document()
-            .ok_or_else(|| "评论配置不是文档类型".to_string())?;
-
-        let comment_options: CommentOptions = bson::from_document(doc.clone())
-            .map_err(|e| format!("解析评论配置失败: {}", e))?;
-
-        Ok(comment_options)
+    /// 记下 AI 审核引擎和模型名（解析失败而放行的结果同样记录）
+    fn llm_result(result: SpamCheckResult, ai_service: &AiService) -> SpamCheckResult {
+        SpamCheckResult {
+            engine: Some(ModerationEngine::Llm),
+            model: Some(ai_service.config().model.clone()),
+            ..result
+        }
     }
 
     fn pass_result() -> SpamCheckResult {
-        SpamCheckResult {
-            is_spam: false,
-            confidence: 0.0,
-            reason: None,
-        }
+        SpamCheckResult::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moderation_record_keeps_engine_and_score() {
+        let result = SpamDetector::parse_score_response(r#"{"score": 7, "reason": "广告"}"#, 5);
+        assert!(result.is_spam);
+        assert_eq!(result.score, Some(7.0));
+        // 未标记引擎的结果不生成审核记录
+        assert!(result.moderation().is_none());
+
+        let result = SpamCheckResult {
+            engine: Some(ModerationEngine::Llm),
+            model: Some("gpt-4o-mini".to_string()),
+            ..result
+        };
+        let moderation = result.moderation().unwrap();
+        assert_eq!(moderation.engine, ModerationEngine::Llm);
+        assert_eq!(moderation.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(moderation.score, Some(7.0));
+        assert!(moderation.is_spam);
+        assert_eq!(moderation.reason.as_deref(), Some("评分: 7/10 - 广告"));
+        assert!(moderation.override_state.is_none());
+
+        assert!(SpamDetector::bayes_result(0.95).moderation().unwrap().is_spam);
     }
-}
\ No newline at end of file
+}
@@ -40,18 +40,18 @@ pub async fn verify_turnstile(token: &str, secret: &str) -> Result<bool, String>
                             .error_codes
                             .map(|codes| codes.join(", "))
                             .unwrap_or_else(|| "Unknown error".to_string());
-                        log::warn!("Turnstile verification failed: {}", error_msg);
+                        tracing::warn!("Turnstile verification failed: {}", error_msg);
                         Ok(false)
                     }
                 }
                 Err(e) => {
-                    log::error!("Failed to parse Turnstile response: {}", e);
+                    tracing::error!("Failed to parse Turnstile response: {}", e);
                     Err("验证服务响应解析失败".to_string())
                 }
             }
         }
         Err(e) => {
-            log::error!("Failed to verify Turnstile token: {}", e);
+            tracing::error!("Failed to verify Turnstile token: {}", e);
             Err("验证服务请求失败".to_string())
         }
     }
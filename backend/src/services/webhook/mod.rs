@@ -0,0 +1,4 @@
+//! Webhook - 将站点事件以签名 HTTP 请求推送给博主配置的地址
+
+pub mod service;
+pub mod worker;
@@ -0,0 +1,267 @@
+//! Webhook 订阅管理与事件入队
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, Database, IndexModel};
+use serde_json::json;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::models::{
+    Comment, CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery,
+    WebhookDeliveryStatus, WebhookEvent,
+};
+use crate::services::db_service::is_duplicate_key;
+
+/// 投递记录保留时长
+const DELIVERY_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// 新投递入队时唤醒投递任务
+pub(super) static DELIVERY_QUEUED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Webhook 服务
+pub struct WebhookService {
+    webhooks: Collection<Webhook>,
+    deliveries: Collection<WebhookDelivery>,
+}
+
+impl WebhookService {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            webhooks: db.collection::<Webhook>("webhooks"),
+            deliveries: db.collection::<WebhookDelivery>("webhook_deliveries"),
+        }
+    }
+
+    /// 创建投递队列与投递日志的索引（投递记录 30 天后过期）
+    pub async fn ensure_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let dedupe = IndexOptions::builder()
+            .unique(true)
+            .partial_filter_expression(doc! { "dedupeKey": { "$exists": true } })
+            .build();
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "webhook": 1, "dedupeKey": 1 })
+                .options(dedupe)
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "status": 1, "nextAttemptAt": 1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "webhook": 1, "created": -1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "created": 1 })
+                .options(IndexOptions::builder().expire_after(DELIVERY_RETENTION).build())
+                .build(),
+        ];
+        self.deliveries.create_indexes(indexes).await?;
+        Ok(())
+    }
+
+    /// 所有 Webhook（按创建时间排序）
+    pub async fn list(&self) -> Result<Vec<Webhook>, String> {
+        self.webhooks
+            .find(doc! {})
+            .sort(doc! { "created": 1 })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find(&self, id: ObjectId) -> Result<Option<Webhook>, String> {
+        self.webhooks
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// 创建 Webhook，未提供密钥时自动生成
+    pub async fn create(&self, request: CreateWebhookRequest) -> Result<Webhook, String> {
+        let webhook = Webhook {
+            id: ObjectId::new(),
+            url: request.url,
+            events: request.events,
+            secret: request
+                .secret
+                .filter(|secret| !secret.is_empty())
+                .unwrap_or_else(generate_secret),
+            enabled: request.enabled.unwrap_or(true),
+            created: DateTime::now(),
+            updated_at: None,
+        };
+        self.webhooks
+            .insert_one(&webhook)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(webhook)
+    }
+
+    /// 更新 Webhook，返回更新后的记录（不存在时返回 None）
+    pub async fn update(
+        &self,
+        id: ObjectId,
+        request: UpdateWebhookRequest,
+    ) -> Result<Option<Webhook>, String> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(url) = request.url {
+            set.insert("url", url);
+        }
+        if let Some(events) = request.events {
+            let events: Vec<&str> = events.iter().map(|event| event.as_str()).collect();
+            set.insert("events", events);
+        }
+        if let Some(enabled) = request.enabled {
+            set.insert("enabled", enabled);
+        }
+
+        self.webhooks
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set })
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// 删除 Webhook 及其投递记录
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        let result = self
+            .webhooks
+            .delete_one(doc! { "_id": id })
+            .await
+            .map_err(|e| e.to_string())?;
+        self.deliveries
+            .delete_many(doc! { "webhook": id })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count > 0)
+    }
+
+    /// 分页查询 Webhook 的投递记录（最新的在前），返回记录和总数
+    pub async fn deliveries(
+        &self,
+        webhook: ObjectId,
+        page: u64,
+        size: i64,
+    ) -> Result<(Vec<WebhookDelivery>, u64), String> {
+        let filter = doc! { "webhook": webhook };
+        let total = self
+            .deliveries
+            .count_documents(filter.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        let items = self
+            .deliveries
+            .find(filter)
+            .sort(doc! { "created": -1, "_id": -1 })
+            .skip((page - 1) * size as u64)
+            .limit(size)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((items, total))
+    }
+
+    /// 为订阅了该事件的 Webhook 创建投递任务；失败只记录日志
+    pub async fn emit(&self, event: WebhookEvent, data: serde_json::Value) {
+        if let Err(e) = self.enqueue(event, &data, None).await {
+            tracing::error!("Webhook 事件 {} 入队失败: {}", event.as_str(), e);
+        }
+    }
+
+    /// 同 [`Self::emit`]，但相同 `dedupe_key` 的事件对每个 Webhook 只投递一次
+    ///
+    /// 用于 Change Stream 事件：每个副本都会收到同一事件，以事件的 resume token 去重
+    pub async fn emit_once(&self, event: WebhookEvent, data: serde_json::Value, dedupe_key: &str) {
+        if let Err(e) = self.enqueue(event, &data, Some(dedupe_key)).await {
+            tracing::error!("Webhook 事件 {} 入队失败: {}", event.as_str(), e);
+        }
+    }
+
+    async fn enqueue(
+        &self,
+        event: WebhookEvent,
+        data: &serde_json::Value,
+        dedupe_key: Option<&str>,
+    ) -> Result<(), String> {
+        let webhooks: Vec<Webhook> = self
+            .webhooks
+            .find(doc! { "enabled": true, "events": event.as_str() })
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+        if webhooks.is_empty() {
+            return Ok(());
+        }
+
+        let now = DateTime::now();
+        let payload = data.to_string();
+        let deliveries: Vec<Document> = webhooks
+            .iter()
+            .map(|webhook| {
+                let mut delivery = doc! {
+                    "webhook": webhook.id,
+                    "event": event.as_str(),
+                    "payload": &payload,
+                    "status": WebhookDeliveryStatus::PENDING,
+                    "attempts": 0,
+                    "nextAttemptAt": now,
+                    "created": now,
+                };
+                if let Some(key) = dedupe_key {
+                    delivery.insert("dedupeKey", key);
+                }
+                delivery
+            })
+            .collect();
+
+        // 逐条插入，其他副本已入队的投递（唯一索引冲突）跳过
+        let deliveries_collection = self.deliveries.clone_with_type::<Document>();
+        let mut queued = 0;
+        for delivery in deliveries {
+            match deliveries_collection.insert_one(delivery).await {
+                Ok(_) => queued += 1,
+                Err(e) if is_duplicate_key(&e) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        if queued == 0 {
+            return Ok(());
+        }
+
+        tracing::info!("Webhook 事件 {} 已入队 ({} 个订阅)", event.as_str(), queued);
+        DELIVERY_QUEUED.notify_one();
+        Ok(())
+    }
+}
+
+/// 生成随机签名密钥（64 位十六进制）
+pub fn generate_secret() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// 评论事件的数据（不含邮箱和 IP）
+pub fn comment_payload(comment: &Comment) -> serde_json::Value {
+    json!({
+        "id": comment.id.map(|id| id.to_hex()),
+        "ref": comment.r#ref.to_hex(),
+        "refType": comment.ref_type,
+        "parent": comment.parent.map(|id| id.to_hex()),
+        "author": comment.author,
+        "text": comment.text,
+        "state": comment.state,
+        "isWhispers": comment.is_whispers,
+        "created": comment.created.to_chrono().to_rfc3339(),
+    })
+}
@@ -0,0 +1,237 @@
+//! Webhook 投递任务
+//!
+//! `webhook_deliveries` 集合即投递队列：任务领取投递时把 `nextAttemptAt` 推后一个租期，
+//! 进程在投递途中退出时，租期结束后会被重新领取。失败按指数退避重试，超过次数上限后标记为失败。
+
+use mongodb::bson::{doc, DateTime};
+use mongodb::options::ReturnDocument;
+use mongodb::{Collection, Database};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::service::DELIVERY_QUEUED;
+use crate::models::{Webhook, WebhookDelivery, WebhookDeliveryStatus};
+use crate::services::metrics::metrics;
+use crate::utils::hmac::hmac_sha256_hex;
+
+/// 最大尝试次数
+pub const MAX_ATTEMPTS: i32 = 8;
+/// 领取投递后的租期
+const LEASE: Duration = Duration::from_secs(60);
+/// 没有新投递入队时的轮询间隔（等待重试的投递按此间隔被发现）
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// 首次重试的等待时间，之后每次翻倍
+const BASE_BACKOFF: Duration = Duration::from_secs(30);
+/// 重试等待时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+/// 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// 投递日志中保留的响应内容长度
+const MAX_ERROR_LEN: usize = 500;
+
+/// 第 `attempts` 次尝试失败后的重试等待时间
+pub fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    BASE_BACKOFF.saturating_mul(1 << exponent).min(MAX_BACKOFF)
+}
+
+/// 请求签名：`sha256=` + HMAC-SHA256(secret, "<timestamp>.<body>")
+pub fn signature(secret: &str, timestamp: u64, body: &str) -> String {
+    format!("sha256={}", hmac_sha256_hex(secret, &format!("{}.{}", timestamp, body)))
+}
+
+/// 持续投递到期的 Webhook（在后台任务中运行，不会返回）
+pub async fn run_delivery_worker(db: Database) {
+    let worker = DeliveryWorker::new(&db);
+
+    loop {
+        loop {
+            match worker.claim().await {
+                Ok(Some(delivery)) => worker.deliver(delivery).await,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("领取 Webhook 投递失败: {}", e);
+                    break;
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = DELIVERY_QUEUED.notified() => {}
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+struct DeliveryWorker {
+    webhooks: Collection<Webhook>,
+    deliveries: Collection<WebhookDelivery>,
+    client: reqwest::Client,
+}
+
+impl DeliveryWorker {
+    fn new(db: &Database) -> Self {
+        Self {
+            webhooks: db.collection::<Webhook>("webhooks"),
+            deliveries: db.collection::<WebhookDelivery>("webhook_deliveries"),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent("neo-space-webhook")
+                .build()
+                .expect("Failed to build webhook HTTP client"),
+        }
+    }
+
+    /// 领取一个到期的投递，并占用一个租期
+    async fn claim(&self) -> Result<Option<WebhookDelivery>, mongodb::error::Error> {
+        let now = DateTime::now();
+        let lease_until = DateTime::from_millis(now.timestamp_millis() + LEASE.as_millis() as i64);
+        self.deliveries
+            .find_one_and_update(
+                doc! {
+                    "status": WebhookDeliveryStatus::PENDING,
+                    "nextAttemptAt": { "$lte": now },
+                },
+                doc! {
+                    "$set": { "nextAttemptAt": lease_until },
+                    "$inc": { "attempts": 1 },
+                },
+            )
+            .sort(doc! { "nextAttemptAt": 1 })
+            .return_document(ReturnDocument::After)
+            .await
+    }
+
+    async fn deliver(&self, delivery: WebhookDelivery) {
+        let webhook = match self.webhooks.find_one(doc! { "_id": delivery.webhook }).await {
+            Ok(webhook) => webhook,
+            Err(e) => {
+                // 数据库异常时保留投递，租期结束后重试
+                tracing::error!("读取 Webhook {} 失败: {}", delivery.webhook, e);
+                return;
+            }
+        };
+
+        let started = Instant::now();
+        let result = match webhook.as_ref().filter(|webhook| webhook.enabled) {
+            Some(webhook) => self.send(webhook, &delivery).await,
+            None => Err((None, "Webhook 已删除或已停用".to_string())),
+        };
+        let duration_ms = started.elapsed().as_millis() as i64;
+
+        let update = match &result {
+            Ok(status) => doc! {
+                "$set": {
+                    "status": WebhookDeliveryStatus::SUCCEEDED,
+                    "responseStatus": *status,
+                    "durationMs": duration_ms,
+                    "deliveredAt": DateTime::now(),
+                },
+                "$unset": { "lastError": "" },
+            },
+            Err((status, error)) => {
+                let exhausted = delivery.attempts >= MAX_ATTEMPTS || webhook.is_none();
+                let next_attempt_at = DateTime::from_millis(
+                    DateTime::now().timestamp_millis() + backoff(delivery.attempts).as_millis() as i64,
+                );
+                doc! {
+                    "$set": {
+                        "status": if exhausted { WebhookDeliveryStatus::FAILED } else { WebhookDeliveryStatus::PENDING },
+                        "responseStatus": *status,
+                        "lastError": error,
+                        "durationMs": duration_ms,
+                        "nextAttemptAt": next_attempt_at,
+                    },
+                }
+            }
+        };
+
+        match &result {
+            Ok(_) => tracing::info!(
+                "Webhook {} 投递成功 ({}, 第 {} 次尝试)",
+                delivery.id,
+                delivery.event.as_str(),
+                delivery.attempts
+            ),
+            Err((_, error)) => tracing::warn!(
+                "Webhook {} 投递失败 ({}, 第 {} 次尝试): {}",
+                delivery.id,
+                delivery.event.as_str(),
+                delivery.attempts,
+                error
+            ),
+        }
+        metrics().observe_webhook_delivery(delivery.event.as_str(), result.is_ok());
+
+        if let Err(e) = self.deliveries.update_one(doc! { "_id": delivery.id }, update).await {
+            tracing::error!("更新 Webhook 投递记录 {} 失败: {}", delivery.id, e);
+        }
+    }
+
+    /// 发送签名请求，成功返回响应状态码，失败返回（状态码, 错误信息）
+    async fn send(
+        &self,
+        webhook: &Webhook,
+        delivery: &WebhookDelivery,
+    ) -> Result<i32, (Option<i32>, String)> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let data: serde_json::Value =
+            serde_json::from_str(&delivery.payload).unwrap_or(serde_json::Value::Null);
+        let body = serde_json::json!({
+            "id": delivery.id.to_hex(),
+            "event": delivery.event,
+            "timestamp": timestamp,
+            "data": data,
+        })
+        .to_string();
+
+        let response = self
+            .client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Id", delivery.id.to_hex())
+            .header("X-Webhook-Event", delivery.event.as_str())
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", signature(&webhook.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(status.as_u16() as i32)
+        } else {
+            let text = response.text().await.unwrap_or_default();
+            let text: String = text.chars().take(MAX_ERROR_LEN).collect();
+            Err((Some(status.as_u16() as i32), format!("HTTP {}: {}", status, text)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        assert_eq!(backoff(1), Duration::from_secs(30));
+        assert_eq!(backoff(2), Duration::from_secs(60));
+        assert_eq!(backoff(4), Duration::from_secs(240));
+        assert_eq!(backoff(MAX_ATTEMPTS), Duration::from_secs(30 * 128));
+        assert_eq!(backoff(40), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let sig = signature("secret", 1700000000, r#"{"event":"comment.created"}"#);
+        assert_eq!(
+            sig,
+            format!("sha256={}", hmac_sha256_hex("secret", r#"1700000000.{"event":"comment.created"}"#))
+        );
+        assert_ne!(sig, signature("secret", 1700000001, r#"{"event":"comment.created"}"#));
+        assert_ne!(sig, signature("other", 1700000000, r#"{"event":"comment.created"}"#));
+    }
+}
@@ -0,0 +1,13 @@
+//! HMAC utilities - 出站请求签名
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 计算 HMAC-SHA256 签名（十六进制）
+pub fn hmac_sha256_hex(secret: &str, message: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -0,0 +1,55 @@
+//! IP 网段 - 单个地址或 CIDR（如 `10.0.0.0/8`、`2001:db8::/32`）
+
+use std::net::IpAddr;
+
+/// IP 网段；单个地址视为 `/32`（IPv6 为 `/128`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNetwork {
+    network: IpAddr,
+    prefix: u32,
+}
+
+impl IpNetwork {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let (network, prefix) = match value.split_once('/') {
+            Some((network, prefix)) => {
+                let prefix = prefix
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid prefix length in \"{}\"", value))?;
+                (network, Some(prefix))
+            }
+            None => (value, None),
+        };
+        let network = network
+            .parse::<IpAddr>()
+            .map_err(|_| format!("invalid IP address \"{}\"", value))?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(bits);
+        if prefix > bits {
+            return Err(format!("prefix length of \"{}\" exceeds {}", value, bits));
+        }
+        Ok(Self { network, prefix })
+    }
+
+    /// 地址是否属于该网段（IPv4 与 IPv6 互不匹配）
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                prefix_matches(u32::from(network).into(), u32::from(ip).into(), self.prefix, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                prefix_matches(u128::from(network), u128::from(ip), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, ip: u128, prefix: u32, bits: u32) -> bool {
+    if prefix == 0 {
+        return true;
+    }
+    let shift = bits - prefix;
+    (network >> shift) == (ip >> shift)
+}
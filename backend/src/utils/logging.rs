@@ -0,0 +1,31 @@
+//! Logging - tracing subscriber 初始化
+//!
+//! - `RUST_LOG`: 日志过滤规则（默认 `info`）
+//! - `log.format`（`LOG_FORMAT`）: `text`（默认，适合本地开发）或 `json`（适合日志收集）
+//!
+//! Rocket 及依赖库通过 `log` 门面输出的日志也会被转发到 tracing。
+
+use tracing_subscriber::EnvFilter;
+
+use crate::config::LogFormat;
+
+/// 初始化全局日志
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let result = if format == LogFormat::Json {
+        builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .try_init()
+    } else {
+        builder.try_init()
+    };
+
+    if let Err(e) = result {
+        eprintln!("日志系统初始化失败: {}", e);
+    }
+}
@@ -2,6 +2,10 @@
 
 pub mod serializers;
 pub mod jwt;
+pub mod logging;
+pub mod request_id;
+pub mod hmac;
+pub mod ip_network;
 
 #[allow(unused)]
-pub use jwt::{generate_jwt, verify_jwt, JwtError};
\ No newline at end of file
+pub use jwt::{generate_jwt, verify_jwt, JwtError};
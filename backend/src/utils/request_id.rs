@@ -0,0 +1,65 @@
+//! Request ID utilities - 请求 ID 的生成、校验与传递
+//!
+//! 请求 ID 在路由处理期间保存在 task-local 中，`ApiResponse` 的失败响应会自动带上它。
+
+use std::future::Future;
+
+/// 请求 ID 响应头（也接受客户端/网关传入的同名请求头）
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// 客户端传入请求 ID 的最大长度
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// 生成新的请求 ID
+pub fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// 校验客户端传入的请求 ID，防止超长或包含控制字符的值污染日志
+pub fn sanitize(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+
+    valid.then_some(value)
+}
+
+/// 在请求 ID 作用域内执行 future
+pub async fn scope<F: Future>(request_id: String, f: F) -> F::Output {
+    REQUEST_ID.scope(request_id, f).await
+}
+
+/// 当前请求的 ID（在路由处理函数之外调用时返回 None）
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("abc-123"), Some("abc-123"));
+        assert_eq!(sanitize("  trace:1.2_3 "), Some("trace:1.2_3"));
+        assert_eq!(sanitize(""), None);
+        assert_eq!(sanitize("bad id"), None);
+        assert_eq!(sanitize("line\nbreak"), None);
+        assert_eq!(sanitize(&"a".repeat(MAX_REQUEST_ID_LEN + 1)), None);
+    }
+
+    #[tokio::test]
+    async fn test_current_is_scoped() {
+        assert_eq!(current(), None);
+        let inside = scope("req-1".to_string(), async { current() }).await;
+        assert_eq!(inside, Some("req-1".to_string()));
+        assert_eq!(current(), None);
+    }
+}
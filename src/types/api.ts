@@ -300,11 +300,27 @@ export interface Comment {
 	parent?: string;
 	/** 用户代理信息（浏览器/系统） */
 	ua?: UAInfo;
+	/** 可见回复总数（仅列表中的根评论） */
+	replyCount?: number;
+	/** 加载剩余回复的游标（仅在还有未内联的回复时出现） */
+	repliesCursor?: string;
 }
 
 export interface CommentListResponse {
+	/** 本页根评论，每个根评论内联最早的若干条回复 */
 	comments: Comment[];
+	/** 可见评论总数 */
 	count: number;
+	/** 根评论分页信息 */
+	pagination: Pagination;
+}
+
+export interface CommentRepliesResponse {
+	comments: Comment[];
+	/** 该评论下的可见回复总数 */
+	count: number;
+	/** 下一批的游标，没有更多时为空 */
+	nextCursor: string | null;
 }
 
 export interface CreateCommentRequest {